@@ -0,0 +1,213 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use minifb::{Key, Window, WindowOptions};
+
+pub trait Backend {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize);
+    fn poll_input(&mut self);
+    fn is_open(&self) -> bool;
+    fn is_key_down(&self, key: Key) -> bool;
+    fn set_frame_rate(&mut self, frame_time: Option<Duration>);
+    // Lets callers get the concrete backend back out of a Box<dyn Backend>.
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+pub struct MinifbBackend {
+    pub window: Window,
+}
+
+impl MinifbBackend {
+    pub fn new(title: &str, width: usize, height: usize) -> MinifbBackend {
+        MinifbBackend {
+            window: Window::new(
+                title,
+                width,
+                height,
+                WindowOptions::default()
+            ).unwrap_or_else(|e| {
+                panic!("{}", e)
+            }),
+        }
+    }
+}
+
+impl Backend for MinifbBackend {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        self.window.update_with_buffer(buffer, width, height).unwrap();
+    }
+
+    fn poll_input(&mut self) {
+        self.window.update();
+    }
+
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(key)
+    }
+
+    fn set_frame_rate(&mut self, frame_time: Option<Duration>) {
+        self.window.limit_update_rate(frame_time);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// Keeps only the most recent presented frame by default; raise max_stored_frames, or set it
+// to None to keep every frame.
+pub struct HeadlessBackend {
+    pub frames: Vec<Vec<u32>>,
+    pub max_stored_frames: Option<usize>,
+    pub frame_count: usize,
+    pub frame_time: Option<Duration>,
+    pub keys_down: Vec<Key>,
+    pub input_script: VecDeque<Vec<Key>>,
+    pub close_after: Option<usize>,
+    pub open: bool,
+}
+
+impl Default for HeadlessBackend {
+    fn default() -> Self {
+        HeadlessBackend::new()
+    }
+}
+
+impl HeadlessBackend {
+    pub fn new() -> HeadlessBackend {
+        HeadlessBackend {
+            frames: Vec::new(),
+            max_stored_frames: Some(1),
+            frame_count: 0,
+            frame_time: None,
+            keys_down: Vec::new(),
+            input_script: VecDeque::new(),
+            close_after: None,
+            open: true,
+        }
+    }
+
+    // Each entry is the set of keys held down for one frame; presenting a frame advances the script.
+    pub fn script_input(&mut self, frames: Vec<Vec<Key>>) {
+        self.input_script.extend(frames);
+    }
+
+    pub fn close_after(&mut self, frames: usize) {
+        self.close_after = Some(frames);
+        if self.frame_count >= frames {
+            self.open = false;
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn press_key(&mut self, key: Key) {
+        if !self.keys_down.contains(&key) {
+            self.keys_down.push(key);
+        }
+    }
+
+    pub fn release_key(&mut self, key: Key) {
+        self.keys_down.retain(|k| *k != key);
+    }
+
+    pub fn last_frame(&self) -> Option<&Vec<u32>> {
+        self.frames.last()
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        if buffer.len() < width * height {
+            panic!("Buffer is smaller than {}x{}", width, height);
+        }
+        self.frames.push(buffer[..width * height].to_vec());
+        if let Some(max) = self.max_stored_frames {
+            if self.frames.len() > max {
+                let excess = self.frames.len() - max;
+                self.frames.drain(0..excess);
+            }
+        }
+        self.frame_count += 1;
+        self.input_script.pop_front();
+        self.poll_input();
+    }
+
+    fn poll_input(&mut self) {
+        if let Some(frames) = self.close_after {
+            if self.frame_count >= frames {
+                self.open = false;
+            }
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key) || self.input_script.front().is_some_and(|keys| keys.contains(&key))
+    }
+
+    fn set_frame_rate(&mut self, frame_time: Option<Duration>) {
+        self.frame_time = frame_time;
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_keeps_only_the_last_frame_by_default() {
+        let mut backend = HeadlessBackend::new();
+        for value in 0..5 {
+            backend.present(&[value; 4], 2, 2);
+        }
+        assert_eq!(backend.frames.len(), 1);
+        assert_eq!(backend.last_frame(), Some(&vec![4; 4]));
+        assert_eq!(backend.frame_count, 5);
+    }
+
+    #[test]
+    fn headless_closes_after_frame_count() {
+        let mut backend = HeadlessBackend::new();
+        backend.close_after(2);
+        backend.present(&[0; 1], 1, 1);
+        assert!(backend.is_open());
+        backend.present(&[0; 1], 1, 1);
+        assert!(!backend.is_open());
+    }
+
+    #[test]
+    fn headless_script_advances_per_frame() {
+        let mut backend = HeadlessBackend::new();
+        backend.script_input(vec![vec![Key::Left], vec![Key::Right]]);
+        assert!(backend.is_key_down(Key::Left));
+        backend.present(&[0; 1], 1, 1);
+        assert!(!backend.is_key_down(Key::Left));
+        assert!(backend.is_key_down(Key::Right));
+        backend.present(&[0; 1], 1, 1);
+        assert!(!backend.is_key_down(Key::Right));
+    }
+}
@@ -0,0 +1,184 @@
+// Pixels are packed non-premultiplied 0xAARRGGBB.
+
+pub const TRANSPARENT: u32 = 0x00000000;
+pub const BLACK: u32 = 0xFF000000;
+pub const WHITE: u32 = 0xFFFFFFFF;
+
+pub fn argb(a: u8, r: u8, g: u8, b: u8) -> u32 {
+    u32::from_be_bytes([a, r, g, b])
+}
+
+// (a, r, g, b)
+pub fn channels(color: u32) -> (u8, u8, u8, u8) {
+    let [a, r, g, b] = color.to_be_bytes();
+    (a, r, g, b)
+}
+
+pub fn alpha(color: u32) -> u8 {
+    (color >> 24) as u8
+}
+
+pub fn with_alpha(color: u32, alpha: u8) -> u32 {
+    (color & 0x00FFFFFF) | ((alpha as u32) << 24)
+}
+
+// Scales every channel, alpha included, by the matching channel of `tint`, so WHITE leaves
+// a colour unchanged.
+pub fn tint(color: u32, tint: u32) -> u32 {
+    let (a, r, g, b) = channels(color);
+    let (ta, tr, tg, tb) = channels(tint);
+    let scale = |c: u8, t: u8| ((c as u32 * t as u32 + 127) / 255) as u8;
+    argb(scale(a, ta), scale(r, tr), scale(g, tg), scale(b, tb))
+}
+
+pub fn opaque(rgb: u32) -> u32 {
+    rgb | BLACK
+}
+
+pub fn source_over(dst: u32, src: u32) -> u32 {
+    match alpha(src) {
+        255 => src,
+        0 => dst,
+        a => composite(dst, src, 1.0, 1.0 - a as f32 / 255.0),
+    }
+}
+
+// General Porter-Duff operator: result = src * Fa + dst * Fb in premultiplied space,
+// where `src_factor` and `dst_factor` are Fa and Fb. A result with zero alpha is TRANSPARENT.
+pub fn composite(dst: u32, src: u32, src_factor: f32, dst_factor: f32) -> u32 {
+    let (sa, sr, sg, sb) = channels(src);
+    let (da, dr, dg, db) = channels(dst);
+    let src_weight = sa as f32 / 255.0 * src_factor;
+    let dst_weight = da as f32 / 255.0 * dst_factor;
+    let out_alpha = (src_weight + dst_weight).min(1.0);
+    if out_alpha <= 0.0 {
+        return TRANSPARENT;
+    }
+    let channel = |s: u8, d: u8| ((s as f32 * src_weight + d as f32 * dst_weight) / out_alpha).round().min(255.0) as u8;
+    argb(
+        (out_alpha * 255.0).round() as u8,
+        channel(sr, dr),
+        channel(sg, dg),
+        channel(sb, db)
+    )
+}
+
+// Separable blend: `mix` combines each destination and source channel, and the mixed colour
+// is then composited source-over, so translucent pixels only partly take on the effect.
+pub fn separable<F: Fn(u8, u8) -> u8>(dst: u32, src: u32, mix: F) -> u32 {
+    let (sa, sr, sg, sb) = channels(src);
+    let (da, dr, dg, db) = channels(dst);
+    if sa == 0 {
+        return dst;
+    }
+    let channel = |s: u8, d: u8| {
+        let mixed = mix(d, s) as u32;
+        (((255 - da as u32) * s as u32 + da as u32 * mixed + 127) / 255) as u8
+    };
+    source_over(dst, argb(sa, channel(sr, dr), channel(sg, dg), channel(sb, db)))
+}
+
+pub fn add(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| d.saturating_add(s))
+}
+
+pub fn subtract(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| d.saturating_sub(s))
+}
+
+pub fn multiply(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| ((d as u32 * s as u32 + 127) / 255) as u8)
+}
+
+pub fn screen(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| 255 - (((255 - d as u32) * (255 - s as u32) + 127) / 255) as u8)
+}
+
+pub fn darken(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| d.min(s))
+}
+
+pub fn lighten(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| d.max(s))
+}
+
+pub fn difference(dst: u32, src: u32) -> u32 {
+    separable(dst, src, |d, s| d.abs_diff(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_saturates_each_channel_on_its_own() {
+        assert_eq!(add(0xFFF00000, 0xFF200000), 0xFFFF0000);
+        // A full blue channel must not carry into green.
+        assert_eq!(add(0xFFF01080, 0xFF2010A0), 0xFFFF20FF);
+        assert_eq!(add(0xFF000000, 0xFF123456), 0xFF123456);
+    }
+
+    #[test]
+    fn subtract_floors_at_zero() {
+        assert_eq!(subtract(0xFF102030, 0xFF201010), 0xFF001020);
+        // Red going below zero must not borrow from alpha.
+        assert_eq!(subtract(0xFF000000, 0xFFFFFFFF), 0xFF000000);
+    }
+
+    #[test]
+    fn blend_mode_values() {
+        assert_eq!(multiply(0xFF80FF00, 0xFF8080FF), 0xFF408000);
+        assert_eq!(screen(0xFF800000, 0xFF80FF00), 0xFFC0FF00);
+        assert_eq!(darken(0xFF40C080, 0xFF8080FF), 0xFF408080);
+        assert_eq!(lighten(0xFF40C080, 0xFF8080FF), 0xFF80C0FF);
+        assert_eq!(difference(0xFF40C080, 0xFF8080FF), 0xFF40407F);
+        assert_eq!(multiply(0xFF123456, WHITE), 0xFF123456);
+        assert_eq!(screen(0xFF123456, BLACK), 0xFF123456);
+    }
+
+    #[test]
+    fn translucent_blends_only_partly_apply() {
+        // A transparent source changes nothing.
+        assert_eq!(add(0xFF808080, 0x00FFFFFF), 0xFF808080);
+        // Over a transparent destination the source is drawn as it is.
+        assert_eq!(add(TRANSPARENT, 0x80FF0000), 0x80FF0000);
+        // Half the saturated red, half the original.
+        assert_eq!(add(0xFF800000, 0x80FF0000), 0xFFC00000);
+        assert_eq!(subtract(0xFF800000, 0x80FF0000), 0xFF400000);
+    }
+
+    #[test]
+    fn separable_passes_destination_then_source() {
+        assert_eq!(separable(0xFF102030, 0xFF405060, |d, _| d), 0xFF102030);
+        assert_eq!(separable(0xFF102030, 0xFF405060, |_, s| s), 0xFF405060);
+    }
+
+    #[test]
+    fn source_over_opaque_destination() {
+        assert_eq!(source_over(0xFF0000FF, 0x80FF0000), 0xFF80007F);
+        assert_eq!(source_over(0xFF0000FF, 0xFFFF0000), 0xFFFF0000);
+        assert_eq!(source_over(0xFF0000FF, 0x00FF0000), 0xFF0000FF);
+    }
+
+    #[test]
+    fn source_over_transparent_destination() {
+        // The colour is kept rather than darkened towards the transparent black.
+        assert_eq!(source_over(TRANSPARENT, 0x80FF0000), 0x80FF0000);
+        assert_eq!(source_over(0x0000FF00, 0x40123456), 0x40123456);
+    }
+
+    #[test]
+    fn source_over_translucent_destination() {
+        // alpha = 0.5 + 0.5 * 0.5, with each colour weighted by its share of it.
+        assert_eq!(source_over(0x800000FF, 0x80FF0000), 0xC0AA0055);
+    }
+
+    #[test]
+    fn composite_factors() {
+        assert_eq!(composite(0xFF0000FF, 0xFFFF0000, 0.0, 0.0), TRANSPARENT);
+        assert_eq!(composite(0xFF0000FF, 0xFFFF0000, 1.0, 0.0), 0xFFFF0000);
+        assert_eq!(composite(0xFF0000FF, 0xFFFF0000, 0.0, 1.0), 0xFF0000FF);
+        // The sum is clamped to full alpha.
+        assert_eq!(composite(0xFF0000FF, 0xFFFF0000, 1.0, 1.0), 0xFFFF00FF);
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+pub mod bmfont;
+pub mod builtin;
+pub mod error;
+pub mod truetype;
+
+pub use builtin::builtin;
+pub use error::FontError;
+pub use truetype::TrueTypeFont;
+
+// An alpha mask drawn with its top-left at (pen x + offset_x, line top + offset_y).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Glyph {
+    pub width: u32,
+    pub height: u32,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub advance: i32,
+    pub coverage: Vec<u8>,
+}
+
+impl Glyph {
+    pub fn coverage_at(&self, x: u32, y: u32) -> u8 {
+        self.coverage[(y * self.width + x) as usize]
+    }
+}
+
+// `base` is the distance from the top of a line to the baseline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BitmapFont {
+    pub line_height: u32,
+    pub base: u32,
+    pub glyphs: HashMap<char, Glyph>,
+    pub kerning: HashMap<(char, char), i32>,
+}
+
+impl BitmapFont {
+    pub fn new(line_height: u32, base: u32) -> BitmapFont {
+        BitmapFont {
+            line_height,
+            base,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new()
+        }
+    }
+
+    pub fn add_glyph(&mut self, character: char, glyph: Glyph) {
+        self.glyphs.insert(character, glyph);
+    }
+
+    // Characters the font lacks are drawn as '?' when it has one.
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character).or_else(|| self.glyphs.get(&'?'))
+    }
+}
+
+impl Font for BitmapFont {
+    fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    fn advance(&self, character: char) -> Option<i32> {
+        self.glyph(character).map(|glyph| glyph.advance)
+    }
+
+    fn kerning(&self, first: char, second: char) -> i32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0)
+    }
+
+    fn with_glyph(&self, character: char, f: &mut dyn FnMut(&Glyph)) {
+        if let Some(glyph) = self.glyph(character) {
+            f(glyph);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// Lines wider than max_width are wrapped at spaces, or between characters when a single
+// word does not fit. Lines are aligned within max_width, or within the widest line. Layout
+// happens in font pixels; scale only applies when the text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextLayout {
+    pub max_width: Option<u32>,
+    pub align: Align,
+    pub scale: u32,
+}
+
+impl Default for TextLayout {
+    fn default() -> TextLayout {
+        TextLayout::new(None, Align::Left)
+    }
+}
+
+impl TextLayout {
+    pub fn new(max_width: Option<u32>, align: Align) -> TextLayout {
+        TextLayout {
+            max_width,
+            align,
+            scale: 1
+        }
+    }
+}
+
+// Anything text can be drawn with. Sizes are in font pixels, before any scaling.
+pub trait Font {
+    // Distance from the top of one line to the top of the next.
+    fn line_height(&self) -> u32;
+
+    // None when the font has nothing to draw for the character.
+    fn advance(&self, character: char) -> Option<i32>;
+
+    fn kerning(&self, first: char, second: char) -> i32;
+
+    // Glyphs may be built on demand, so they are lent to `f` rather than returned.
+    fn with_glyph(&self, character: char, f: &mut dyn FnMut(&Glyph));
+
+    // Calls f(x, y, glyph) with each glyph's top-left, relative to the top-left of the text.
+    // Lines break at '\n' and wherever the layout wraps them.
+    fn layout(&self, text: &str, layout: &TextLayout, f: &mut dyn FnMut(i32, i32, &Glyph)) {
+        let lines = wrap_lines(self, text, layout.max_width);
+        let block_width = block_width(self, &lines, layout.max_width);
+        for (row, line) in lines.iter().enumerate() {
+            let y = (row as u32 * self.line_height()) as i32;
+            let (positions, width) = pen_positions(self, line);
+            let x = match layout.align {
+                Align::Left => 0,
+                Align::Center => (block_width - width) / 2,
+                Align::Right => block_width - width,
+            };
+            for (pen, character) in positions {
+                self.with_glyph(character, &mut |glyph| f(x + pen + glyph.offset_x, y + glyph.offset_y, glyph));
+            }
+        }
+    }
+
+    // The width lines are aligned within, and the height of every line.
+    fn measure(&self, text: &str, layout: &TextLayout) -> (u32, u32) {
+        let lines = wrap_lines(self, text, layout.max_width);
+        let width = block_width(self, &lines, layout.max_width);
+        (width.max(0) as u32, lines.len() as u32 * self.line_height())
+    }
+}
+
+// The pen position of every drawable character on a line, and where the pen ends up.
+fn pen_positions<F: Font + ?Sized>(font: &F, line: &str) -> (Vec<(i32, char)>, i32) {
+    let mut positions = Vec::new();
+    let mut pen = 0;
+    let mut previous = None;
+    for character in line.chars() {
+        let Some(advance) = font.advance(character) else {
+            continue;
+        };
+        if let Some(previous) = previous {
+            pen += font.kerning(previous, character);
+        }
+        positions.push((pen, character));
+        pen += advance;
+        previous = Some(character);
+    }
+    (positions, pen)
+}
+
+fn line_width<F: Font + ?Sized>(font: &F, line: &str) -> i32 {
+    pen_positions(font, line).1
+}
+
+fn block_width<F: Font + ?Sized>(font: &F, lines: &[String], max_width: Option<u32>) -> i32 {
+    match max_width {
+        Some(width) => width as i32,
+        None => lines.iter().map(|line| line_width(font, line)).max().unwrap_or(0),
+    }
+}
+
+// Greedy wrapping: each line takes as many words as fit, and the spaces a line breaks at
+// are dropped. Every line keeps at least one character so wrapping always finishes.
+fn wrap_lines<F: Font + ?Sized>(font: &F, text: &str, max_width: Option<u32>) -> Vec<String> {
+    let Some(max_width) = max_width else {
+        return text.split('\n').map(str::to_string).collect();
+    };
+    let max_width = max_width as i32;
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if line_width(font, &candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for character in word.chars() {
+                line.push(character);
+                if line.chars().count() > 1 && line_width(font, &line) > max_width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, character.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Letters are 3 pixels apart and spaces 2; 'b' is drawn one right and two down.
+    fn font() -> BitmapFont {
+        let glyph = |advance, offset_x, offset_y| Glyph {
+            width: 1,
+            height: 1,
+            offset_x,
+            offset_y,
+            advance,
+            coverage: vec![255]
+        };
+        let mut font = BitmapFont::new(5, 4);
+        font.add_glyph('a', glyph(3, 0, 0));
+        font.add_glyph('b', glyph(3, 1, 2));
+        font.add_glyph(' ', glyph(2, 0, 0));
+        font
+    }
+
+    fn wrap(text: &str, max_width: u32) -> Vec<String> {
+        wrap_lines(&font(), text, Some(max_width))
+    }
+
+    fn positions(text: &str, layout: TextLayout) -> Vec<(i32, i32)> {
+        let mut positions = Vec::new();
+        font().layout(text, &layout, &mut |x, y, _| positions.push((x, y)));
+        positions
+    }
+
+    #[test]
+    fn wraps_greedily_at_spaces() {
+        // "aa ab" is 14 wide and "aa ab aa" 22.
+        assert_eq!(wrap("aa ab aa", 14), vec!["aa ab", "aa"]);
+        assert_eq!(wrap("aa ab aa", 13), vec!["aa", "ab", "aa"]);
+        assert_eq!(wrap("aa ab aa", 22), vec!["aa ab aa"]);
+        assert_eq!(wrap("a\n\naa aa", 6), vec!["a", "", "aa", "aa"]);
+        assert_eq!(wrap_lines(&font(), "aa ab aa", None), vec!["aa ab aa"]);
+    }
+
+    #[test]
+    fn breaks_long_words_between_characters() {
+        assert_eq!(wrap("aaaaa", 7), vec!["aa", "aa", "a"]);
+        assert_eq!(wrap("a aaaa", 7), vec!["a", "aa", "aa"]);
+        // Every line keeps a character, even when none fits.
+        assert_eq!(wrap("aab", 1), vec!["a", "a", "b"]);
+    }
+
+    #[test]
+    fn lines_are_aligned_within_the_block() {
+        let layout = |max_width, align| TextLayout::new(max_width, align);
+        // Both lines are laid out on the line height; the second line is 3 wide against 6.
+        assert_eq!(positions("aa\na", layout(None, Align::Left)), vec![(0, 0), (3, 0), (0, 5)]);
+        assert_eq!(positions("aa\na", layout(None, Align::Center)), vec![(0, 0), (3, 0), (1, 5)]);
+        assert_eq!(positions("aa\na", layout(None, Align::Right)), vec![(0, 0), (3, 0), (3, 5)]);
+
+        // With a max width the lines are aligned within it.
+        assert_eq!(positions("aa\na", layout(Some(10), Align::Center)), vec![(2, 0), (5, 0), (3, 5)]);
+        assert_eq!(positions("aa\na", layout(Some(10), Align::Right)), vec![(4, 0), (7, 0), (7, 5)]);
+        assert_eq!(positions("aa a", layout(Some(6), Align::Right)), vec![(0, 0), (3, 0), (3, 5)]);
+    }
+
+    #[test]
+    fn glyph_offsets_and_kerning() {
+        let mut font = font();
+        assert_eq!(positions("ab", TextLayout::default()), vec![(0, 0), (4, 2)]);
+        font.kerning.insert(('a', 'b'), -1);
+        let mut kerned = Vec::new();
+        font.layout("ab", &TextLayout::default(), &mut |x, y, _| kerned.push((x, y)));
+        assert_eq!(kerned, vec![(0, 0), (3, 2)]);
+        assert_eq!(font.measure("ab", &TextLayout::default()), (5, 5));
+    }
+
+    #[test]
+    fn measures_the_wrapped_block() {
+        let font = font();
+        assert_eq!(font.measure("aa ab aa", &TextLayout::default()), (22, 5));
+        assert_eq!(font.measure("aa ab aa", &TextLayout::new(Some(14), Align::Left)), (14, 10));
+        assert_eq!(font.measure("aa\na", &TextLayout::default()), (6, 10));
+        assert_eq!(font.measure("", &TextLayout::default()), (0, 5));
+        // Characters the font cannot draw take no room.
+        assert_eq!(font.measure("a\u{e9}a", &TextLayout::default()), (6, 5));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::engine::color;
+use crate::engine::font::{BitmapFont, FontError, Glyph};
+use crate::engine::image::{ppm, Image};
+
+impl BitmapFont {
+    // AngelCode BMFont in its text format. Page images are found next to the .fnt file.
+    pub fn load_bmfont<P: AsRef<Path>>(path: P) -> Result<BitmapFont, FontError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut pages = Vec::new();
+        for file in page_files(&text)? {
+            pages.push(Image::load(directory.join(file))?);
+        }
+        BitmapFont::parse_bmfont(&text, &pages)
+    }
+
+    // `pages` are indexed by page id. Glyph coverage comes from the page's alpha, or from its
+    // brightness when the page is opaque, so white-on-black exports work as well.
+    pub fn parse_bmfont(text: &str, pages: &[Image]) -> Result<BitmapFont, FontError> {
+        let mut font = BitmapFont::new(0, 0);
+        let opaque: Vec<bool> = pages.iter().map(|page| page.is_opaque()).collect();
+        for line in text.lines() {
+            let (tag, fields) = parse_line(line)?;
+            match tag {
+                "common" => {
+                    font.line_height = positive(&fields, "lineHeight")?;
+                    font.base = positive(&fields, "base")?;
+                }
+                "char" => {
+                    let id = number(&fields, "id")?;
+                    let Some(character) = char::from_u32(id as u32) else {
+                        continue;
+                    };
+                    let page_id = number_or(&fields, "page", 0)? as usize;
+                    let page = pages.get(page_id)
+                        .ok_or_else(|| FontError::Parse(format!("char {} is on missing page {}", id, page_id)))?;
+                    let (x, y) = (number(&fields, "x")?, number(&fields, "y")?);
+                    let (width, height) = (number(&fields, "width")?, number(&fields, "height")?);
+                    let outside = || FontError::Parse(format!("char {} lies outside its page", id));
+                    let right = x.checked_add(width).ok_or_else(outside)?;
+                    let bottom = y.checked_add(height).ok_or_else(outside)?;
+                    if x < 0 || y < 0 || width < 0 || height < 0 || right as usize > page.width || bottom as usize > page.height {
+                        return Err(outside());
+                    }
+                    let mut coverage = Vec::with_capacity((width * height) as usize);
+                    for row in y..y + height {
+                        for col in x..x + width {
+                            let pixel = page.get(row as usize, col as usize);
+                            coverage.push(if opaque[page_id] { ppm::luma(pixel) } else { color::alpha(pixel) });
+                        }
+                    }
+                    font.add_glyph(character, Glyph {
+                        width: width as u32,
+                        height: height as u32,
+                        offset_x: number(&fields, "xoffset")?,
+                        offset_y: number(&fields, "yoffset")?,
+                        advance: number(&fields, "xadvance")?,
+                        coverage
+                    });
+                }
+                "kerning" => {
+                    let first = char::from_u32(number(&fields, "first")? as u32);
+                    let second = char::from_u32(number(&fields, "second")? as u32);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        font.kerning.insert((first, second), number(&fields, "amount")?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if font.line_height == 0 {
+            return Err(FontError::Parse("missing common line".to_string()));
+        }
+        Ok(font)
+    }
+}
+
+// Page ids must run from 0 up to one less than the common line's page count.
+fn page_files(text: &str) -> Result<Vec<String>, FontError> {
+    let mut count = None;
+    let mut pages = Vec::new();
+    for line in text.lines() {
+        let (tag, fields) = parse_line(line)?;
+        match tag {
+            "common" => count = Some(number(&fields, "pages")?),
+            "page" => {
+                let count = count.ok_or_else(|| FontError::Parse("page before the common line".to_string()))?;
+                let id = number(&fields, "id")?;
+                if id < 0 || id >= count {
+                    return Err(FontError::Parse(format!("page id {} is not below the page count {}", id, count)));
+                }
+                let file = fields.get("file").ok_or_else(|| FontError::Parse("page without a file".to_string()))?;
+                pages.push((id as usize, file.clone()));
+            }
+            _ => {}
+        }
+    }
+    pages.sort_by_key(|(id, _)| *id);
+    let contiguous = pages.iter().enumerate().all(|(index, (id, _))| index == *id);
+    if !contiguous || count != Some(pages.len() as i32) {
+        return Err(FontError::Parse("page ids do not match the page count".to_string()));
+    }
+    Ok(pages.into_iter().map(|(_, file)| file).collect())
+}
+
+// A line is a tag followed by key=value pairs; values may be quoted and contain spaces.
+fn parse_line(line: &str) -> Result<(&str, HashMap<&str, String>), FontError> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mut fields = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let (key, after) = rest.split_once('=')
+            .ok_or_else(|| FontError::Parse(format!("expected key=value in '{}'", line)))?;
+        let (value, after) = if let Some(quoted) = after.strip_prefix('"') {
+            quoted.split_once('"').ok_or_else(|| FontError::Parse(format!("unterminated quote in '{}'", line)))?
+        } else {
+            after.split_once(char::is_whitespace).unwrap_or((after, ""))
+        };
+        fields.insert(key, value.to_string());
+        rest = after;
+    }
+    Ok((tag, fields))
+}
+
+fn number(fields: &HashMap<&str, String>, key: &str) -> Result<i32, FontError> {
+    let value = fields.get(key).ok_or_else(|| FontError::Parse(format!("missing {}", key)))?;
+    value.parse().map_err(|_| FontError::Parse(format!("{} is not a number: {}", key, value)))
+}
+
+fn positive(fields: &HashMap<&str, String>, key: &str) -> Result<u32, FontError> {
+    let value = number(fields, key)?;
+    if value <= 0 {
+        return Err(FontError::Parse(format!("{} must be positive: {}", key, value)));
+    }
+    Ok(value as u32)
+}
+
+fn number_or(fields: &HashMap<&str, String>, key: &str, default: i32) -> Result<i32, FontError> {
+    if fields.contains_key(key) {
+        number(fields, key)
+    } else {
+        Ok(default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::font::Font;
+
+    const COMMON: &str = "common lineHeight=10 base=8 scaleW=4 scaleH=4 pages=1";
+
+    fn page() -> Image {
+        let mut page = Image::new(4, 4);
+        page.set(1, 2, color::WHITE);
+        page
+    }
+
+    fn parse(lines: &[&str]) -> Result<BitmapFont, FontError> {
+        BitmapFont::parse_bmfont(&lines.join("\n"), &[page()])
+    }
+
+    #[test]
+    fn parses_chars_and_kerning() {
+        let font = parse(&[
+            "info face=\"Test Font\" size=8",
+            COMMON,
+            "char id=65 x=1 y=0 width=3 height=2 xoffset=1 yoffset=2 xadvance=5 page=0",
+            "kerning first=65 second=65 amount=-1",
+        ]).unwrap();
+        assert_eq!((font.line_height, font.base), (10, 8));
+        let glyph = font.glyph('A').unwrap();
+        assert_eq!((glyph.width, glyph.height, glyph.offset_x, glyph.offset_y, glyph.advance), (3, 2, 1, 2, 5));
+        assert_eq!(glyph.coverage, vec![0, 0, 0, 0, 255, 0]);
+        assert_eq!(font.kerning('A', 'A'), -1);
+    }
+
+    #[test]
+    fn rejects_chars_outside_the_page() {
+        for char_line in [
+            "char id=65 x=2 y=0 width=3 height=1 xoffset=0 yoffset=0 xadvance=1",
+            "char id=65 x=-1 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1",
+            "char id=65 x=2147483647 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1",
+            "char id=65 x=0 y=1 width=1 height=2147483647 xoffset=0 yoffset=0 xadvance=1",
+            "char id=65 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1 page=1",
+        ] {
+            assert!(matches!(parse(&[COMMON, char_line]), Err(FontError::Parse(_))), "{}", char_line);
+        }
+    }
+
+    #[test]
+    fn rejects_non_positive_metrics() {
+        for common in ["common lineHeight=-1 base=8 pages=1", "common lineHeight=10 base=0 pages=1"] {
+            assert!(matches!(parse(&[common]), Err(FontError::Parse(_))), "{}", common);
+        }
+        assert!(matches!(parse(&["info size=8"]), Err(FontError::Parse(_))));
+    }
+
+    #[test]
+    fn page_ids_must_match_the_page_count() {
+        let files = page_files("common lineHeight=10 base=8 pages=2\npage id=1 file=\"b.png\"\npage id=0 file=\"a.png\"").unwrap();
+        assert_eq!(files, vec!["a.png".to_string(), "b.png".to_string()]);
+        for text in [
+            "common lineHeight=10 base=8 pages=1\npage id=-1 file=\"a.png\"",
+            "common lineHeight=10 base=8 pages=1\npage id=2000000000 file=\"a.png\"",
+            "common lineHeight=10 base=8 pages=2\npage id=0 file=\"a.png\"",
+            "common lineHeight=10 base=8 pages=2\npage id=0 file=\"a.png\"\npage id=0 file=\"b.png\"",
+            "page id=0 file=\"a.png\"",
+        ] {
+            assert!(matches!(page_files(text), Err(FontError::Parse(_))), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+use std::io;
+
+use crate::engine::image::ImageError;
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    Image(ImageError),
+    Parse(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "I/O error: {}", e),
+            FontError::Image(e) => write!(f, "Failed to load font page: {}", e),
+            FontError::Parse(message) => write!(f, "Failed to parse font: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FontError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FontError::Io(e) => Some(e),
+            FontError::Image(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> FontError {
+        FontError::Io(e)
+    }
+}
+
+impl From<ImageError> for FontError {
+    fn from(e: ImageError) -> FontError {
+        FontError::Image(e)
+    }
+}
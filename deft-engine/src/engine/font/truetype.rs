@@ -0,0 +1,332 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+
+use ttf_parser::gpos::{PairAdjustment, PositioningSubtable};
+use ttf_parser::{Face, GlyphId, OutlineBuilder, Tag};
+
+use crate::engine::font::{Font, FontError, Glyph};
+use crate::engine::image::raster::Rasterizer;
+use crate::engine::image::Path;
+use crate::linalg::Vec2;
+
+// A TrueType or OpenType font rendered at `size` pixels per em. Glyphs are rasterized
+// with antialiasing the first time they are drawn and cached after that. The font is fixed
+// once made; load it again for another size.
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    index: u32,
+    size: f32,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    glyphs: RefCell<HashMap<char, Rc<Glyph>>>,
+    kerning: RefCell<HashMap<(char, char), i32>>,
+}
+
+impl TrueTypeFont {
+    pub fn load<P: AsRef<std::path::Path>>(path: P, size: f32) -> Result<TrueTypeFont, FontError> {
+        TrueTypeFont::from_bytes(fs::read(path)?, 0, size)
+    }
+
+    // `index` picks a face from a font collection; plain font files only have face 0.
+    pub fn from_bytes(data: Vec<u8>, index: u32, size: f32) -> Result<TrueTypeFont, FontError> {
+        if !size.is_finite() || size <= 0.0 {
+            return Err(FontError::Parse(format!("invalid font size {}", size)));
+        }
+        let face = Face::parse(&data, index).map_err(|e| FontError::Parse(e.to_string()))?;
+        let scale = size / face.units_per_em() as f32;
+        let (ascent, descent, line_gap) = (face.ascender() as f32 * scale, face.descender() as f32 * scale, face.line_gap() as f32 * scale);
+        Ok(TrueTypeFont {
+            data,
+            index,
+            size,
+            ascent,
+            descent,
+            line_gap,
+            glyphs: RefCell::new(HashMap::new()),
+            kerning: RefCell::new(HashMap::new())
+        })
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    // Distances in pixels from the baseline, up for ascent and down (so negative) for descent.
+    pub fn ascent(&self) -> f32 {
+        self.ascent
+    }
+
+    pub fn descent(&self) -> f32 {
+        self.descent
+    }
+
+    pub fn line_gap(&self) -> f32 {
+        self.line_gap
+    }
+
+    // The data was checked when the font was made, so parsing it again cannot fail.
+    fn face(&self) -> Face<'_> {
+        Face::parse(&self.data, self.index).unwrap()
+    }
+
+    fn scale(&self, face: &Face) -> f32 {
+        self.size / face.units_per_em() as f32
+    }
+
+    // Characters the font lacks get its missing glyph, usually an empty box.
+    fn rasterize(&self, character: char) -> Glyph {
+        let face = self.face();
+        let scale = self.scale(&face);
+        let id = face.glyph_index(character).unwrap_or(GlyphId(0));
+        let advance = (face.glyph_hor_advance(id).unwrap_or(0) as f32 * scale).round() as i32;
+        let mut outline = GlyphOutline {
+            path: Path::new(),
+            scale
+        };
+        let Some(bounds) = face.outline_glyph(id, &mut outline) else {
+            return Glyph {
+                width: 0,
+                height: 0,
+                offset_x: 0,
+                offset_y: 0,
+                advance,
+                coverage: Vec::new()
+            };
+        };
+
+        // Font units point up from the baseline; the outline was flipped to point down.
+        let left = (bounds.x_min as f32 * scale).floor();
+        let top = (-bounds.y_max as f32 * scale).floor();
+        let width = ((bounds.x_max as f32 * scale).ceil() - left).max(1.0) as usize;
+        let height = ((-bounds.y_min as f32 * scale).ceil() - top).max(1.0) as usize;
+        outline.path.translate(Vec2::new(-left, -top));
+        let mut rasterizer = Rasterizer::new(width, height);
+        for contour in outline.path.contours() {
+            rasterizer.polygon(&contour);
+        }
+        let mut coverage = vec![0; width * height];
+        rasterizer.for_each_coverage(|x, y, value| coverage[y * width + x] = (value * 255.0).round() as u8);
+        Glyph {
+            width: width as u32,
+            height: height as u32,
+            offset_x: left as i32,
+            offset_y: self.ascent.round() as i32 + top as i32,
+            advance,
+            coverage
+        }
+    }
+
+    // The legacy kern table, then pair adjustments from the GPOS kern feature.
+    fn find_kerning(&self, first: char, second: char) -> i32 {
+        let face = self.face();
+        let (Some(left), Some(right)) = (face.glyph_index(first), face.glyph_index(second)) else {
+            return 0;
+        };
+        let mut amount = 0;
+        if let Some(kern) = face.tables().kern {
+            for subtable in kern.subtables {
+                if subtable.horizontal && !subtable.variable && !subtable.has_cross_stream {
+                    amount += subtable.glyphs_kerning(left, right).unwrap_or(0) as i32;
+                }
+            }
+        }
+        if let Some(gpos) = face.tables().gpos {
+            let mut lookups: Vec<u16> = gpos.features.into_iter()
+                .filter(|feature| feature.tag == Tag::from_bytes(b"kern"))
+                .flat_map(|feature| feature.lookup_indices)
+                .collect();
+            lookups.sort_unstable();
+            lookups.dedup();
+            for index in lookups {
+                let Some(lookup) = gpos.lookups.get(index) else {
+                    continue;
+                };
+                // Only the first subtable that covers the pair applies.
+                for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+                    if let PositioningSubtable::Pair(pair) = subtable {
+                        if let Some(adjustment) = pair_adjustment(&pair, left, right) {
+                            amount += adjustment;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        (amount as f32 * self.scale(&face)).round() as i32
+    }
+}
+
+impl Font for TrueTypeFont {
+    fn line_height(&self) -> u32 {
+        (self.ascent - self.descent + self.line_gap).round().max(1.0) as u32
+    }
+
+    fn advance(&self, character: char) -> Option<i32> {
+        let mut advance = None;
+        self.with_glyph(character, &mut |glyph| advance = Some(glyph.advance));
+        advance
+    }
+
+    fn kerning(&self, first: char, second: char) -> i32 {
+        if let Some(amount) = self.kerning.borrow().get(&(first, second)) {
+            return *amount;
+        }
+        let amount = self.find_kerning(first, second);
+        self.kerning.borrow_mut().insert((first, second), amount);
+        amount
+    }
+
+    // The cache is not borrowed while `f` runs, so `f` may use the font too.
+    fn with_glyph(&self, character: char, f: &mut dyn FnMut(&Glyph)) {
+        let cached = self.glyphs.borrow().get(&character).cloned();
+        let glyph = match cached {
+            Some(glyph) => glyph,
+            None => {
+                let glyph = Rc::new(self.rasterize(character));
+                self.glyphs.borrow_mut().insert(character, glyph.clone());
+                glyph
+            }
+        };
+        f(&glyph);
+    }
+}
+
+fn pair_adjustment(pair: &PairAdjustment, left: GlyphId, right: GlyphId) -> Option<i32> {
+    match pair {
+        PairAdjustment::Format1 { coverage, sets } => {
+            let records = sets.get(coverage.get(left)?)?.get(right)?;
+            Some(records.0.x_advance as i32)
+        }
+        PairAdjustment::Format2 { coverage, classes, matrix } => {
+            if !coverage.contains(left) {
+                return None;
+            }
+            let records = matrix.get((classes.0.get(left), classes.1.get(right)))?;
+            Some(records.0.x_advance as i32)
+        }
+    }
+}
+
+// Collects an outline as a Path in pixels, with y pointing down from the baseline.
+struct GlyphOutline {
+    path: Path,
+    scale: f32,
+}
+
+impl GlyphOutline {
+    fn point(&self, x: f32, y: f32) -> Vec2 {
+        Vec2::new(x * self.scale, -y * self.scale)
+    }
+}
+
+impl OutlineBuilder for GlyphOutline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to(self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.path.quad_to(self.point(x1, y1), self.point(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.path.curve_to(self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+    }
+
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Built by tests/fixtures/make_test_font.py, which lists its glyphs. 1000 units per em.
+    const FIXTURE: &[u8] = include_bytes!("../../../tests/fixtures/test-font.ttf");
+
+    fn font(size: f32) -> TrueTypeFont {
+        TrueTypeFont::from_bytes(FIXTURE.to_vec(), 0, size).unwrap()
+    }
+
+    #[test]
+    fn rejects_bad_sizes_and_data() {
+        assert!(matches!(TrueTypeFont::from_bytes(vec![1, 2, 3], 0, 12.0), Err(FontError::Parse(_))));
+        for size in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(TrueTypeFont::from_bytes(FIXTURE.to_vec(), 0, size).is_err());
+        }
+        assert!(TrueTypeFont::from_bytes(FIXTURE.to_vec(), 1, 12.0).is_err());
+        assert!(TrueTypeFont::from_bytes(FIXTURE[..FIXTURE.len() / 2].to_vec(), 0, 12.0).is_err());
+        assert!(matches!(TrueTypeFont::load("missing.ttf", 12.0), Err(FontError::Io(_))));
+    }
+
+    #[test]
+    fn loads_from_a_file() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/test-font.ttf");
+        let font = TrueTypeFont::load(path, 20.0).unwrap();
+        assert_eq!((font.data(), font.index(), font.size()), (FIXTURE, 0, 20.0));
+    }
+
+    #[test]
+    fn metrics_are_scaled_to_the_size() {
+        let font = font(20.0);
+        assert_eq!((font.ascent(), font.descent(), font.line_gap()), (16.0, -4.0, 0.0));
+        assert_eq!(font.line_height(), 20);
+        assert_eq!((font.advance('A'), font.advance('B'), font.advance(' ')), (Some(12), Some(10), Some(5)));
+        // Characters the font lacks use its empty .notdef glyph.
+        font.with_glyph('z', &mut |glyph| {
+            assert_eq!((glyph.width, glyph.height, glyph.advance), (0, 0, 10));
+        });
+    }
+
+    #[test]
+    fn glyphs_can_be_used_inside_with_glyph() {
+        let font = font(16.0);
+        let mut advances = Vec::new();
+        font.with_glyph('A', &mut |glyph| {
+            advances.push(glyph.advance);
+            advances.push(font.advance('B').unwrap());
+            font.with_glyph('A', &mut |again| advances.push(again.advance));
+        });
+        assert_eq!(advances, vec![10, 8, 10]);
+    }
+
+    #[test]
+    fn glyphs_have_coverage_and_pairs_kern() {
+        let font = font(32.0);
+        font.with_glyph('O', &mut |glyph| {
+            assert!(glyph.width > 0 && glyph.height > 0);
+            assert!(glyph.coverage.contains(&255));
+            assert_eq!(glyph.coverage_at(glyph.width / 2, glyph.height / 2), 0);
+        });
+        assert_eq!(font.kerning('A', 'V'), -3);
+        assert_eq!(font.kerning('V', 'A'), -3);
+        assert_eq!((font.kerning('A', 'A'), font.kerning('A', 'z')), (0, 0));
+        assert_eq!(font.line_height(), (font.ascent() - font.descent() + font.line_gap()).round() as u32);
+    }
+
+    // At 10 pixels per em, B is 4 by 7 pixels with its left edge half a pixel in.
+    #[test]
+    fn glyphs_sit_on_the_baseline() {
+        let font = font(10.0);
+        font.with_glyph('B', &mut |glyph| {
+            assert_eq!((glyph.width, glyph.height, glyph.offset_x, glyph.offset_y), (5, 7, 0, 1));
+            let row: Vec<u8> = (0..5).map(|x| glyph.coverage_at(x, 3)).collect();
+            assert_eq!(row, vec![128, 255, 255, 255, 128]);
+        });
+    }
+}
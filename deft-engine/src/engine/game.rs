@@ -1,10 +1,35 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::engine::color;
+use crate::engine::font;
 use crate::engine::image;
 use crate::engine::physics;
+use crate::engine::sprite;
+use crate::linalg::{Mat4, Quat, Vec2, Vec3};
 
+// Overlay is Porter-Duff source-over and Override is source (copy). Clear through Xor are
+// the remaining Porter-Duff operators; Addition through Difference are per-channel blends.
 pub enum DrawMode {
     Overlay,
     Override,
     Addition,
+    Subtract,
+    Multiply,
+    Screen,
+    Darken,
+    Lighten,
+    Difference,
+    Clear,
+    Destination,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
 }
 
 impl Copy for DrawMode {}
@@ -14,33 +39,153 @@ impl Clone for DrawMode {
     }
 }
 
+impl DrawMode {
+    pub fn blend(&self, dst: u32, src: u32) -> u32 {
+        let src_alpha = color::alpha(src) as f32 / 255.0;
+        let dst_alpha = color::alpha(dst) as f32 / 255.0;
+        let (src_factor, dst_factor) = match self {
+            DrawMode::Overlay => return color::source_over(dst, src),
+            DrawMode::Override => return src,
+            DrawMode::Addition => return color::add(dst, src),
+            DrawMode::Subtract => return color::subtract(dst, src),
+            DrawMode::Multiply => return color::multiply(dst, src),
+            DrawMode::Screen => return color::screen(dst, src),
+            DrawMode::Darken => return color::darken(dst, src),
+            DrawMode::Lighten => return color::lighten(dst, src),
+            DrawMode::Difference => return color::difference(dst, src),
+            DrawMode::Clear => return color::TRANSPARENT,
+            DrawMode::Destination => return dst,
+            DrawMode::DestinationOver => (1.0 - dst_alpha, 1.0),
+            DrawMode::SourceIn => (dst_alpha, 0.0),
+            DrawMode::DestinationIn => (0.0, src_alpha),
+            DrawMode::SourceOut => (1.0 - dst_alpha, 0.0),
+            DrawMode::DestinationOut => (0.0, 1.0 - src_alpha),
+            DrawMode::SourceAtop => (dst_alpha, 1.0 - src_alpha),
+            DrawMode::DestinationAtop => (1.0 - dst_alpha, src_alpha),
+            DrawMode::Xor => (1.0 - dst_alpha, 1.0 - src_alpha),
+        };
+        color::composite(dst, src, src_factor, dst_factor)
+    }
+}
+
+// Position, motion and placement shared by every game object. Each update moves coord by
+// velocity and then velocity by acceleration.
+#[derive(Debug, Clone, Copy)]
+pub struct Body {
+    pub coord: Vec3,
+    pub velocity: Vec3,
+    pub acceleration: Vec3,
+    pub transform: Mat4,
+    pub orientation: Quat,
+}
+
+impl Body {
+    pub fn new(coord: Vec3) -> Body {
+        Body {
+            coord,
+            velocity: Vec3::ZERO,
+            acceleration: Vec3::ZERO,
+            transform: Mat4::IDENTITY,
+            orientation: Quat::IDENTITY,
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.coord += self.velocity;
+        self.velocity += self.acceleration;
+    }
+}
+
 pub trait GameObjectCommon {
-    fn update(&mut self);
+    fn body(&self) -> &Body;
+    fn body_mut(&mut self) -> &mut Body;
     fn check_collision(&self, other: &dyn GameObjectCommon) -> bool;
-    fn coord(&self) -> (i32, i32, i32);
-    fn velocity(&self) -> (f32, f32, f32);
-    fn acceleration(&self) -> (f32, f32, f32);
     fn size(&self) -> (u32, u32, u32);
     fn collision(&self) -> &dyn physics::CollisionObjectCommon;
     fn image(&self) -> &image::Image;
     fn generate_image(&mut self);
     fn generate_image_hollow(&mut self);
-    fn move_to(&mut self, x: i32, y: i32, z: i32);
-    fn translate(&mut self, x: f32, y: f32, z: f32);
-    fn set_velocity(&mut self, x: f32, y: f32, z: f32);
-    fn add_velocity(&mut self, x: f32, y: f32, z: f32);
-    fn set_acceleration(&mut self, x: f32, y: f32, z: f32);
-    fn add_acceleration(&mut self, x: f32, y: f32, z: f32);
     fn mode(&self) -> &DrawMode;
     fn filled(&self) -> bool;
+    fn antialiased(&self) -> bool;
+    fn set_antialiased(&mut self, antialiased: bool);
+
+    // Called whenever coord changes, so the collision shape can follow.
+    fn moved(&mut self) {}
+
+    fn update(&mut self) {
+        self.body_mut().step();
+        self.moved();
+    }
+
+    fn coord(&self) -> Vec3 {
+        self.body().coord
+    }
+
+    fn velocity(&self) -> Vec3 {
+        self.body().velocity
+    }
+
+    fn acceleration(&self) -> Vec3 {
+        self.body().acceleration
+    }
+
+    fn move_to(&mut self, position: Vec3) {
+        self.body_mut().coord = position;
+        self.moved();
+    }
+
+    fn translate(&mut self, offset: Vec3) {
+        self.body_mut().coord += offset;
+        self.moved();
+    }
+
+    fn set_velocity(&mut self, velocity: Vec3) {
+        self.body_mut().velocity = velocity;
+    }
+
+    fn add_velocity(&mut self, velocity: Vec3) {
+        self.body_mut().velocity += velocity;
+    }
+
+    fn set_acceleration(&mut self, acceleration: Vec3) {
+        self.body_mut().acceleration = acceleration;
+    }
+
+    fn add_acceleration(&mut self, acceleration: Vec3) {
+        self.body_mut().acceleration += acceleration;
+    }
+
+    fn transform(&self) -> Mat4 {
+        self.body().transform
+    }
+
+    fn set_transform(&mut self, transform: Mat4) {
+        self.body_mut().transform = transform;
+    }
+
+    fn orientation(&self) -> Quat {
+        self.body().orientation
+    }
+
+    fn set_orientation(&mut self, orientation: Quat) {
+        self.body_mut().orientation = orientation;
+    }
+
+    fn rotate(&mut self, rotation: Quat) {
+        self.set_orientation((rotation * self.orientation()).normalize());
+    }
+
+    // Local transform (scale, shear) applied about the object's origin, then oriented and moved to coord.
+    fn model_matrix(&self) -> Mat4 {
+        Mat4::translation(self.coord()) * self.orientation().to_mat4() * self.transform()
+    }
 }
 
 
 
 pub struct Rect {
-    pub coord: (i32, i32, i32),
-    pub velocity: (f32, f32, f32),
-    pub acceleration: (f32, f32, f32),
+    pub body: Body,
     pub width: u32,
     pub height: u32,
     pub depth: u32,
@@ -49,33 +194,24 @@ pub struct Rect {
     pub color: u32,
     pub draw_mode: DrawMode,
     pub filled: bool,
+    pub antialiased: bool,
 }
 
 impl GameObjectCommon for Rect {
-    fn update(&mut self) {
-        self.coord.0 = (self.coord.0 as f32 + self.velocity.0) as i32;
-        self.coord.1 = (self.coord.1 as f32 + self.velocity.1) as i32;
-        self.coord.2 = (self.coord.2 as f32 + self.velocity.2) as i32;
-
-        self.velocity.0 += self.acceleration.0;
-        self.velocity.1 += self.acceleration.1;
-        self.velocity.2 += self.acceleration.2;
+    fn body(&self) -> &Body {
+        &self.body
     }
 
-    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
-        physics::check_collision(self.collision(), other.collision())
-    }
-
-    fn coord(&self) -> (i32, i32, i32) {
-        self.coord
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
     }
 
-    fn velocity(&self) -> (f32, f32, f32) {
-        self.velocity
+    fn moved(&mut self) {
+        self.collision.coord = self.body.coord;
     }
 
-    fn acceleration(&self) -> (f32, f32, f32) {
-        self.acceleration
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
     }
 
     fn size(&self) -> (u32, u32, u32) {
@@ -90,42 +226,6 @@ impl GameObjectCommon for Rect {
         &self.image
     }
 
-    fn move_to(&mut self, x: i32, y: i32, z: i32) {
-        self.coord.0 = x;
-        self.coord.1 = y;
-        self.coord.2 = z;
-    }
-
-    fn translate(&mut self, x: f32, y: f32, z: f32) {
-        self.coord.0 = (self.coord.0 as f32 + x) as i32;
-        self.coord.1 = (self.coord.1 as f32 + y) as i32;
-        self.coord.2 = (self.coord.2 as f32 + z) as i32;
-    }
-
-    fn set_velocity(&mut self, x: f32, y: f32, z: f32) {
-        self.velocity.0 = x;
-        self.velocity.1 = y;
-        self.velocity.2 = z;
-    }
-
-    fn add_velocity(&mut self, x: f32, y: f32, z: f32) {
-        self.velocity.0 += x;
-        self.velocity.1 += y;
-        self.velocity.2 += z;
-    }
-
-    fn set_acceleration(&mut self, x: f32, y: f32, z: f32) {
-        self.acceleration.0 = x;
-        self.acceleration.1 = y;
-        self.acceleration.2 = z;
-    }
-
-    fn add_acceleration(&mut self, x: f32, y: f32, z: f32) {
-        self.acceleration.0 += x;
-        self.acceleration.1 += y;
-        self.acceleration.2 += z;
-    }
-
     fn generate_image(&mut self) {
         for i in 0..self.width {
             for j in 0..self.height {
@@ -149,22 +249,28 @@ impl GameObjectCommon for Rect {
     }
 
     fn filled(&self) -> bool {
-        return self.filled;
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
     }
 }
 
 impl Rect {
-    pub fn new2d(x: i32, y: i32, width: u32, height: u32, color: u32, draw_mode: DrawMode, filled: bool) -> Rect {
+    pub fn new2d(x: f32, y: f32, width: u32, height: u32, color: u32, draw_mode: DrawMode, filled: bool) -> Rect {
         Rect {
-            coord: (x, y, 0),
-            velocity: (0.0, 0.0, 0.0),
-            acceleration: (0.0, 0.0, 0.0),
+            body: Body::new(Vec3::new(x, y, 0.0)),
             width,
             height,
             depth: 0,
             image: image::Image::new_filled(0x000000, width as usize, height as usize),
             collision: physics::RectCollision {
-                coord: (x, y, 0),
+                coord: Vec3::new(x, y, 0.0),
                 width,
                 height,
                 depth: 0
@@ -172,35 +278,276 @@ impl Rect {
             color,
             draw_mode,
             filled,
+            antialiased: false,
+        }
+    }
+}
+
+pub struct Circle {
+    pub body: Body,
+    pub radius: u32,
+    pub image: image::Image,
+    pub collision: physics::CircleCollision,
+    pub color: u32,
+    pub draw_mode: DrawMode,
+    pub filled: bool,
+    pub antialiased: bool,
+}
+
+impl GameObjectCommon for Circle {
+    fn body(&self) -> &Body {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn moved(&mut self) {
+        self.sync_collision();
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (2 * self.radius + 1, 2 * self.radius + 1, 0)
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        &self.collision
+    }
+
+    fn image(&self) -> &image::Image {
+        &self.image
+    }
+
+    fn generate_image(&mut self) {
+        let size = 2 * self.radius as usize + 1;
+        let r = self.radius as f32;
+        self.image = image::Image::new(size, size);
+        if self.antialiased {
+            self.image.fill_circle_aa(&Point::new(r + 0.5, r + 0.5, 0.0), r + 0.5, self.color);
+        } else {
+            self.image.fill_circle(&Point::new(r, r, 0.0), self.radius, self.color);
+        }
+    }
+
+    fn generate_image_hollow(&mut self) {
+        let size = 2 * self.radius as usize + 1;
+        let r = self.radius as f32;
+        self.image = image::Image::new(size, size);
+        if self.antialiased {
+            self.image.draw_circle_aa(&Point::new(r + 0.5, r + 0.5, 0.0), r, self.color);
+        } else {
+            self.image.draw_circle(&Point::new(r, r, 0.0), self.radius, self.color);
+        }
+    }
+
+    fn mode(&self) -> &DrawMode {
+        &self.draw_mode
+    }
+
+    fn filled(&self) -> bool {
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
+}
+
+impl Circle {
+    // `x` and `y` are the centre; coord is the top-left of the bounding square, like Rect.
+    pub fn new2d(x: f32, y: f32, radius: u32, color: u32, draw_mode: DrawMode, filled: bool) -> Circle {
+        let mut circle = Circle {
+            body: Body::new(Vec3::new(x - radius as f32, y - radius as f32, 0.0)),
+            radius,
+            image: image::Image::new(1, 1),
+            collision: physics::CircleCollision {
+                center: Vec3::ZERO,
+                radius: 0.0
+            },
+            color,
+            draw_mode,
+            filled,
+            antialiased: false,
+        };
+        circle.sync_collision();
+        circle
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.body.coord + Vec3::new(self.radius as f32, self.radius as f32, 0.0)
+    }
+
+    pub fn set_radius(&mut self, radius: u32) {
+        let center = self.center();
+        self.radius = radius;
+        self.body.coord = center - Vec3::new(radius as f32, radius as f32, 0.0);
+        self.sync_collision();
+    }
+
+    // The drawn disc spans whole pixels, so the shape reaches half a pixel past `radius`.
+    fn sync_collision(&mut self) {
+        let reach = self.radius as f32 + 0.5;
+        self.collision.center = self.body.coord + Vec3::new(reach, reach, 0.0);
+        self.collision.radius = reach;
+    }
+}
+
+pub struct Ellipse {
+    pub body: Body,
+    pub radius_x: u32,
+    pub radius_y: u32,
+    pub image: image::Image,
+    pub collision: physics::EllipseCollision,
+    pub color: u32,
+    pub draw_mode: DrawMode,
+    pub filled: bool,
+    pub antialiased: bool,
+}
+
+impl GameObjectCommon for Ellipse {
+    fn body(&self) -> &Body {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn moved(&mut self) {
+        self.sync_collision();
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (2 * self.radius_x + 1, 2 * self.radius_y + 1, 0)
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        &self.collision
+    }
+
+    fn image(&self) -> &image::Image {
+        &self.image
+    }
+
+    fn generate_image(&mut self) {
+        let (rx, ry) = (self.radius_x as f32, self.radius_y as f32);
+        self.image = image::Image::new(2 * self.radius_x as usize + 1, 2 * self.radius_y as usize + 1);
+        if self.antialiased {
+            self.image.fill_ellipse_aa(&Point::new(rx + 0.5, ry + 0.5, 0.0), rx + 0.5, ry + 0.5, self.color);
+        } else {
+            self.image.fill_ellipse(&Point::new(rx, ry, 0.0), self.radius_x, self.radius_y, self.color);
+        }
+    }
+
+    fn generate_image_hollow(&mut self) {
+        let (rx, ry) = (self.radius_x as f32, self.radius_y as f32);
+        self.image = image::Image::new(2 * self.radius_x as usize + 1, 2 * self.radius_y as usize + 1);
+        if self.antialiased {
+            self.image.draw_ellipse_aa(&Point::new(rx + 0.5, ry + 0.5, 0.0), rx, ry, self.color);
+        } else {
+            self.image.draw_ellipse(&Point::new(rx, ry, 0.0), self.radius_x, self.radius_y, self.color);
         }
     }
+
+    fn mode(&self) -> &DrawMode {
+        &self.draw_mode
+    }
+
+    fn filled(&self) -> bool {
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
+}
+
+impl Ellipse {
+    // `x` and `y` are the centre; coord is the top-left of the bounding box, like Rect.
+    pub fn new2d(x: f32, y: f32, radius_x: u32, radius_y: u32, color: u32, draw_mode: DrawMode, filled: bool) -> Ellipse {
+        let mut ellipse = Ellipse {
+            body: Body::new(Vec3::new(x - radius_x as f32, y - radius_y as f32, 0.0)),
+            radius_x,
+            radius_y,
+            image: image::Image::new(1, 1),
+            collision: physics::EllipseCollision {
+                center: Vec3::ZERO,
+                radius_x: 0.0,
+                radius_y: 0.0
+            },
+            color,
+            draw_mode,
+            filled,
+            antialiased: false,
+        };
+        ellipse.sync_collision();
+        ellipse
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.body.coord + Vec3::new(self.radius_x as f32, self.radius_y as f32, 0.0)
+    }
+
+    pub fn set_radii(&mut self, radius_x: u32, radius_y: u32) {
+        let center = self.center();
+        self.radius_x = radius_x;
+        self.radius_y = radius_y;
+        self.body.coord = center - Vec3::new(radius_x as f32, radius_y as f32, 0.0);
+        self.sync_collision();
+    }
+
+    fn sync_collision(&mut self) {
+        let reach = Vec3::new(self.radius_x as f32 + 0.5, self.radius_y as f32 + 0.5, 0.0);
+        self.collision.center = self.body.coord + reach;
+        self.collision.radius_x = reach.x;
+        self.collision.radius_y = reach.y;
+    }
 }
+
 pub struct Point {
-    pub coord: (i32, i32, i32),
-    pub velocity: (f32, f32, f32),
-    pub acceleration: (f32, f32, f32)
+    pub coord: Vec3,
+    pub velocity: Vec3,
+    pub acceleration: Vec3
 }
 
 impl Copy for Point {}
 
 impl Clone for Point {
     fn clone(&self) -> Point {
-        Point {
-            coord: self.coord,
-            velocity: self.velocity,
-            acceleration: self.acceleration
-        }
+        *self
     }
 }
 
 impl Point {
-    pub fn new(x: i32, y: i32, z: i32) -> Point {
+    pub fn new(x: f32, y: f32, z: f32) -> Point {
         Point {
-            coord: (x, y, z),
-            velocity: (0.0, 0.0, 0.0),
-            acceleration: (0.0, 0.0, 0.0)
+            coord: Vec3::new(x, y, z),
+            velocity: Vec3::ZERO,
+            acceleration: Vec3::ZERO
         }
     }
+
+    pub fn update(&mut self) {
+        self.coord += self.velocity;
+        self.velocity += self.acceleration;
+    }
 }
 
 pub struct Points {
@@ -215,6 +562,12 @@ impl Clone for Points {
     }
 }
 
+impl Default for Points {
+    fn default() -> Points {
+        Points::new()
+    }
+}
+
 impl Points {
     pub fn new() -> Points {
         Points {
@@ -226,75 +579,48 @@ impl Points {
         self.points.push(point);
     }
 
-    pub fn min_x(&self) -> i32 {
-        let mut min = self.points[0].coord.0;
+    pub fn min(&self) -> Vec3 {
+        let mut min = self.points[0].coord;
         for point in self.points.iter() {
-            if point.coord.0 < min {
-                min = point.coord.0;
-            }
+            min = min.min(point.coord);
         }
         min
     }
 
-    pub fn max_x(&self) -> i32 {
-        let mut max = self.points[0].coord.0;
+    pub fn max(&self) -> Vec3 {
+        let mut max = self.points[0].coord;
         for point in self.points.iter() {
-            if point.coord.0 > max {
-                max = point.coord.0;
-            }
+            max = max.max(point.coord);
         }
         max
     }
 
-    pub fn min_y(&self) -> i32 {
-        let mut min = self.points[0].coord.1;
-        for point in self.points.iter() {
-            if point.coord.1 < min {
-                min = point.coord.1;
-            }
-        }
-        min
+    pub fn min_x(&self) -> f32 {
+        self.min().x
     }
 
-    pub fn max_y(&self) -> i32 {
-        let mut max = self.points[0].coord.1;
-        for point in self.points.iter() {
-            if point.coord.1 > max {
-                max = point.coord.1;
-            }
-        }
-        max
+    pub fn max_x(&self) -> f32 {
+        self.max().x
     }
 
-    pub fn min_z(&self) -> i32 {
-        let mut min = self.points[0].coord.2;
-        for point in self.points.iter() {
-            if point.coord.2 < min {
-                min = point.coord.2;
-            }
-        }
-        min
+    pub fn min_y(&self) -> f32 {
+        self.min().y
     }
 
-    pub fn max_z(&self) -> i32 {
-        let mut max = self.points[0].coord.2;
-        for point in self.points.iter() {
-            if point.coord.2 > max {
-                max = point.coord.2;
-            }
-        }
-        max
+    pub fn max_y(&self) -> f32 {
+        self.max().y
+    }
+
+    pub fn min_z(&self) -> f32 {
+        self.min().z
     }
 
-    pub fn center(&self) -> (i32, i32, i32) {
-        let min_x = self.min_x();
-        let max_x = self.max_x();
-        let min_y = self.min_y();
-        let max_y = self.max_y();
-        let min_z = self.min_z();
-        let max_z = self.max_z();
+    pub fn max_z(&self) -> f32 {
+        self.max().z
+    }
 
-        ((min_x + max_x) / 2, (min_y + max_y) / 2, (min_z + max_z) / 2)
+    pub fn center(&self) -> Vec3 {
+        (self.min() + self.max()) / 2.0
     }
 
     pub fn width(&self) -> u32 {
@@ -309,8 +635,8 @@ impl Points {
         (self.max_z() - self.min_z()) as u32
     }
 
-    pub fn coord(&self) -> (i32, i32, i32) {
-        (self.min_x(), self.min_y(), self.min_z())
+    pub fn coord(&self) -> Vec3 {
+        self.min()
     }
 
     pub fn size(&self) -> (u32, u32, u32) {
@@ -318,60 +644,70 @@ impl Points {
     }
 }
 
+// Each point moves on its own, so only the body's transform and orientation are used.
 pub struct Polygon {
+    pub body: Body,
     pub points: Points,
+    pub holes: Vec<Points>,
+    pub fill_rule: image::FillRule,
+    pub stroke: Option<image::StrokeStyle>,
     pub image: image::Image,
     pub collision: physics::PolygonCollision,
     pub color: u32,
     pub draw_mode: DrawMode,
     pub filled: bool,
+    pub antialiased: bool,
 }
 
 impl GameObjectCommon for Polygon {
-    fn update(&mut self) {
-        for point in self.points.points.iter_mut() {
-            point.coord.0 = (point.coord.0 as f32 + point.velocity.0) as i32;
-            point.coord.1 = (point.coord.1 as f32 + point.velocity.1) as i32;
-            point.coord.2 = (point.coord.2 as f32 + point.velocity.2) as i32;
+    fn body(&self) -> &Body {
+        &self.body
+    }
 
-            point.velocity.0 += point.acceleration.0;
-            point.velocity.1 += point.acceleration.1;
-            point.velocity.2 += point.acceleration.2;
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn update(&mut self) {
+        for point in self.all_points_mut() {
+            point.update();
         }
+        self.sync_collision();
     }
 
-    fn coord(&self) -> (i32, i32, i32) {
-        self.points.coord()
+    fn coord(&self) -> Vec3 {
+        let padding = self.stroke_padding() as f32;
+        self.points.coord() - Vec3::new(padding, padding, 0.0)
     }
 
-    fn velocity(&self) -> (f32, f32, f32) {
-        // average velocity of points
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = 0.0;
+    fn velocity(&self) -> Vec3 {
+        // average velocity of points, zero when there are none
+        if self.points.points.is_empty() {
+            return Vec3::ZERO;
+        }
+        let mut sum = Vec3::ZERO;
         for point in self.points.points.iter() {
-            x += point.velocity.0;
-            y += point.velocity.1;
-            z += point.velocity.2;
+            sum += point.velocity;
         }
-        (x / self.points.points.len() as f32, y / self.points.points.len() as f32, z / self.points.points.len() as f32)
+        sum / self.points.points.len() as f32
     }
 
-    fn acceleration(&self) -> (f32, f32, f32) {
-        // average acceleration of points
-        let mut x = 0.0;
-        let mut y = 0.0;
-        let mut z = 0.0;
+    fn acceleration(&self) -> Vec3 {
+        // average acceleration of points, zero when there are none
+        if self.points.points.is_empty() {
+            return Vec3::ZERO;
+        }
+        let mut sum = Vec3::ZERO;
         for point in self.points.points.iter() {
-            x += point.acceleration.0;
-            y += point.acceleration.1;
-            z += point.acceleration.2;
+            sum += point.acceleration;
         }
-        (x / self.points.points.len() as f32, y / self.points.points.len() as f32, z / self.points.points.len() as f32)
+        sum / self.points.points.len() as f32
     }
 
     fn size(&self) -> (u32, u32, u32) {
-        self.points.size()
+        let (width, height, depth) = self.points.size();
+        let padding = self.stroke_padding();
+        (width + 2 * padding, height + 2 * padding, depth)
     }
 
     fn collision(&self) -> &dyn physics::CollisionObjectCommon {
@@ -386,97 +722,69 @@ impl GameObjectCommon for Polygon {
         &self.image
     }
 
-    fn move_to(&mut self, x: i32, y: i32, z: i32) {
-        let (min_x, min_y, min_z) = self.points.coord();
-        let x = x - min_x;
-        let y = y - min_y;
-        let z = z - min_z;
-        for point in self.points.points.iter_mut() {
-            point.coord.0 = point.coord.0 + x;
-            point.coord.1 = point.coord.1 + y;
-            point.coord.2 = point.coord.2 + z;
-        }
+    fn move_to(&mut self, position: Vec3) {
+        let offset = position - self.coord();
+        self.translate(offset);
     }
 
-    fn translate(&mut self, x: f32, y: f32, z: f32) {
-        for point in self.points.points.iter_mut() {
-            point.coord.0 = (point.coord.0 as f32 + x) as i32;
-            point.coord.1 = (point.coord.1 as f32 + y) as i32;
-            point.coord.2 = (point.coord.2 as f32 + z) as i32;
+    fn translate(&mut self, offset: Vec3) {
+        for point in self.all_points_mut() {
+            point.coord += offset;
         }
+        self.sync_collision();
     }
 
-    fn set_velocity(&mut self, x: f32, y: f32, z: f32) {
-        for point in self.points.points.iter_mut() {
-            point.velocity.0 = x;
-            point.velocity.1 = y;
-            point.velocity.2 = z;
+    fn set_velocity(&mut self, velocity: Vec3) {
+        for point in self.all_points_mut() {
+            point.velocity = velocity;
         }
     }
 
-    fn add_velocity(&mut self, x: f32, y: f32, z: f32) {
-        for point in self.points.points.iter_mut() {
-            point.velocity.0 += x;
-            point.velocity.1 += y;
-            point.velocity.2 += z;
+    fn add_velocity(&mut self, velocity: Vec3) {
+        for point in self.all_points_mut() {
+            point.velocity += velocity;
         }
     }
 
-    fn set_acceleration(&mut self, x: f32, y: f32, z: f32) {
-        for point in self.points.points.iter_mut() {
-            point.acceleration.0 = x;
-            point.acceleration.1 = y;
-            point.acceleration.2 = z;
+    fn set_acceleration(&mut self, acceleration: Vec3) {
+        for point in self.all_points_mut() {
+            point.acceleration = acceleration;
         }
     }
 
-    fn add_acceleration(&mut self, x: f32, y: f32, z: f32) {
-        for point in self.points.points.iter_mut() {
-            point.acceleration.0 += x;
-            point.acceleration.1 += y;
-            point.acceleration.2 += z;
+    fn add_acceleration(&mut self, acceleration: Vec3) {
+        for point in self.all_points_mut() {
+            point.acceleration += acceleration;
         }
     }
 
-
     fn generate_image(&mut self) {
-        let (min_x, min_y, _min_z) = self.points.coord();
-        let (width, height, _) = self.points.size();
-        self.image = image::Image::new_filled(0x000000, width as usize + 1, height as usize + 1);
-        let points = self.points();
-        let mut transformed_points = Vec::new();
-        for i in 0..points.len() {
-            let point1 = points[i];
-            let x1 = point1.coord.0 as i32 - min_x as i32;
-            let y1 = point1.coord.1 as i32 - min_y as i32;
-            let point = Point::new(x1 as i32, y1 as i32, 0);
-            transformed_points.push(point);
-        }
-
-        let mut new_polygon = Polygon::new2d(self.color, self.draw_mode, self.filled);
-        new_polygon.set_points(Points { points: transformed_points });
-
-        self.image.fill_convex_polygon(&new_polygon, self.color);
+        let local = self.local_copy();
+        self.image.fill_polygon(&local, self.color);
     }
 
     fn generate_image_hollow(&mut self) {
-        let (min_x, min_y, _min_z) = self.points.coord();
-        let (width, height, _) = self.points.size();
-        self.image = image::Image::new_filled(0x000000, width as usize + 1, height as usize + 1);
-        let points = self.points();
-        let mut transformed_points = Vec::new();
-        for i in 0..points.len() {
-            let point1 = points[i];
-            let x1 = point1.coord.0 as i32 - min_x as i32;
-            let y1 = point1.coord.1 as i32 - min_y as i32;
-            let point = Point::new(x1 as i32, y1 as i32, 0);
-            transformed_points.push(point);
+        let local = self.local_copy();
+        if let Some(stroke) = &self.stroke {
+            let outline = local.outline_path();
+            if self.antialiased {
+                self.image.stroke_path_aa(&outline, stroke, self.color);
+            } else {
+                self.image.stroke_path(&outline, stroke, self.color);
+            }
+            return;
         }
-
-        for i in 0..transformed_points.len() {
-            let point1 = &transformed_points[i];
-            let point2 = &transformed_points[(i + 1) % transformed_points.len()];
-            self.image.draw_line(point1, point2, self.color);
+        for contour in std::iter::once(&local.points).chain(local.holes.iter()) {
+            let points = &contour.points;
+            for i in 0..points.len() {
+                let point1 = &points[i];
+                let point2 = &points[(i + 1) % points.len()];
+                if self.antialiased {
+                    self.image.draw_line_aa(point1, point2, self.color);
+                } else {
+                    self.image.draw_line(point1, point2, self.color);
+                }
+            }
         }
     }
 
@@ -485,15 +793,26 @@ impl GameObjectCommon for Polygon {
     }
 
     fn filled(&self) -> bool {
-        return self.filled;
+        self.filled
     }
 
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
 }
 
 impl Polygon {
     pub fn new(color: u32, draw_mode: DrawMode, filled: bool) -> Polygon {
         Polygon {
+            body: Body::new(Vec3::ZERO),
             points: Points::new(),
+            holes: Vec::new(),
+            fill_rule: image::FillRule::NonZero,
+            stroke: None,
             image: image::Image::new(1, 1),
             collision: physics::PolygonCollision {
                 points: Points::new()
@@ -501,12 +820,17 @@ impl Polygon {
             color,
             draw_mode,
             filled,
+            antialiased: false,
         }
     }
 
     pub fn new2d(color: u32, draw_mode: DrawMode, filled: bool) -> Polygon {
         Polygon {
+            body: Body::new(Vec3::ZERO),
             points: Points::new(),
+            holes: Vec::new(),
+            fill_rule: image::FillRule::NonZero,
+            stroke: None,
             image: image::Image::new(1, 1),
             collision: physics::PolygonCollision {
                 points: Points::new()
@@ -514,6 +838,7 @@ impl Polygon {
             color,
             draw_mode,
             filled,
+            antialiased: false,
         }
     }
 
@@ -524,45 +849,1074 @@ impl Polygon {
 
     pub fn set_points(&mut self, points: Points) {
         self.points = points;
+        self.sync_collision();
     }
 
-    pub fn points(&self) -> Vec<&Point> {
-        let mut output = Vec::new();
-        for point in self.points.points.iter() {
-            output.push(point);
-        }
-        output
+    fn sync_collision(&mut self) {
+        self.collision.points = self.points.clone();
     }
-}
 
-impl Polygon {
-    pub fn translate_point(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        self.points.points[index].coord.0 = (self.points.points[index].coord.0 as f32 + x) as i32;
-        self.points.points[index].coord.1 = (self.points.points[index].coord.1 as f32 + y) as i32;
-        self.points.points[index].coord.2 = (self.points.points[index].coord.2 as f32 + z) as i32;
+    // Holes follow the polygon when it moves but take no part in collision.
+    pub fn add_hole(&mut self, hole: Points) {
+        self.holes.push(hole);
     }
 
-    pub fn set_velocity_point(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        self.points.points[index].velocity.0 = x;
-        self.points.points[index].velocity.1 = y;
-        self.points.points[index].velocity.2 = z;
+    pub fn set_fill_rule(&mut self, fill_rule: image::FillRule) {
+        self.fill_rule = fill_rule;
     }
 
-    pub fn add_velocity_point(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        self.points.points[index].velocity.0 += x;
-        self.points.points[index].velocity.1 += y;
-        self.points.points[index].velocity.2 += z;
+    // The outline followed by every hole. Holes are turned against the outline so they
+    // cut out under either fill rule, whichever way round their points were given.
+    pub fn contours(&self) -> Vec<Vec<Vec2>> {
+        let outline: Vec<Vec2> = self.points.points.iter().map(|point| Vec2::new(point.coord.x, point.coord.y)).collect();
+        let outline_area = image::fill::signed_area(&outline);
+        let mut contours = vec![outline];
+        for hole in self.holes.iter() {
+            let mut contour: Vec<Vec2> = hole.points.iter().map(|point| Vec2::new(point.coord.x, point.coord.y)).collect();
+            if image::fill::signed_area(&contour) * outline_area > 0.0 {
+                contour.reverse();
+            }
+            contours.push(contour);
+        }
+        contours
     }
 
-    pub fn set_acceleration_point(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        self.points.points[index].acceleration.0 = x;
-        self.points.points[index].acceleration.1 = y;
-        self.points.points[index].acceleration.2 = z;
+    // None draws the outline one pixel wide with draw_line.
+    pub fn set_stroke(&mut self, stroke: Option<image::StrokeStyle>) {
+        self.stroke = stroke;
     }
 
-    pub fn add_acceleration_point(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        self.points.points[index].acceleration.0 += x;
-        self.points.points[index].acceleration.1 += y;
-        self.points.points[index].acceleration.2 += z;
+    // Every contour as a closed path. Integer coordinates are pixel centres, as with draw_line.
+    pub fn outline_path(&self) -> image::Path {
+        let mut path = image::Path::new();
+        for contour in self.contours() {
+            for (i, point) in contour.iter().enumerate() {
+                let point = *point + Vec2::splat(0.5);
+                if i == 0 {
+                    path.move_to(point);
+                } else {
+                    path.line_to(point);
+                }
+            }
+            path.close();
+        }
+        path
     }
-}
\ No newline at end of file
+
+    // Room left around the points so a stroke fits in the image.
+    fn stroke_padding(&self) -> u32 {
+        self.stroke.as_ref().map_or(0, |stroke| stroke.reach().ceil() as u32 + 1)
+    }
+
+    fn all_points_mut(&mut self) -> impl Iterator<Item = &mut Point> {
+        self.points.points.iter_mut().chain(self.holes.iter_mut().flat_map(|hole| hole.points.iter_mut()))
+    }
+
+    // A copy moved into image coordinates, with a blank image sized for it and any stroke.
+    fn local_copy(&mut self) -> Polygon {
+        let min = self.coord();
+        let (width, height, _) = self.size();
+        self.image = image::Image::new_filled(0x000000, width as usize + 1, height as usize + 1);
+        let mut local = Polygon::new2d(self.color, self.draw_mode, self.filled);
+        local.set_points(self.points.clone());
+        local.holes = self.holes.clone();
+        local.fill_rule = self.fill_rule;
+        local.antialiased = self.antialiased;
+        local.translate(Vec3::new(-min.x, -min.y, 0.0));
+        local
+    }
+
+    pub fn points(&self) -> Vec<&Point> {
+        let mut output = Vec::new();
+        for point in self.points.points.iter() {
+            output.push(point);
+        }
+        output
+    }
+}
+
+impl Polygon {
+    pub fn translate_point(&mut self, index: usize, offset: Vec3) {
+        self.points.points[index].coord += offset;
+        self.sync_collision();
+    }
+
+    pub fn set_velocity_point(&mut self, index: usize, velocity: Vec3) {
+        self.points.points[index].velocity = velocity;
+    }
+
+    pub fn add_velocity_point(&mut self, index: usize, velocity: Vec3) {
+        self.points.points[index].velocity += velocity;
+    }
+
+    pub fn set_acceleration_point(&mut self, index: usize, acceleration: Vec3) {
+        self.points.points[index].acceleration = acceleration;
+    }
+
+    pub fn add_acceleration_point(&mut self, index: usize, acceleration: Vec3) {
+        self.points.points[index].acceleration += acceleration;
+    }
+}
+
+// A vector path drawn into its own image. The path is kept relative to coord, which is the
+// top-left of the image; filled paths use fill_rule and hollow ones are stroked.
+pub struct Path {
+    pub body: Body,
+    pub path: image::Path,
+    pub width: u32,
+    pub height: u32,
+    pub image: image::Image,
+    pub collision: physics::RectCollision,
+    pub color: u32,
+    pub draw_mode: DrawMode,
+    pub filled: bool,
+    pub antialiased: bool,
+    pub fill_rule: image::FillRule,
+    pub stroke: image::StrokeStyle,
+}
+
+impl GameObjectCommon for Path {
+    fn body(&self) -> &Body {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn moved(&mut self) {
+        self.collision.coord = self.body.coord;
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (self.width, self.height, 0)
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        &self.collision
+    }
+
+    fn image(&self) -> &image::Image {
+        &self.image
+    }
+
+    fn generate_image(&mut self) {
+        self.image = image::Image::new(self.width as usize, self.height as usize);
+        if self.antialiased {
+            self.image.fill_path_with_rule_aa(&self.path, self.fill_rule, self.color);
+        } else {
+            self.image.fill_path_with_rule(&self.path, self.fill_rule, self.color);
+        }
+    }
+
+    fn generate_image_hollow(&mut self) {
+        self.image = image::Image::new(self.width as usize, self.height as usize);
+        if self.antialiased {
+            self.image.stroke_path_aa(&self.path, &self.stroke, self.color);
+        } else {
+            self.image.stroke_path(&self.path, &self.stroke, self.color);
+        }
+    }
+
+    fn mode(&self) -> &DrawMode {
+        &self.draw_mode
+    }
+
+    fn filled(&self) -> bool {
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
+}
+
+impl Path {
+    // `path` is in world coordinates; the object is placed so it draws where the path lies.
+    pub fn new2d(path: image::Path, stroke: image::StrokeStyle, color: u32, draw_mode: DrawMode, filled: bool) -> Path {
+        let mut object = Path {
+            body: Body::new(Vec3::ZERO),
+            path,
+            width: 0,
+            height: 0,
+            image: image::Image::new(1, 1),
+            collision: physics::RectCollision::new(0.0, 0.0, 0.0, 0, 0, 0),
+            color,
+            draw_mode,
+            filled,
+            antialiased: false,
+            fill_rule: image::FillRule::NonZero,
+            stroke,
+        };
+        object.fit();
+        object
+    }
+
+    // Replaces the path, given in world coordinates like new2d.
+    pub fn set_path(&mut self, path: image::Path) {
+        self.path = path;
+        self.body.coord = Vec3::new(0.0, 0.0, self.body.coord.z);
+        self.fit();
+    }
+
+    pub fn set_stroke(&mut self, stroke: image::StrokeStyle) {
+        self.stroke = stroke;
+        self.fit();
+    }
+
+    pub fn set_fill_rule(&mut self, fill_rule: image::FillRule) {
+        self.fill_rule = fill_rule;
+    }
+
+    // Moves the path within the object so its bounds, widened by the stroke, start at the
+    // image origin, and shifts coord to match so nothing moves on screen.
+    fn fit(&mut self) {
+        let (min, max) = self.path.bounds().unwrap_or((Vec2::ZERO, Vec2::ZERO));
+        let reach = Vec2::splat(self.stroke.reach() + 1.0);
+        let origin = Vec2::new((min - reach).x.floor(), (min - reach).y.floor());
+        let end = max + reach;
+        self.path.translate(-origin);
+        self.body.coord += origin.extend(0.0);
+        self.width = (end.x - origin.x).ceil() as u32;
+        self.height = (end.y - origin.y).ceil() as u32;
+        self.collision = physics::RectCollision::new(self.body.coord.x, self.body.coord.y, self.body.coord.z, self.width, self.height, 0);
+    }
+}
+
+// A string drawn with any font. Its size is the measured text, so it can be laid out
+// against other objects; hollow and filled text look the same.
+pub struct Text {
+    pub body: Body,
+    pub text: String,
+    pub font: Rc<dyn font::Font>,
+    pub layout: font::TextLayout,
+    pub width: u32,
+    pub height: u32,
+    pub image: image::Image,
+    pub collision: physics::RectCollision,
+    pub color: u32,
+    pub draw_mode: DrawMode,
+    pub filled: bool,
+    pub antialiased: bool,
+}
+
+impl GameObjectCommon for Text {
+    fn body(&self) -> &Body {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn moved(&mut self) {
+        self.collision.coord = self.body.coord;
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (self.width, self.height, 0)
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        &self.collision
+    }
+
+    fn image(&self) -> &image::Image {
+        &self.image
+    }
+
+    fn generate_image(&mut self) {
+        self.image = image::Image::new(self.width.max(1) as usize, self.height.max(1) as usize);
+        self.image.draw_text_layout(0, 0, &self.text, self.font.as_ref(), &self.layout, self.color);
+    }
+
+    fn generate_image_hollow(&mut self) {
+        self.generate_image();
+    }
+
+    fn mode(&self) -> &DrawMode {
+        &self.draw_mode
+    }
+
+    fn filled(&self) -> bool {
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
+}
+
+impl Text {
+    pub fn new2d(x: f32, y: f32, text: &str, font: Rc<dyn font::Font>, color: u32, draw_mode: DrawMode) -> Text {
+        let mut object = Text {
+            body: Body::new(Vec3::new(x, y, 0.0)),
+            text: text.to_string(),
+            font,
+            layout: font::TextLayout::default(),
+            width: 0,
+            height: 0,
+            image: image::Image::new(1, 1),
+            collision: physics::RectCollision::new(x, y, 0.0, 0, 0, 0),
+            color,
+            draw_mode,
+            filled: true,
+            antialiased: false,
+        };
+        object.sync_size();
+        object
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.sync_size();
+    }
+
+    pub fn set_font(&mut self, font: Rc<dyn font::Font>) {
+        self.font = font;
+        self.sync_size();
+    }
+
+    // In font pixels, before scaling.
+    pub fn set_max_width(&mut self, max_width: Option<u32>) {
+        self.layout.max_width = max_width;
+        self.sync_size();
+    }
+
+    pub fn set_align(&mut self, align: font::Align) {
+        self.layout.align = align;
+    }
+
+    pub fn set_scale(&mut self, scale: u32) {
+        self.layout.scale = scale.max(1);
+        self.sync_size();
+    }
+
+    fn sync_size(&mut self) {
+        let (width, height) = self.font.measure(&self.text, &self.layout);
+        self.width = width * self.layout.scale;
+        self.height = height * self.layout.scale;
+        self.collision = physics::RectCollision::new(self.body.coord.x, self.body.coord.y, self.body.coord.z, self.width, self.height, 0);
+    }
+}
+
+// A loaded image, or a region of one such as a sprite sheet frame, drawn as an object. The
+// source is shared so many sprites can draw from one sheet. Flips apply first, then the
+// tint scales every channel and opacity scales alpha. `pixel_collision` is an alpha threshold:
+// when set, only source pixels above it collide, whatever the tint and opacity; otherwise
+// the sprite collides by its bounds.
+pub struct Sprite {
+    pub body: Body,
+    pub source: Rc<image::Image>,
+    pub region: image::ClipRect,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub tint: u32,
+    pub opacity: f32,
+    pub pixel_collision: Option<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub image: image::Image,
+    pub collision: physics::MaskCollision,
+    pub draw_mode: DrawMode,
+    pub filled: bool,
+    pub antialiased: bool,
+}
+
+impl GameObjectCommon for Sprite {
+    fn body(&self) -> &Body {
+        &self.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.body
+    }
+
+    fn moved(&mut self) {
+        self.collision.coord = self.body.coord;
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        physics::check_collision(self.collision(), other.collision())
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (self.width, self.height, 0)
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        &self.collision
+    }
+
+    fn image(&self) -> &image::Image {
+        &self.image
+    }
+
+    fn generate_image(&mut self) {
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        let mut image = self.frame();
+        for pixel in image.pixels.data.iter_mut() {
+            let tinted = color::tint(*pixel, self.tint);
+            *pixel = color::with_alpha(tinted, (color::alpha(tinted) as f32 * opacity).round() as u8);
+        }
+        self.image = image;
+    }
+
+    fn generate_image_hollow(&mut self) {
+        self.generate_image();
+    }
+
+    fn mode(&self) -> &DrawMode {
+        &self.draw_mode
+    }
+
+    fn filled(&self) -> bool {
+        self.filled
+    }
+
+    fn antialiased(&self) -> bool {
+        self.antialiased
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.antialiased = antialiased;
+    }
+}
+
+impl Sprite {
+    pub fn new2d(x: f32, y: f32, source: Rc<image::Image>, draw_mode: DrawMode) -> Sprite {
+        let region = source.bounds();
+        Sprite::from_region(x, y, source, region, draw_mode)
+    }
+
+    // `region` is clipped to the source image.
+    pub fn from_region(x: f32, y: f32, source: Rc<image::Image>, region: image::ClipRect, draw_mode: DrawMode) -> Sprite {
+        let mut sprite = Sprite {
+            body: Body::new(Vec3::new(x, y, 0.0)),
+            region: region.intersect(&source.bounds()),
+            source,
+            flip_x: false,
+            flip_y: false,
+            tint: color::WHITE,
+            opacity: 1.0,
+            pixel_collision: None,
+            width: 0,
+            height: 0,
+            image: image::Image::new(1, 1),
+            collision: physics::MaskCollision::new(x, y, 0.0, 0, 0, None),
+            draw_mode,
+            filled: true,
+            antialiased: false,
+        };
+        sprite.sync_collision();
+        sprite
+    }
+
+    pub fn set_source(&mut self, source: Rc<image::Image>) {
+        self.region = source.bounds();
+        self.source = source;
+        self.sync_collision();
+    }
+
+    pub fn set_region(&mut self, region: image::ClipRect) {
+        self.region = region.intersect(&self.source.bounds());
+        self.sync_collision();
+    }
+
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self.sync_collision();
+    }
+
+    pub fn set_tint(&mut self, tint: u32) {
+        self.tint = tint;
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    pub fn set_pixel_collision(&mut self, alpha_threshold: Option<u8>) {
+        self.pixel_collision = alpha_threshold;
+        self.sync_collision();
+    }
+
+    // The region of the source, flipped.
+    pub fn frame(&self) -> image::Image {
+        let (width, height) = (self.region.width as usize, self.region.height as usize);
+        let mut frame = image::Image::new(width, height);
+        for row in 0..height {
+            let source_row = self.region.y as usize + if self.flip_y { height - 1 - row } else { row };
+            for col in 0..width {
+                let source_col = self.region.x as usize + if self.flip_x { width - 1 - col } else { col };
+                frame.set(row, col, self.source.get(source_row, source_col));
+            }
+        }
+        frame
+    }
+
+    // Shows `region` of `source` with a pixel mask already built from it, flipped and
+    // thresholded as this sprite is. The mask is ignored unless pixel collision is on.
+    pub fn set_frame(&mut self, source: Rc<image::Image>, region: image::ClipRect, mask: Option<Rc<physics::CollisionMask>>) {
+        self.region = region.intersect(&source.bounds());
+        self.source = source;
+        self.sync_size();
+        self.collision.mask = mask.filter(|_| self.pixel_collision.is_some());
+    }
+
+    // Keeps the size, and any pixel mask, matching the current frame.
+    fn sync_collision(&mut self) {
+        self.sync_size();
+        self.collision.mask = self.pixel_collision
+            .map(|threshold| Rc::new(physics::CollisionMask::from_image(&self.frame(), threshold)));
+    }
+
+    fn sync_size(&mut self) {
+        self.width = self.region.width as u32;
+        self.height = self.region.height as u32;
+        self.collision.width = self.width;
+        self.collision.height = self.height;
+    }
+}
+
+pub type FinishCallback = Box<dyn FnMut(&str)>;
+
+// A Sprite that plays clips from a sprite sheet. `update` advances the animation by `tick`;
+// call `advance` instead to drive it by measured time. `on_finish` is called with the
+// clip's name when a clip that plays once ends, and at the end of every cycle of one that
+// repeats.
+pub struct AnimatedSprite {
+    pub sprite: Sprite,
+    pub sheet: Rc<sprite::SpriteSheet>,
+    pub clip: Option<String>,
+    pub position: usize,
+    pub reversing: bool,
+    pub elapsed: Duration,
+    pub playing: bool,
+    pub tick: Duration,
+    pub on_finish: Option<FinishCallback>,
+}
+
+impl GameObjectCommon for AnimatedSprite {
+    fn body(&self) -> &Body {
+        &self.sprite.body
+    }
+
+    fn body_mut(&mut self) -> &mut Body {
+        &mut self.sprite.body
+    }
+
+    fn moved(&mut self) {
+        self.sprite.moved();
+    }
+
+    fn update(&mut self) {
+        self.sprite.update();
+        self.advance(self.tick);
+    }
+
+    fn check_collision(&self, other: &dyn GameObjectCommon) -> bool {
+        self.sprite.check_collision(other)
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        self.sprite.size()
+    }
+
+    fn collision(&self) -> &dyn physics::CollisionObjectCommon {
+        self.sprite.collision()
+    }
+
+    fn image(&self) -> &image::Image {
+        self.sprite.image()
+    }
+
+    fn generate_image(&mut self) {
+        self.sprite.generate_image();
+    }
+
+    fn generate_image_hollow(&mut self) {
+        self.sprite.generate_image_hollow();
+    }
+
+    fn mode(&self) -> &DrawMode {
+        self.sprite.mode()
+    }
+
+    fn filled(&self) -> bool {
+        self.sprite.filled()
+    }
+
+    fn antialiased(&self) -> bool {
+        self.sprite.antialiased()
+    }
+
+    fn set_antialiased(&mut self, antialiased: bool) {
+        self.sprite.set_antialiased(antialiased);
+    }
+}
+
+impl AnimatedSprite {
+    // Shows the sheet's first frame until a clip is played.
+    pub fn new2d(x: f32, y: f32, sheet: Rc<sprite::SpriteSheet>, draw_mode: DrawMode) -> AnimatedSprite {
+        let mut object = AnimatedSprite {
+            sprite: Sprite::from_region(x, y, sheet.image().clone(), image::ClipRect::new(0, 0, 0, 0), draw_mode),
+            sheet,
+            clip: None,
+            position: 0,
+            reversing: false,
+            elapsed: Duration::ZERO,
+            playing: false,
+            tick: Duration::from_secs(1) / 60,
+            on_finish: None,
+        };
+        if !object.sheet.frames().is_empty() {
+            object.show_frame(0);
+        }
+        object
+    }
+
+    // Starts the named clip from its first frame. Returns false, changing nothing, when the
+    // sheet has no such clip or the clip has no frames.
+    pub fn play(&mut self, name: &str) -> bool {
+        let Some(first) = self.sheet.clip(name).and_then(|clip| clip.frames.first().copied()) else {
+            return false;
+        };
+        self.clip = Some(name.to_string());
+        self.position = 0;
+        self.reversing = false;
+        self.elapsed = Duration::ZERO;
+        self.playing = true;
+        self.show_frame(first);
+        true
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = self.clip.is_some();
+    }
+
+    pub fn set_on_finish<F: FnMut(&str) + 'static>(&mut self, on_finish: F) {
+        self.on_finish = Some(Box::new(on_finish));
+    }
+
+    // The sheet frame being shown, if the clip is playing or paused.
+    pub fn current_frame(&self) -> Option<usize> {
+        let clip = self.sheet.clip(self.clip.as_ref()?)?;
+        clip.frames.get(self.position).copied()
+    }
+
+    // Frames last at least a millisecond, so a long step always ends.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let Some(name) = self.clip.clone() else {
+            return;
+        };
+        // A handle on the sheet, so the clip can be borrowed while self changes.
+        let sheet = self.sheet.clone();
+        let Some(clip) = sheet.clip(&name) else {
+            return;
+        };
+        if !self.playing || clip.frames.is_empty() {
+            return;
+        }
+        let start = self.position;
+        let mut finished = 0;
+        self.elapsed += elapsed;
+        while self.playing {
+            let duration = sheet.frames()[clip.frames[self.position]].duration.max(Duration::from_millis(1));
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            if self.step(clip) {
+                finished += 1;
+            }
+        }
+        if self.position != start {
+            self.show_frame(clip.frames[self.position]);
+        }
+        if let Some(on_finish) = self.on_finish.as_mut() {
+            for _ in 0..finished {
+                on_finish(&name);
+            }
+        }
+    }
+
+    // Moves to the clip's next frame, and says whether that ended a run through the clip.
+    fn step(&mut self, clip: &sprite::Clip) -> bool {
+        let last = clip.frames.len() - 1;
+        match clip.mode {
+            sprite::PlayMode::Once => {
+                if self.position < last {
+                    self.position += 1;
+                    return false;
+                }
+                self.playing = false;
+                self.elapsed = Duration::ZERO;
+                true
+            }
+            sprite::PlayMode::Loop => {
+                self.position = if self.position < last { self.position + 1 } else { 0 };
+                self.position == 0
+            }
+            sprite::PlayMode::PingPong => {
+                if last == 0 {
+                    return true;
+                }
+                if !self.reversing && self.position == last {
+                    self.reversing = true;
+                }
+                if self.reversing {
+                    self.position -= 1;
+                } else {
+                    self.position += 1;
+                }
+                if self.reversing && self.position == 0 {
+                    self.reversing = false;
+                    return true;
+                }
+                false
+            }
+        }
+    }
+
+    // Shares the sheet's image and, with pixel collision on, its mask for the frame.
+    pub fn show_frame(&mut self, index: usize) {
+        let (source, region) = self.sheet.frame_source(index);
+        let sprite = &self.sprite;
+        let mask = sprite.pixel_collision
+            .map(|threshold| self.sheet.frame_mask(index, threshold, sprite.flip_x, sprite.flip_y));
+        self.sprite.set_frame(source, region, mask);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 4x4 image whose left half is opaque and right half transparent.
+    fn half_solid() -> Rc<image::Image> {
+        let mut source = image::Image::new(4, 4);
+        for row in 0..4 {
+            for col in 0..2 {
+                source.set(row, col, color::WHITE);
+            }
+        }
+        Rc::new(source)
+    }
+
+    // Source is 80% red, destination 40% blue.
+    #[test]
+    fn porter_duff_operators() {
+        let (dst, src) = (0x660000FF, 0xCCFF0000);
+        let cases = [
+            (DrawMode::Overlay, 0xE0E80017),
+            (DrawMode::Override, src),
+            (DrawMode::Clear, color::TRANSPARENT),
+            (DrawMode::Destination, dst),
+            (DrawMode::DestinationOver, 0xE08B0074),
+            (DrawMode::SourceIn, 0x52FF0000),
+            (DrawMode::DestinationIn, 0x520000FF),
+            (DrawMode::SourceOut, 0x7AFF0000),
+            (DrawMode::DestinationOut, 0x140000FF),
+            (DrawMode::SourceAtop, 0x66CC0033),
+            (DrawMode::DestinationAtop, 0xCC990066),
+            (DrawMode::Xor, 0x8FDB0024),
+        ];
+        for (mode, expected) in cases {
+            assert_eq!(mode.blend(dst, src), expected);
+        }
+    }
+
+    // Every object moves through its body and drags its collision shape along.
+    #[test]
+    fn objects_move_with_their_collision() {
+        let mut path = image::Path::new();
+        path.move_to(Vec2::new(0.0, 0.0));
+        path.line_to(Vec2::new(4.0, 4.0));
+        let mut objects: Vec<Box<dyn GameObjectCommon>> = vec![
+            Box::new(Rect::new2d(0.0, 0.0, 4, 4, color::WHITE, DrawMode::Overlay, true)),
+            Box::new(Circle::new2d(2.0, 2.0, 2, color::WHITE, DrawMode::Overlay, true)),
+            Box::new(Ellipse::new2d(2.0, 2.0, 2, 1, color::WHITE, DrawMode::Overlay, true)),
+            Box::new(Path::new2d(path, image::StrokeStyle::default(), color::WHITE, DrawMode::Overlay, true)),
+            Box::new(Sprite::new2d(0.0, 0.0, half_solid(), DrawMode::Overlay)),
+        ];
+        let mut polygon = Polygon::new2d(color::WHITE, DrawMode::Overlay, true);
+        for (x, y) in [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)] {
+            polygon.add_point(Point::new(x, y, 0.0));
+        }
+        objects.push(Box::new(polygon));
+
+        let target = Rect::new2d(40.0, 2.0, 4, 4, color::WHITE, DrawMode::Overlay, true);
+        for object in objects.iter_mut() {
+            let start = object.coord();
+            assert!(!object.check_collision(&target));
+            object.set_velocity(Vec3::new(10.0, 0.0, 0.0));
+            object.add_acceleration(Vec3::new(2.0, 0.0, 0.0));
+            object.update();
+            object.update();
+            assert_eq!(object.coord() - start, Vec3::new(22.0, 0.0, 0.0));
+            assert_eq!(object.velocity(), Vec3::new(14.0, 0.0, 0.0));
+            object.translate(Vec3::new(16.0, 0.0, 0.0));
+            assert!(object.check_collision(&target));
+            object.move_to(start);
+            assert!(!object.check_collision(&target));
+
+            object.rotate(Quat::from_axis_angle(Vec3::Z, 0.5));
+            object.set_transform(Mat4::scale(Vec3::splat(2.0)));
+            assert_eq!(object.model_matrix().get_translation(), start);
+            assert_eq!(object.transform(), Mat4::scale(Vec3::splat(2.0)));
+        }
+    }
+
+    // The outline is stroked through pixel centres, with room for the miter corners.
+    #[test]
+    fn polygon_outline_stroke() {
+        let mut polygon = Polygon::new2d(color::WHITE, DrawMode::Overlay, false);
+        for (x, y) in [(10.0, 10.0), (20.0, 10.0), (20.0, 20.0), (10.0, 20.0)] {
+            polygon.add_point(Point::new(x, y, 0.0));
+        }
+        polygon.set_stroke(Some(image::StrokeStyle::new(3.0)));
+        assert_eq!(polygon.coord(), Vec3::new(3.0, 3.0, 0.0));
+        assert_eq!(polygon.size(), (24, 24, 0));
+        polygon.generate_image_hollow();
+        let image = polygon.image();
+
+        // Points land at 7 and 17; the stroke covers one pixel either side of them.
+        let row: Vec<bool> = (4..11).map(|col| image.get(12, col) != 0).collect();
+        assert_eq!(row, vec![false, false, true, true, true, false, false]);
+        let column: Vec<bool> = (14..21).map(|row| image.get(row, 12) != 0).collect();
+        assert_eq!(column, vec![false, false, true, true, true, false, false]);
+        assert_eq!(image.get(12, 12), 0);
+        assert_eq!((image.get(6, 6), image.get(18, 18)), (color::WHITE, color::WHITE));
+        assert_eq!((image.get(5, 5), image.get(19, 19)), (0, 0));
+    }
+
+    #[test]
+    fn text_size_matches_the_font() {
+        let font: Rc<dyn font::Font> = Rc::new(font::builtin().clone());
+        let mut text = Text::new2d(3.0, 4.0, "Hello there\nworld", font.clone(), color::WHITE, DrawMode::Overlay);
+        assert_eq!((text.width, text.height), font.measure("Hello there\nworld", &font::TextLayout::default()));
+        assert_eq!(text.size(), (88, 16, 0));
+
+        text.set_max_width(Some(48));
+        assert_eq!(text.size(), (48, 24, 0));
+        text.set_scale(2);
+        assert_eq!(text.size(), (96, 48, 0));
+        text.set_text("");
+        assert_eq!(text.size(), (48 * 2, 8 * 2, 0));
+
+        text.set_text("Hi");
+        text.generate_image();
+        assert_eq!((text.image().width, text.image().height), (96, 16));
+        assert_eq!(text.collision().size(), (96, 16, 0));
+    }
+
+    #[test]
+    fn empty_polygon_is_at_rest() {
+        let polygon = Polygon::new2d(color::WHITE, DrawMode::Overlay, true);
+        assert_eq!(polygon.velocity(), Vec3::ZERO);
+        assert_eq!(polygon.acceleration(), Vec3::ZERO);
+    }
+
+    #[test]
+    fn sprite_collides_with_rect() {
+        let mut sprite = Sprite::new2d(0.0, 0.0, half_solid(), DrawMode::Overlay);
+        let mut rect = Rect::new2d(3.0, 1.0, 4, 2, color::WHITE, DrawMode::Overlay, true);
+        assert!(sprite.check_collision(&rect));
+        assert!(rect.check_collision(&sprite));
+
+        // With a pixel mask only the opaque half counts.
+        sprite.set_pixel_collision(Some(0));
+        assert!(!sprite.check_collision(&rect));
+        assert!(!rect.check_collision(&sprite));
+        rect.translate(Vec3::new(-1.5, 0.0, 0.0));
+        assert!(sprite.check_collision(&rect));
+        assert!(rect.check_collision(&sprite));
+
+        sprite.translate(Vec3::new(0.0, 10.0, 0.0));
+        assert!(!sprite.check_collision(&rect));
+    }
+
+    // Pixels of a 3x2 source are numbered 1 to 6 along each row.
+    #[test]
+    fn sprite_flips() {
+        let source = Rc::new(image::Image::from_data(3, 2, (1..=6).map(|n| 0xFF000000 | n).collect()));
+        let mut sprite = Sprite::new2d(0.0, 0.0, source.clone(), DrawMode::Overlay);
+        let numbers = |sprite: &mut Sprite| {
+            sprite.generate_image();
+            assert_eq!(sprite.image().pixels.data, sprite.frame().pixels.data);
+            sprite.frame().pixels.data.iter().map(|pixel| pixel & 0xFF).collect::<Vec<u32>>()
+        };
+        assert_eq!(numbers(&mut sprite), vec![1, 2, 3, 4, 5, 6]);
+        sprite.set_flip(true, false);
+        assert_eq!(numbers(&mut sprite), vec![3, 2, 1, 6, 5, 4]);
+        sprite.set_flip(false, true);
+        assert_eq!(numbers(&mut sprite), vec![4, 5, 6, 1, 2, 3]);
+        sprite.set_flip(true, true);
+        assert_eq!(numbers(&mut sprite), vec![6, 5, 4, 3, 2, 1]);
+
+        // Regions flip within themselves.
+        let mut region = Sprite::from_region(0.0, 0.0, source, image::ClipRect::new(1, 0, 2, 2), DrawMode::Overlay);
+        region.set_flip(true, false);
+        assert_eq!(numbers(&mut region), vec![3, 2, 6, 5]);
+        assert_eq!(region.size(), (2, 2, 0));
+    }
+
+    #[test]
+    fn sprite_tint_and_opacity() {
+        let source = Rc::new(image::Image::from_data(2, 2, vec![0xFF808080, 0x40FFFFFF, color::TRANSPARENT, color::WHITE]));
+        let mut sprite = Sprite::new2d(0.0, 0.0, source, DrawMode::Overlay);
+        let pixels = |sprite: &mut Sprite| {
+            sprite.generate_image();
+            sprite.image().pixels.data.clone()
+        };
+        assert_eq!(pixels(&mut sprite), vec![0xFF808080, 0x40FFFFFF, color::TRANSPARENT, color::WHITE]);
+
+        // The tint scales each channel, alpha included.
+        sprite.set_tint(0xFFFF0000);
+        assert_eq!(pixels(&mut sprite), vec![0xFF800000, 0x40FF0000, color::TRANSPARENT, 0xFFFF0000]);
+        sprite.set_tint(0x80FFFFFF);
+        assert_eq!(pixels(&mut sprite), vec![0x80808080, 0x20FFFFFF, color::TRANSPARENT, 0x80FFFFFF]);
+
+        // Opacity scales alpha after the tint and leaves the colour alone.
+        sprite.set_tint(color::WHITE);
+        sprite.set_opacity(0.5);
+        assert_eq!(pixels(&mut sprite), vec![0x80808080, 0x20FFFFFF, color::TRANSPARENT, 0x80FFFFFF]);
+        sprite.set_tint(0x80FF0000);
+        assert_eq!(pixels(&mut sprite), vec![0x40800000, 0x10FF0000, color::TRANSPARENT, 0x40FF0000]);
+
+        sprite.set_opacity(2.0);
+        assert_eq!(sprite.opacity, 1.0);
+        sprite.set_opacity(0.0);
+        assert!(pixels(&mut sprite).iter().all(|pixel| color::alpha(*pixel) == 0));
+    }
+
+    #[test]
+    fn animated_sprite_shares_the_sheet_frames() {
+        let mut sheet = sprite::SpriteSheet::new(half_solid());
+        let plain = sheet.add_frame(None, sprite::Frame::new(image::ClipRect::new(0, 0, 2, 2), Duration::from_millis(10))).unwrap();
+        let mut trimmed = sprite::Frame::new(image::ClipRect::new(0, 2, 2, 2), Duration::from_millis(10));
+        (trimmed.offset_x, trimmed.width) = (2, 4);
+        let trimmed = sheet.add_frame(None, trimmed).unwrap();
+        sheet.add_clip("run", sprite::Clip::new(vec![plain, trimmed], sprite::PlayMode::Loop)).unwrap();
+        let sheet = Rc::new(sheet);
+
+        let mut object = AnimatedSprite::new2d(0.0, 0.0, sheet.clone(), DrawMode::Overlay);
+        object.sprite.set_pixel_collision(Some(0));
+        assert!(object.play("run"));
+        assert!(Rc::ptr_eq(&object.sprite.source, sheet.image()));
+        object.advance(Duration::from_millis(10));
+        assert_eq!(object.current_frame(), Some(trimmed));
+        let (upright, _) = sheet.frame_source(trimmed);
+        assert!(Rc::ptr_eq(&object.sprite.source, &upright));
+        assert_eq!(object.size(), (4, 2, 0));
+        let mask = object.sprite.collision.mask.clone().unwrap();
+        assert!(Rc::ptr_eq(&mask, &sheet.frame_mask(trimmed, 0, false, false)));
+
+        // Only the right half of the untrimmed frame is solid.
+        let left = Rect::new2d(0.0, 0.0, 2, 2, color::WHITE, DrawMode::Overlay, true);
+        let right = Rect::new2d(2.5, 0.0, 1, 1, color::WHITE, DrawMode::Overlay, true);
+        assert!(!object.check_collision(&left));
+        assert!(object.check_collision(&right));
+    }
+
+    // A sprite playing "walk", a clip of `count` 10ms frames, that logs each finish.
+    fn walking(count: i32, mode: sprite::PlayMode) -> (AnimatedSprite, Rc<std::cell::RefCell<Vec<String>>>) {
+        let mut sheet = sprite::SpriteSheet::new(half_solid());
+        let frames = (0..count)
+            .map(|i| sheet.add_frame(None, sprite::Frame::new(image::ClipRect::new(i % 2 * 2, i / 2 * 2, 2, 2), Duration::from_millis(10))).unwrap())
+            .collect();
+        sheet.add_clip("walk", sprite::Clip::new(frames, mode)).unwrap();
+        let mut object = AnimatedSprite::new2d(0.0, 0.0, Rc::new(sheet), DrawMode::Overlay);
+        let finished = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let log = finished.clone();
+        object.set_on_finish(move |name| log.borrow_mut().push(name.to_string()));
+        assert!(object.play("walk"));
+        (object, finished)
+    }
+
+    fn frames_every_10ms(object: &mut AnimatedSprite, steps: usize) -> Vec<usize> {
+        (0..steps).map(|_| {
+            object.advance(Duration::from_millis(10));
+            object.current_frame().unwrap()
+        }).collect()
+    }
+
+    #[test]
+    fn ping_pong_bounces_between_the_ends() {
+        let (mut object, finished) = walking(3, sprite::PlayMode::PingPong);
+        assert_eq!(object.current_frame(), Some(0));
+        assert_eq!(frames_every_10ms(&mut object, 8), vec![1, 2, 1, 0, 1, 2, 1, 0]);
+        assert_eq!(finished.borrow().len(), 2);
+        assert!(object.playing);
+
+        // A single frame clip finishes every time its frame ends.
+        let (mut object, finished) = walking(1, sprite::PlayMode::PingPong);
+        assert_eq!(frames_every_10ms(&mut object, 3), vec![0, 0, 0]);
+        assert_eq!(finished.borrow().len(), 3);
+        assert!(object.playing);
+    }
+
+    #[test]
+    fn once_stops_on_its_last_frame() {
+        let (mut object, finished) = walking(3, sprite::PlayMode::Once);
+        object.advance(Duration::from_millis(25));
+        assert_eq!((object.current_frame(), object.playing), (Some(2), true));
+        assert!(finished.borrow().is_empty());
+        object.advance(Duration::from_millis(5));
+        assert_eq!((object.current_frame(), object.playing), (Some(2), false));
+        assert_eq!(*finished.borrow(), vec!["walk".to_string()]);
+
+        object.advance(Duration::from_secs(1));
+        assert_eq!(object.current_frame(), Some(2));
+        assert_eq!(finished.borrow().len(), 1);
+
+        // Playing again starts over.
+        assert!(object.play("walk"));
+        assert_eq!((object.current_frame(), object.playing), (Some(0), true));
+    }
+
+    #[test]
+    fn one_advance_can_finish_several_times() {
+        let (mut object, finished) = walking(3, sprite::PlayMode::Loop);
+        object.advance(Duration::from_millis(95));
+        assert_eq!(*finished.borrow(), vec!["walk".to_string(); 3]);
+        assert_eq!(object.current_frame(), Some(0));
+        // The 5ms left over counts towards the next frame.
+        object.advance(Duration::from_millis(5));
+        assert_eq!(object.current_frame(), Some(1));
+
+        let (mut object, finished) = walking(3, sprite::PlayMode::PingPong);
+        object.advance(Duration::from_millis(90));
+        assert_eq!((finished.borrow().len(), object.current_frame()), (2, Some(1)));
+
+        // Paused sprites hold their frame and time.
+        object.pause();
+        object.advance(Duration::from_secs(1));
+        assert_eq!((finished.borrow().len(), object.current_frame()), (2, Some(1)));
+        object.resume();
+        object.advance(Duration::from_millis(10));
+        assert_eq!(object.current_frame(), Some(2));
+    }
+}
@@ -1,10 +1,36 @@
 use crate::linalg;
+use crate::engine::color;
 use crate::engine::game;
 
+pub mod antialias;
+pub mod bmp;
+pub mod clip;
+pub mod error;
+pub mod fill;
+pub mod format;
+pub mod path;
+pub mod png;
+pub mod ppm;
+pub mod raster;
+pub mod shapes;
+pub mod stroke;
+pub mod text;
+pub mod tga;
+pub mod transform;
+
+pub use clip::ClipRect;
+pub use error::ImageError;
+pub use fill::FillRule;
+pub use format::ImageFormat;
+pub use path::{Path, PathCommand};
+pub use stroke::{LineCap, LineJoin, StrokeStyle};
+pub use transform::Filter;
+
 pub struct Image {
     pub width: usize,
     pub height: usize,
-    pub pixels: linalg::Matrix,
+    pub pixels: linalg::Matrix<u32>,
+    pub clip_stack: Vec<ClipRect>,
 }
 
 impl Image {
@@ -13,6 +39,7 @@ impl Image {
             width,
             height,
             pixels: linalg::Matrix::zeros(height as u32, width as u32),
+            clip_stack: Vec::new(),
         }
     }
 
@@ -21,6 +48,7 @@ impl Image {
             width,
             height,
             pixels: linalg::Matrix::from_data(height as u32, width as u32, vec![rgb; width * height]),
+            clip_stack: Vec::new(),
         }
     }
 
@@ -29,9 +57,18 @@ impl Image {
             width,
             height,
             pixels: linalg::Matrix::from_data(height as u32, width as u32, data),
+            clip_stack: Vec::new(),
         }
     }
 
+    pub fn fill(&mut self, color: u32) {
+        self.pixels.fill(color);
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.pixels.data.iter().all(|pixel| color::alpha(*pixel) == 255)
+    }
+
     pub fn flatten(&self) -> Vec<u32> {
         self.pixels.data.clone()
     }
@@ -44,6 +81,10 @@ impl Image {
         self.pixels.get(row as u32, col as u32)
     }
 
+    pub fn get_checked(&self, row: usize, col: usize) -> Option<u32> {
+        self.pixels.get_checked(row as u32, col as u32)
+    }
+
     pub fn set(&mut self, row: usize, col: usize, value: u32) {
         self.pixels.set(row as u32, col as u32, value);
     }
@@ -53,57 +94,139 @@ impl Image {
             width: end_col - start_col,
             height: end_row - start_row,
             pixels: self.pixels.get_block(start_row as u32, start_col as u32, end_row as u32, end_col as u32),
+            clip_stack: Vec::new(),
+        }
+    }
+
+    pub fn view_block(&self, start_row: usize, start_col: usize, end_row: usize, end_col: usize) -> linalg::MatrixView<'_, u32> {
+        self.pixels.view_block(start_row as u32, start_col as u32, end_row as u32, end_col as u32)
+    }
+
+    pub fn set_block(&mut self, start_row: i32, start_col: i32, block: &Image) {
+        self.set_block_view(start_row, start_col, &block.pixels.view());
+    }
+
+    pub fn add_block(&mut self, start_row: i32, start_col: i32, block: &Image) {
+        self.add_block_view(start_row, start_col, &block.pixels.view());
+    }
+
+    pub fn overlay_block(&mut self, start_row: i32, start_col: i32, block: &Image) {
+        self.overlay_block_view(start_row, start_col, &block.pixels.view());
+    }
+
+    pub fn set_block_view(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<u32>) {
+        if let Some((mut target, source)) = self.clip_block(start_row, start_col, block) {
+            target.copy_from(&source).unwrap_or_else(|e| panic!("{}", e));
+        }
+    }
+
+    pub fn add_block_view(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<u32>) {
+        self.zip_block(start_row, start_col, block, color::add);
+    }
+
+    pub fn overlay_block_view(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<u32>) {
+        self.zip_block(start_row, start_col, block, color::source_over);
+    }
+
+    pub fn blend_block(&mut self, start_row: i32, start_col: i32, block: &Image, mode: game::DrawMode) {
+        self.blend_block_view(start_row, start_col, &block.pixels.view(), mode);
+    }
+
+    pub fn blend_block_view(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<u32>, mode: game::DrawMode) {
+        match mode {
+            game::DrawMode::Override => self.set_block_view(start_row, start_col, block),
+            game::DrawMode::Overlay => self.overlay_block_view(start_row, start_col, block),
+            mode => self.zip_block(start_row, start_col, block, |dst, src| mode.blend(dst, src)),
+        }
+    }
+
+    fn zip_block<F: Fn(u32, u32) -> u32 + Sync + Send>(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<u32>, f: F) {
+        if let Some((mut target, source)) = self.clip_block(start_row, start_col, block) {
+            target.zip_apply(&source, f).unwrap_or_else(|e| panic!("{}", e));
         }
     }
 
-    pub fn set_block(&mut self, start_row: usize, start_col: usize, block: &Image) {
-        self.pixels.set_block(start_row as u32, start_col as u32, &block.pixels);
+    // The visible part of `block` placed at (start_row, start_col), as matching destination
+    // and source windows, or None when it lies entirely outside the clip rectangle.
+    fn clip_block<'a>(&mut self, start_row: i32, start_col: i32, block: &linalg::MatrixView<'a, u32>) -> Option<(linalg::MatrixViewMut<'_, u32>, linalg::MatrixView<'a, u32>)> {
+        let placed = ClipRect::new(start_col, start_row, block.cols as i32, block.rows as i32);
+        let visible = self.clip_rect().intersect(&placed);
+        if visible.is_empty() {
+            return None;
+        }
+        let source = block.sub_view(
+            (visible.y - start_row) as u32,
+            (visible.x - start_col) as u32,
+            (visible.bottom() - start_row) as u32,
+            (visible.right() - start_col) as u32
+        ).unwrap_or_else(|e| panic!("{}", e));
+        let target = self.pixels.view_block_mut(visible.y as u32, visible.x as u32, visible.bottom() as u32, visible.right() as u32);
+        Some((target, source))
     }
 
-    pub fn add_block(&mut self, start_row: usize, start_col: usize, block: &Image) {
-        self.pixels.add_block(start_row as u32, start_col as u32, &block.pixels);
+    pub fn plot(&mut self, x: i32, y: i32, color: u32) {
+        if self.clip_rect().contains(x, y) {
+            self.pixels.set(y as u32, x as u32, color);
+        }
+    }
+
+    // Source-over with the colour's alpha scaled by `coverage`, for antialiased edges.
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: u32, coverage: f32) {
+        if coverage <= 0.0 || !self.clip_rect().contains(x, y) {
+            return;
+        }
+        let alpha = (color::alpha(color) as f32 * coverage.min(1.0)).round() as u8;
+        let (row, col) = (y as u32, x as u32);
+        let blended = color::source_over(self.pixels.get(row, col), color::with_alpha(color, alpha));
+        self.pixels.set(row, col, blended);
     }
 
-    pub fn overlay_block(&mut self, start_row: usize, start_col: usize, block: &Image) {
-        self.pixels.overlay_block(start_row as u32, start_col as u32, &block.pixels);
+    // Inclusive horizontal run from x1 to x2 on row y, in either order.
+    pub fn draw_span(&mut self, y: i32, x1: i32, x2: i32, color: u32) {
+        let clip = self.clip_rect();
+        if y < clip.y || y >= clip.bottom() {
+            return;
+        }
+        let start = x1.min(x2).max(clip.x);
+        let end = x1.max(x2).min(clip.right() - 1);
+        if start <= end {
+            self.pixels.row_mut(y as u32)[start as usize..=end as usize].fill(color);
+        }
     }
 }
 
 impl Image {
     pub fn rgb(r: u8, g: u8, b: u8) -> u32 {
-        let (r, g, b) = (r as u32, g as u32, b as u32);
-        (r << 16) | (g << 8) | b
+        color::argb(255, r, g, b)
+    }
+
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+        color::argb(a, r, g, b)
     }
 
     pub fn draw_object_2d_filled(&mut self, obj: &mut Box<dyn game::GameObjectCommon>) {
-        let (x, y, _) = obj.coord();
+        let coord = obj.coord();
+        let (x, y) = (coord.x.floor() as i32, coord.y.floor() as i32);
         obj.generate_image();
         let image = obj.image();
-        match obj.mode() {
-            game::DrawMode::Addition => self.add_block(y as usize, x as usize, &image),
-            game::DrawMode::Overlay => self.overlay_block(y as usize, x as usize, &image),
-            game::DrawMode::Override => self.set_block(y as usize, x as usize, &image),
-        }
+        self.blend_block(y, x, image, *obj.mode());
     }
 
     pub fn draw_object_2d_hollow(&mut self, obj: &mut Box<dyn game::GameObjectCommon>) {
-        let (x, y, _) = obj.coord();
+        let coord = obj.coord();
+        let (x, y) = (coord.x.floor() as i32, coord.y.floor() as i32);
         obj.generate_image_hollow();
         let image = obj.image();
-        match obj.mode() {
-            game::DrawMode::Addition => self.add_block(y as usize, x as usize, &image),
-            game::DrawMode::Overlay => self.overlay_block(y as usize, x as usize, &image),
-            game::DrawMode::Override => self.set_block(y as usize, x as usize, &image),
-        }
+        self.blend_block(y, x, image, *obj.mode());
     }
 
     pub fn draw_line(&mut self, point1: &game::Point, point2: &game::Point, color: u32) {
-        let (x1, y1, _) = point1.coord;
-        let (x2, y2, _) = point2.coord;
-        let dx = x2 as i32 - x1 as i32;
-        let dy = y2 as i32 - y1 as i32;
-        let mut x = x1 as i32;
-        let mut y = y1 as i32;
+        let (x1, y1) = (point1.coord.x.floor() as i32, point1.coord.y.floor() as i32);
+        let (x2, y2) = (point2.coord.x.floor() as i32, point2.coord.y.floor() as i32);
+        let dx = x2 - x1;
+        let dy = y2 - y1;
+        let mut x = x1;
+        let mut y = y1;
 
         let x_inc = if dx < 0 { -1 } else { 1 };
         let y_inc = if dy < 0 { -1 } else { 1 };
@@ -115,7 +238,7 @@ impl Image {
             let two_dy = 2 * dy;
             let two_dy_dx = 2 * (dy - dx);
             for _ in 0..dx {
-                self.set(y as usize, x as usize, color);
+                self.plot(x, y, color);
                 x += x_inc;
                 if p < 0 {
                     p += two_dy;
@@ -129,7 +252,7 @@ impl Image {
             let two_dx = 2 * dx;
             let two_dx_dy = 2 * (dx - dy);
             for _ in 0..dy {
-                self.set(y as usize, x as usize, color);
+                self.plot(x, y, color);
                 y += y_inc;
                 if p < 0 {
                     p += two_dx;
@@ -149,66 +272,130 @@ impl Image {
         }
 
         let mut points = points.clone();
-        points.sort_by(|a, b| a.coord.1.cmp(&b.coord.1));
+        points.sort_by(|a, b| a.coord.y.total_cmp(&b.coord.y));
 
         let (p1, p2, p3) = (points[0], points[1], points[2]);
 
         let fill_bottom_flat_triangle = |image: &mut Image, v1: &game::Point, v2: &game::Point, v3: &game::Point, color: u32| {
-            let inv_slope1 = (v2.coord.0 as f32 - v1.coord.0 as f32) / (v2.coord.1 as f32 - v1.coord.1 as f32);
-            let inv_slope2 = (v3.coord.0 as f32 - v1.coord.0 as f32) / (v3.coord.1 as f32 - v1.coord.1 as f32);
+            let inv_slope1 = (v2.coord.x - v1.coord.x) / (v2.coord.y - v1.coord.y);
+            let inv_slope2 = (v3.coord.x - v1.coord.x) / (v3.coord.y - v1.coord.y);
 
-            let mut curx1 = v1.coord.0 as f32;
-            let mut curx2 = v1.coord.0 as f32;
+            let mut curx1 = v1.coord.x;
+            let mut curx2 = v1.coord.x;
 
-            for scanline_y in v1.coord.1..=v2.coord.1 {
-                image.draw_line(&game::Point::new(curx1 as i32, scanline_y, 0), &game::Point::new(curx2 as i32, scanline_y, 0), color);
+            for scanline_y in v1.coord.y as i32..=v2.coord.y as i32 {
+                image.draw_span(scanline_y, curx1.floor() as i32, curx2.floor() as i32, color);
                 curx1 += inv_slope1;
                 curx2 += inv_slope2;
             }
         };
 
         let fill_top_flat_triangle = |image: &mut Image, v1: &game::Point, v2: &game::Point, v3: &game::Point, color: u32| {
-            let inv_slope1 = (v3.coord.0 as f32 - v1.coord.0 as f32) / (v3.coord.1 as f32 - v1.coord.1 as f32);
-            let inv_slope2 = (v3.coord.0 as f32 - v2.coord.0 as f32) / (v3.coord.1 as f32 - v2.coord.1 as f32);
+            let inv_slope1 = (v3.coord.x - v1.coord.x) / (v3.coord.y - v1.coord.y);
+            let inv_slope2 = (v3.coord.x - v2.coord.x) / (v3.coord.y - v2.coord.y);
 
-            let mut curx1 = v3.coord.0 as f32;
-            let mut curx2 = v3.coord.0 as f32;
+            let mut curx1 = v3.coord.x;
+            let mut curx2 = v3.coord.x;
 
-            for scanline_y in (v1.coord.1..=v3.coord.1).rev() {
-                image.draw_line(&game::Point::new(curx1 as i32, scanline_y, 0), &game::Point::new(curx2 as i32, scanline_y, 0), color);
+            for scanline_y in (v1.coord.y as i32..=v3.coord.y as i32).rev() {
+                image.draw_span(scanline_y, curx1.floor() as i32, curx2.floor() as i32, color);
                 curx1 -= inv_slope1;
                 curx2 -= inv_slope2;
             }
         };
 
-        if p2.coord.1 == p3.coord.1 {
+        if p2.coord.y == p3.coord.y {
             fill_bottom_flat_triangle(self, p1, p2, p3, color);
-        } else if p1.coord.1 == p2.coord.1 {
+        } else if p1.coord.y == p2.coord.y {
             fill_top_flat_triangle(self, p1, p2, p3, color);
         } else {
             let p4 = game::Point::new(
-                p1.coord.0 + ((p2.coord.1 - p1.coord.1) as f32 / (p3.coord.1 - p1.coord.1) as f32 * (p3.coord.0 - p1.coord.0) as f32) as i32,
-                p2.coord.1,
-                0,
+                p1.coord.x + (p2.coord.y - p1.coord.y) / (p3.coord.y - p1.coord.y) * (p3.coord.x - p1.coord.x),
+                p2.coord.y,
+                0.0,
             );
             fill_bottom_flat_triangle(self, p1, p2, &p4, color);
             fill_top_flat_triangle(self, p2, &p4, p3, color);
         }
     }
+}
 
-    pub fn fill_convex_polygon(&mut self, polygon: &game::Polygon, color: u32) {
-        let points = polygon.points();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT: i32 = 6;
+
+    // Draws into a 4x4 image and into a large one shifted so nothing is cut off; the small
+    // image must be the matching window of the large one.
+    fn assert_clipped<F: Fn(&mut Image, i32)>(draw: F) {
+        let mut small = Image::new(4, 4);
+        draw(&mut small, 0);
+        let mut large = Image::new(20, 20);
+        draw(&mut large, SHIFT);
+        let s = SHIFT as usize;
+        assert_eq!(small.pixels.data, large.get_block(s, s, s + 4, s + 4).pixels.data);
+        assert!(small.pixels.data.iter().any(|pixel| *pixel != 0), "nothing was drawn");
+    }
 
-        // Fan Triangulation
-        let mut triangles = Vec::new();
-        for i in 1..points.len() - 1 {
-            triangles.push(vec![points[0], points[i], points[i + 1]]);
-        }
+    fn point(x: i32, y: i32, shift: i32) -> game::Point {
+        game::Point::new((x + shift) as f32, (y + shift) as f32, 0.0)
+    }
+
+    fn counting(width: usize, height: usize) -> Image {
+        Image::from_data(width, height, (1..=(width * height) as u32).collect())
+    }
 
-        for triangle in triangles {
-            self.fill_triangle(triangle, color);
+    #[test]
+    fn blocks_are_cut_at_every_edge() {
+        let block = counting(3, 3);
+        let mut image = Image::new(4, 4);
+        image.set_block(-1, -1, &block);
+        assert_eq!(image.get_block(0, 0, 2, 2).pixels.data, vec![5, 6, 8, 9]);
+        image.set_block(3, 2, &block);
+        assert_eq!((image.get(3, 2), image.get(3, 3)), (1, 2));
+        assert_eq!(image.get(2, 3), 0);
+
+        let before = image.pixels.data.clone();
+        for (row, col) in [(-3, 0), (0, -3), (4, 0), (0, 4), (i32::MIN, i32::MAX)] {
+            image.set_block(row, col, &block);
+            image.blend_block(row, col, &block, game::DrawMode::Addition);
         }
-        
+        assert_eq!(image.pixels.data, before);
+
+        let opaque = Image::from_data(6, 5, counting(6, 5).pixels.data.iter().map(|pixel| 0xFF000000 | pixel).collect());
+        assert_clipped(|image, shift| image.blend_block(shift - 2, shift + 1, &opaque, game::DrawMode::Overlay));
     }
-}
 
+    #[test]
+    fn lines_leave_and_enter_the_image() {
+        assert_clipped(|image, shift| image.draw_line(&point(-3, -3, shift), &point(6, 5, shift), 7));
+        assert_clipped(|image, shift| image.draw_line(&point(-10, 1, shift), &point(10, 2, shift), 7));
+        assert_clipped(|image, shift| image.draw_line(&point(2, 9, shift), &point(1, -6, shift), 7));
+
+        let mut image = Image::new(4, 4);
+        image.draw_line(&point(-9, -1, 0), &point(-1, -8, 0), 7);
+        image.draw_line(&point(5, 0, 0), &point(9, 3, 0), 7);
+        assert!(image.pixels.data.iter().all(|pixel| *pixel == 0));
+    }
+
+    #[test]
+    fn fills_cross_the_edges() {
+        assert_clipped(|image, shift| image.draw_span(1 + shift, -5 + shift, 10 + shift, 3));
+        assert_clipped(|image, shift| image.fill_circle(&point(0, 0, shift), 2, 3));
+        assert_clipped(|image, shift| image.fill_circle(&point(4, 3, shift), 3, 3));
+        assert_clipped(|image, shift| image.fill_ellipse(&point(3, -1, shift), 4, 2, 3));
+        assert_clipped(|image, shift| image.fill_rounded_rect(-3 + shift, -2 + shift, 8, 9, 3, 3));
+        assert_clipped(|image, shift| image.fill_triangle(vec![&point(-2, -1, shift), &point(5, 1, shift), &point(1, 6, shift)], 3));
+        assert_clipped(|image, shift| image.blend_pixel(3 + shift, shift, 0xFFFFFFFF, 0.5));
+
+        let mut image = Image::new(4, 4);
+        image.draw_span(-1, 0, 3, 3);
+        image.draw_span(4, 0, 3, 3);
+        image.draw_span(2, 4, 9, 3);
+        image.draw_span(2, -9, -1, 3);
+        image.fill_circle(&point(-8, -8, 0), 3, 3);
+        image.blend_pixel(-1, 0, 0xFFFFFFFF, 1.0);
+        assert!(image.pixels.data.iter().all(|pixel| *pixel == 0));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::engine::game;
+use crate::engine::image::{ClipRect, FillRule, Image};
+use crate::linalg::Vec2;
+
+impl Image {
+    // Xiaolin Wu's line; integer coordinates are pixel centres, as with draw_line.
+    pub fn draw_line_aa(&mut self, point1: &game::Point, point2: &game::Point, color: u32) {
+        let (mut x0, mut y0) = (point1.coord.x, point1.coord.y);
+        let (mut x1, mut y1) = (point2.coord.x, point2.coord.y);
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+        let gradient = if x1 == x0 { 1.0 } else { (y1 - y0) / (x1 - x0) };
+        let plot = |image: &mut Image, x: i32, y: i32, coverage: f32| {
+            if steep {
+                image.blend_pixel(y, x, color, coverage);
+            } else {
+                image.blend_pixel(x, y, color, coverage);
+            }
+        };
+
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap = 1.0 - fract(x0 + 0.5);
+        let (first_x, first_y) = (x_start as i32, y_start.floor() as i32);
+        plot(self, first_x, first_y, (1.0 - fract(y_start)) * gap);
+        plot(self, first_x, first_y + 1, fract(y_start) * gap);
+        let mut y = y_start + gradient;
+
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap = fract(x1 + 0.5);
+        let (last_x, last_y) = (x_end as i32, y_end.floor() as i32);
+        if last_x != first_x {
+            plot(self, last_x, last_y, (1.0 - fract(y_end)) * gap);
+            plot(self, last_x, last_y + 1, fract(y_end) * gap);
+        }
+
+        let clip = self.clip_rect();
+        let (low, high) = if steep { (clip.y, clip.bottom()) } else { (clip.x, clip.right()) };
+        let first = (first_x + 1).max(low - 1);
+        y += gradient * (first - first_x - 1) as f32;
+        for x in first..last_x.min(high + 1) {
+            plot(self, x, y.floor() as i32, 1.0 - fract(y));
+            plot(self, x, y.floor() as i32 + 1, fract(y));
+            y += gradient;
+        }
+    }
+
+    // Exact area coverage; edges may cross, overlaps are not double counted.
+    pub fn fill_polygon_aa(&mut self, polygon: &game::Polygon, color: u32) {
+        self.fill_contours_aa(&polygon.contours(), polygon.fill_rule, color);
+    }
+
+    pub fn fill_path_aa(&mut self, points: &[Vec2], color: u32) {
+        if points.len() >= 3 {
+            self.fill_contours_aa(&[points.to_vec()], FillRule::NonZero, color);
+        }
+    }
+
+    // Pixel (x, y) is sampled at its centre (x + 0.5, y + 0.5); edges fade over one pixel.
+    pub fn fill_circle_aa(&mut self, center: &game::Point, radius: f32, color: u32) {
+        let (cx, cy) = (center.coord.x, center.coord.y);
+        self.for_each_in_circle(cx, cy, radius + 1.0, |image, x, y, distance| {
+            image.blend_pixel(x, y, color, (radius - distance + 0.5).clamp(0.0, 1.0));
+        });
+    }
+
+    // One pixel wide ring centred on the radius.
+    pub fn draw_circle_aa(&mut self, center: &game::Point, radius: f32, color: u32) {
+        let (cx, cy) = (center.coord.x, center.coord.y);
+        self.for_each_in_circle(cx, cy, radius + 1.0, |image, x, y, distance| {
+            image.blend_pixel(x, y, color, (1.0 - (distance - radius).abs()).clamp(0.0, 1.0));
+        });
+    }
+
+    // Coverage from the implicit function's first-order distance estimate, which is exact
+    // on the axes and close elsewhere unless the ellipse is very eccentric.
+    pub fn fill_ellipse_aa(&mut self, center: &game::Point, radius_x: f32, radius_y: f32, color: u32) {
+        let (cx, cy) = (center.coord.x, center.coord.y);
+        self.for_each_in_ellipse(cx, cy, radius_x, radius_y, |image, x, y, distance| {
+            image.blend_pixel(x, y, color, (0.5 - distance).clamp(0.0, 1.0));
+        });
+    }
+
+    pub fn draw_ellipse_aa(&mut self, center: &game::Point, radius_x: f32, radius_y: f32, color: u32) {
+        let (cx, cy) = (center.coord.x, center.coord.y);
+        self.for_each_in_ellipse(cx, cy, radius_x, radius_y, |image, x, y, distance| {
+            image.blend_pixel(x, y, color, (1.0 - distance.abs()).clamp(0.0, 1.0));
+        });
+    }
+
+    fn for_each_in_ellipse<F: FnMut(&mut Image, i32, i32, f32)>(&mut self, cx: f32, cy: f32, radius_x: f32, radius_y: f32, mut f: F) {
+        if radius_x <= 0.0 || radius_y <= 0.0 {
+            return;
+        }
+        let bounds = ClipRect::from_edges(
+            (cx - radius_x - 1.0).floor() as i32,
+            (cy - radius_y - 1.0).floor() as i32,
+            (cx + radius_x + 1.0).ceil() as i32,
+            (cy + radius_y + 1.0).ceil() as i32
+        );
+        let area = self.clip_rect().intersect(&bounds);
+        let (a2, b2) = (radius_x * radius_x, radius_y * radius_y);
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right() {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                let value = dx * dx / a2 + dy * dy / b2 - 1.0;
+                let gradient = 2.0 * ((dx / a2).powi(2) + (dy / b2).powi(2)).sqrt();
+                let distance = if gradient > 0.0 { value / gradient } else { -radius_x.min(radius_y) };
+                f(self, x, y, distance);
+            }
+        }
+    }
+
+    fn for_each_in_circle<F: FnMut(&mut Image, i32, i32, f32)>(&mut self, cx: f32, cy: f32, reach: f32, mut f: F) {
+        let bounds = ClipRect::from_edges(
+            (cx - reach).floor() as i32,
+            (cy - reach).floor() as i32,
+            (cx + reach).ceil() as i32,
+            (cy + reach).ceil() as i32
+        );
+        let area = self.clip_rect().intersect(&bounds);
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right() {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                f(self, x, y, (dx * dx + dy * dy).sqrt());
+            }
+        }
+    }
+}
+
+fn fract(value: f32) -> f32 {
+    value - value.floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::color;
+
+    fn point(x: f32, y: f32) -> game::Point {
+        game::Point::new(x, y, 0.0)
+    }
+
+    // White drawn over transparent pixels leaves the coverage in the alpha channel.
+    fn coverage(image: &Image, x: usize, y: usize) -> u8 {
+        color::alpha(image.get(y, x))
+    }
+
+    // Draws into a 10x10 image and into a large one shifted so nothing is cut off; the
+    // coverage in the small image must match the window of the large one.
+    fn assert_clipped(draw: impl Fn(&mut Image, f32)) {
+        let mut small = Image::new(10, 10);
+        draw(&mut small, 0.0);
+        let mut large = Image::new(100, 100);
+        draw(&mut large, 40.0);
+        let mut drawn = false;
+        for y in 0..10 {
+            for x in 0..10 {
+                let (a, b) = (coverage(&small, x, y), coverage(&large, x + 40, y + 40));
+                assert!(a.abs_diff(b) <= 1, "({x}, {y}): {a} against {b}");
+                drawn |= a > 0;
+            }
+        }
+        assert!(drawn, "nothing was drawn");
+    }
+
+    #[test]
+    fn wu_line_splits_coverage_between_rows() {
+        let mut image = Image::new(10, 4);
+        image.draw_line_aa(&point(0.0, 0.0), &point(8.0, 2.0), color::WHITE);
+        // The line drops a quarter pixel per column.
+        let column = |x: usize| [0, 1, 2].map(|y| coverage(&image, x, y));
+        assert_eq!(column(1), [191, 64, 0]);
+        assert_eq!(column(2), [128, 128, 0]);
+        assert_eq!(column(3), [64, 191, 0]);
+        assert_eq!(column(4), [0, 255, 0]);
+        assert_eq!(column(7), [0, 64, 191]);
+        for x in 1..8 {
+            let total: u32 = column(x).iter().map(|alpha| *alpha as u32).sum();
+            assert!(total.abs_diff(255) <= 1, "column {x} has {total}");
+        }
+        assert_eq!(column(9), [0, 0, 0]);
+
+        let mut reversed = Image::new(10, 4);
+        reversed.draw_line_aa(&point(8.0, 2.0), &point(0.0, 0.0), color::WHITE);
+        assert_eq!(reversed.pixels.data, image.pixels.data);
+
+        // A steep line is the same line with the axes swapped.
+        let mut steep = Image::new(4, 10);
+        steep.draw_line_aa(&point(0.0, 0.0), &point(2.0, 8.0), color::WHITE);
+        for y in 0..4 {
+            for x in 0..10 {
+                assert_eq!(coverage(&steep, y, x), coverage(&image, x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn wu_line_end_points() {
+        // Ends on pixel centres cover half of their pixel.
+        let mut image = Image::new(8, 3);
+        image.draw_line_aa(&point(1.0, 1.0), &point(5.0, 1.0), color::WHITE);
+        let row: Vec<u8> = (0..8).map(|x| coverage(&image, x, 1)).collect();
+        assert_eq!(row, vec![0, 128, 255, 255, 255, 128, 0, 0]);
+        assert!((0..8).all(|x| coverage(&image, x, 0) == 0 && coverage(&image, x, 2) == 0));
+
+        // Ends between pixel centres cover their share of the pixel.
+        let mut image = Image::new(8, 3);
+        image.draw_line_aa(&point(1.25, 1.0), &point(4.75, 1.0), color::WHITE);
+        let row: Vec<u8> = (0..8).map(|x| coverage(&image, x, 1)).collect();
+        assert_eq!(row, vec![0, 64, 255, 255, 255, 64, 0, 0]);
+
+        // A zero length line still marks its pixel.
+        let mut image = Image::new(3, 3);
+        image.draw_line_aa(&point(1.0, 1.0), &point(1.0, 1.0), color::WHITE);
+        assert_eq!(coverage(&image, 1, 1), 128);
+    }
+
+    #[test]
+    fn circles_are_symmetric() {
+        let draws: [&dyn Fn(&mut Image); 4] = [
+            &|image| image.fill_circle_aa(&point(8.0, 8.0), 5.3, color::WHITE),
+            &|image| image.draw_circle_aa(&point(8.0, 8.0), 5.3, color::WHITE),
+            &|image| image.fill_ellipse_aa(&point(8.0, 8.0), 5.3, 5.3, color::WHITE),
+            &|image| image.draw_ellipse_aa(&point(8.0, 8.0), 5.3, 5.3, color::WHITE),
+        ];
+        for draw in draws {
+            let mut image = Image::new(16, 16);
+            draw(&mut image);
+            let mut partial = false;
+            for y in 0..16 {
+                for x in 0..16 {
+                    let alpha = coverage(&image, x, y);
+                    assert_eq!(alpha, coverage(&image, 15 - x, y));
+                    assert_eq!(alpha, coverage(&image, x, 15 - y));
+                    assert_eq!(alpha, coverage(&image, y, x));
+                    partial |= alpha > 0 && alpha < 255;
+                }
+            }
+            assert!(partial);
+        }
+    }
+
+    #[test]
+    fn filled_circle_coverage_matches_its_area() {
+        let mut image = Image::new(16, 16);
+        image.fill_circle_aa(&point(8.0, 8.0), 5.3, color::WHITE);
+        let total: f32 = image.pixels.data.iter().map(|pixel| color::alpha(*pixel) as f32 / 255.0).sum();
+        let area = std::f32::consts::PI * 5.3 * 5.3;
+        assert!((total - area).abs() < 0.5, "{total} against {area}");
+        assert_eq!((coverage(&image, 7, 7), coverage(&image, 0, 0)), (255, 0));
+        // The centre of (13, 7) is 5.52 from the middle, so the edge covers 0.28 of it.
+        assert_eq!(coverage(&image, 13, 7), 71);
+    }
+
+    #[test]
+    fn shapes_cut_by_the_edges() {
+        assert_clipped(|image, shift| image.draw_line_aa(&point(shift - 20.0, shift - 5.0), &point(shift + 30.0, shift + 8.0), color::WHITE));
+        assert_clipped(|image, shift| image.draw_line_aa(&point(shift + 3.0, shift + 25.0), &point(shift + 6.5, shift - 12.0), color::WHITE));
+        assert_clipped(|image, shift| image.fill_circle_aa(&point(shift - 2.0, shift + 3.0), 6.5, color::WHITE));
+        assert_clipped(|image, shift| image.draw_circle_aa(&point(shift + 11.0, shift + 11.0), 5.0, color::WHITE));
+        assert_clipped(|image, shift| image.fill_ellipse_aa(&point(shift + 5.0, shift - 1.0), 12.0, 3.0, color::WHITE));
+
+        // Very long lines only walk the visible columns.
+        let mut image = Image::new(10, 10);
+        image.draw_line_aa(&point(-1e7, 5.0), &point(1e7, 5.0), color::WHITE);
+        assert!((0..10).all(|x| coverage(&image, x, 5) == 255));
+
+        let mut image = Image::new(10, 10);
+        image.draw_line_aa(&point(-30.0, -2.0), &point(40.0, -3.0), color::WHITE);
+        image.fill_circle_aa(&point(-5.0, 20.0), 4.0, color::WHITE);
+        assert!(image.pixels.data.iter().all(|pixel| *pixel == 0));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::engine::image::{Image, ImageError};
+
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const V4_HEADER_SIZE: usize = 108;
+const LCS_SRGB: u32 = 0x73524742;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+pub fn is_bmp(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"BM")
+}
+
+impl Image {
+    // Uncompressed 24-bit and 32-bit files, bottom-up or top-down. 32-bit files only
+    // carry alpha when written with BI_BITFIELDS and an alpha mask; otherwise pixels are opaque.
+    pub fn decode_bmp(bytes: &[u8]) -> Result<Image, ImageError> {
+        if !is_bmp(bytes) {
+            return Err(ImageError::Unsupported("not a BMP file".to_string()));
+        }
+        let offset = read_u32(bytes, 10)? as usize;
+        let header_size = read_u32(bytes, FILE_HEADER_SIZE)? as usize;
+        if header_size < INFO_HEADER_SIZE {
+            return Err(ImageError::Unsupported(format!("BMP header of {} bytes", header_size)));
+        }
+        let width = read_u32(bytes, 18)? as i32;
+        let height = read_u32(bytes, 22)? as i32;
+        let bits = read_u16(bytes, 28)?;
+        let compression = read_u32(bytes, 30)?;
+        if width < 0 {
+            return Err(ImageError::Decode(format!("negative width {}", width)));
+        }
+
+        let masks = match (bits, compression) {
+            (24, BI_RGB) | (32, BI_RGB) => [0x00FF0000, 0x0000FF00, 0x000000FF, 0],
+            (32, BI_BITFIELDS) => {
+                let alpha = if header_size >= INFO_HEADER_SIZE + 16 { read_u32(bytes, 66)? } else { 0 };
+                [read_u32(bytes, 54)?, read_u32(bytes, 58)?, read_u32(bytes, 62)?, alpha]
+            }
+            _ => {
+                return Err(ImageError::Unsupported(format!("{}-bit BMP with compression {}", bits, compression)));
+            }
+        };
+
+        let (width, top_down) = (width as usize, height < 0);
+        let height = height.unsigned_abs() as usize;
+        let bytes_per_pixel = bits as usize / 8;
+        let stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+        let end = stride.checked_mul(height).and_then(|size| size.checked_add(offset))
+            .ok_or_else(|| ImageError::Decode("image dimensions overflow".to_string()))?;
+        if bytes.len() < end {
+            return Err(ImageError::Decode(format!("expected {} bytes, got {}", end, bytes.len())));
+        }
+
+        let mut data = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let source_row = if top_down { row } else { height - 1 - row };
+            let start = offset + source_row * stride;
+            for pixel in bytes[start..start + width * bytes_per_pixel].chunks_exact(bytes_per_pixel) {
+                let value = if bytes_per_pixel == 3 {
+                    u32::from_le_bytes([pixel[0], pixel[1], pixel[2], 0])
+                } else {
+                    u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]])
+                };
+                let channel = |mask: u32| extract_channel(value, mask);
+                let alpha = if masks[3] == 0 { 255 } else { channel(masks[3]) };
+                data.push(Image::rgba(channel(masks[0]), channel(masks[1]), channel(masks[2]), alpha));
+            }
+        }
+        Ok(Image::from_data(width, height, data))
+    }
+
+    // Bottom-up BI_RGB, or a V4 header with an alpha mask when `alpha` is set.
+    pub fn encode_bmp(&self, alpha: bool) -> Vec<u8> {
+        let header_size = if alpha { V4_HEADER_SIZE } else { INFO_HEADER_SIZE };
+        let bytes_per_pixel = if alpha { 4 } else { 3 };
+        let stride = (self.width * bytes_per_pixel).div_ceil(4) * 4;
+        let offset = FILE_HEADER_SIZE + header_size;
+        let size = offset + stride * self.height;
+
+        let mut bytes = Vec::with_capacity(size);
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+
+        bytes.extend_from_slice(&(header_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.width as i32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as i32).to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&(bytes_per_pixel as u16 * 8).to_le_bytes());
+        bytes.extend_from_slice(&(if alpha { BI_BITFIELDS } else { BI_RGB }).to_le_bytes());
+        bytes.extend_from_slice(&((stride * self.height) as u32).to_le_bytes());
+        // 72 DPI in pixels per metre.
+        bytes.extend_from_slice(&2835u32.to_le_bytes());
+        bytes.extend_from_slice(&2835u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 8]);
+        if alpha {
+            for mask in [0x00FF0000u32, 0x0000FF00, 0x000000FF, 0xFF000000] {
+                bytes.extend_from_slice(&mask.to_le_bytes());
+            }
+            bytes.extend_from_slice(&LCS_SRGB.to_le_bytes());
+            bytes.resize(offset, 0);
+        }
+
+        for row in (0..self.height as u32).rev().map(|row| self.pixels.row(row)) {
+            let start = bytes.len();
+            for pixel in row {
+                let [b, g, r, a] = pixel.to_le_bytes();
+                bytes.extend_from_slice(&[b, g, r]);
+                if alpha {
+                    bytes.push(a);
+                }
+            }
+            bytes.resize(start + stride, 0);
+        }
+        bytes
+    }
+}
+
+// Shifts the masked bits down and rescales them to 8 bits.
+fn extract_channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shifted = (value & mask) >> mask.trailing_zeros();
+    let max = mask >> mask.trailing_zeros();
+    ((shifted as u64 * 255 + max as u64 / 2) / max as u64) as u8
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16, ImageError> {
+    bytes.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| ImageError::Decode("truncated BMP header".to_string()))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32, ImageError> {
+    bytes.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| ImageError::Decode("truncated BMP header".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Five pixels wide so 24-bit rows need padding.
+    fn sample(alpha: bool) -> Image {
+        let mut image = Image::new(5, 3);
+        for row in 0..3 {
+            for col in 0..5 {
+                let a = if alpha { (col * 60) as u8 } else { 255 };
+                image.set(row, col, Image::rgba((row * 100) as u8, (col * 50) as u8, 7, a));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn round_trips_with_and_without_alpha() {
+        for alpha in [false, true] {
+            let image = sample(alpha);
+            let decoded = Image::decode_bmp(&image.encode_bmp(alpha)).unwrap();
+            assert_eq!((decoded.width, decoded.height), (5, 3));
+            assert_eq!(decoded.pixels.data, image.pixels.data, "alpha {}", alpha);
+        }
+        let empty = Image::decode_bmp(&Image::new(0, 0).encode_bmp(false)).unwrap();
+        assert_eq!((empty.width, empty.height), (0, 0));
+    }
+
+    #[test]
+    fn alpha_is_dropped_without_the_alpha_mask() {
+        let decoded = Image::decode_bmp(&sample(true).encode_bmp(false)).unwrap();
+        assert_eq!(decoded.pixels.data, sample(false).pixels.data);
+    }
+
+    #[test]
+    fn reads_top_down_rows() {
+        let image = sample(false);
+        let mut bytes = image.encode_bmp(false);
+        // Negating the height flips the row order the file is read in.
+        bytes[22..26].copy_from_slice(&(-3i32).to_le_bytes());
+        let decoded = Image::decode_bmp(&bytes).unwrap();
+        assert_eq!(decoded.pixels.row(0), image.pixels.row(2));
+        assert_eq!(decoded.pixels.row(2), image.pixels.row(0));
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        for alpha in [false, true] {
+            let bytes = sample(alpha).encode_bmp(alpha);
+            for length in 0..bytes.len() {
+                assert!(Image::decode_bmp(&bytes[..length]).is_err(), "alpha {} length {}", alpha, length);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt_input_is_an_error() {
+        let bytes = sample(false).encode_bmp(false);
+        let corrupt = |at: usize, value: &[u8]| {
+            let mut bytes = bytes.clone();
+            bytes[at..at + value.len()].copy_from_slice(value);
+            Image::decode_bmp(&bytes)
+        };
+        assert!(matches!(corrupt(0, b"XX"), Err(ImageError::Unsupported(_))));
+        assert!(matches!(corrupt(14, &12u32.to_le_bytes()), Err(ImageError::Unsupported(_))));
+        assert!(matches!(corrupt(28, &8u16.to_le_bytes()), Err(ImageError::Unsupported(_))));
+        assert!(matches!(corrupt(30, &1u32.to_le_bytes()), Err(ImageError::Unsupported(_))));
+        assert!(matches!(corrupt(18, &(-5i32).to_le_bytes()), Err(ImageError::Decode(_))));
+        assert!(matches!(corrupt(18, &i32::MAX.to_le_bytes()), Err(ImageError::Decode(_))));
+        assert!(matches!(corrupt(22, &i32::MIN.to_le_bytes()), Err(ImageError::Decode(_))));
+        assert!(matches!(corrupt(10, &u32::MAX.to_le_bytes()), Err(ImageError::Decode(_))));
+
+        // The largest size a header can give, which overflows on 32-bit targets.
+        let mut huge = sample(true).encode_bmp(true);
+        huge[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+        huge[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+        assert!(matches!(Image::decode_bmp(&huge), Err(ImageError::Decode(_))));
+    }
+}
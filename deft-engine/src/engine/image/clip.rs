@@ -0,0 +1,133 @@
+use crate::engine::image::Image;
+
+// Half-open pixel rectangle: x..x + width, y..y + height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl ClipRect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> ClipRect {
+        ClipRect {
+            x,
+            y,
+            width: width.max(0),
+            height: height.max(0)
+        }
+    }
+
+    pub fn from_edges(left: i32, top: i32, right: i32, bottom: i32) -> ClipRect {
+        ClipRect::new(left, top, right.saturating_sub(left), bottom.saturating_sub(top))
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x.saturating_add(self.width)
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        ClipRect::from_edges(x, y, self.right().min(other.right()), self.bottom().min(other.bottom()))
+    }
+}
+
+impl Image {
+    pub fn bounds(&self) -> ClipRect {
+        ClipRect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    // Drawing is limited to the intersection of the image bounds and every pushed rectangle.
+    pub fn clip_rect(&self) -> ClipRect {
+        self.clip_stack.last().copied().unwrap_or_else(|| self.bounds())
+    }
+
+    pub fn push_clip(&mut self, rect: ClipRect) {
+        let clip = self.clip_rect().intersect(&rect);
+        self.clip_stack.push(clip);
+    }
+
+    pub fn pop_clip(&mut self) -> Option<ClipRect> {
+        self.clip_stack.pop()
+    }
+
+    pub fn reset_clip(&mut self) {
+        self.clip_stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_edges_are_half_open() {
+        let rect = ClipRect::new(-2, 1, 3, 2);
+        assert_eq!((rect.right(), rect.bottom()), (1, 3));
+        assert!(rect.contains(-2, 1) && rect.contains(0, 2));
+        assert!(!rect.contains(1, 1) && !rect.contains(0, 3));
+        assert!(ClipRect::new(0, 0, -4, 2).is_empty());
+        assert_eq!(ClipRect::from_edges(i32::MIN, 0, i32::MAX, 1).width, i32::MAX);
+        assert_eq!(ClipRect::new(i32::MAX - 1, 0, 5, 1).right(), i32::MAX);
+    }
+
+    #[test]
+    fn intersections() {
+        let a = ClipRect::new(0, 0, 10, 10);
+        assert_eq!(a.intersect(&ClipRect::new(-5, 4, 8, 20)), ClipRect::new(0, 4, 3, 6));
+        assert!(a.intersect(&ClipRect::new(10, 0, 5, 5)).is_empty());
+        assert!(a.intersect(&ClipRect::new(-20, -20, 5, 5)).is_empty());
+    }
+
+    #[test]
+    fn nested_clips_intersect_and_pop_back() {
+        let mut image = Image::new(8, 8);
+        image.push_clip(ClipRect::new(2, 2, 10, 10));
+        assert_eq!(image.clip_rect(), ClipRect::new(2, 2, 6, 6));
+        image.push_clip(ClipRect::new(0, 0, 4, 5));
+        assert_eq!(image.clip_rect(), ClipRect::new(2, 2, 2, 3));
+
+        image.draw_span(3, 0, 7, 1);
+        image.plot(5, 3, 2);
+        let row: Vec<u32> = (0..8).map(|col| image.get(3, col)).collect();
+        assert_eq!(row, vec![0, 0, 1, 1, 0, 0, 0, 0]);
+
+        assert_eq!(image.pop_clip(), Some(ClipRect::new(2, 2, 2, 3)));
+        assert_eq!(image.clip_rect(), ClipRect::new(2, 2, 6, 6));
+        image.plot(5, 3, 2);
+        assert_eq!(image.get(3, 5), 2);
+        image.pop_clip();
+        assert_eq!(image.clip_rect(), image.bounds());
+        assert_eq!(image.pop_clip(), None);
+    }
+
+    #[test]
+    fn reset_and_disjoint_clips() {
+        let mut image = Image::new(4, 4);
+        image.push_clip(ClipRect::new(1, 1, 2, 2));
+        image.push_clip(ClipRect::new(3, 3, 1, 1));
+        assert!(image.clip_rect().is_empty());
+        image.draw_span(3, 0, 3, 1);
+        image.plot(3, 3, 1);
+        assert!(image.pixels.data.iter().all(|pixel| *pixel == 0));
+
+        image.reset_clip();
+        assert_eq!(image.clip_rect(), image.bounds());
+        image.plot(3, 3, 1);
+        assert_eq!(image.get(3, 3), 1);
+    }
+}
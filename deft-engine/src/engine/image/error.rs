@@ -0,0 +1,36 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Decode(String),
+    Encode(String),
+    Unsupported(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "I/O error: {}", e),
+            ImageError::Decode(message) => write!(f, "Failed to decode image: {}", message),
+            ImageError::Encode(message) => write!(f, "Failed to encode image: {}", message),
+            ImageError::Unsupported(message) => write!(f, "Unsupported image: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> ImageError {
+        ImageError::Io(e)
+    }
+}
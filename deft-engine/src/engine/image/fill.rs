@@ -0,0 +1,225 @@
+use crate::engine::game;
+use crate::engine::image::raster::Rasterizer;
+use crate::engine::image::{ClipRect, Image};
+use crate::linalg::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    EvenOdd,
+    NonZero,
+}
+
+struct Edge {
+    from: Vec2,
+    to: Vec2,
+    winding: i32,
+}
+
+impl Image {
+    // Any number of closed contours, each an implicitly closed list of points. Under NonZero,
+    // holes need the opposite orientation to the outline around them; EvenOdd does not care.
+    // A pixel is filled when its centre is inside.
+    pub fn fill_contours(&mut self, contours: &[Vec<Vec2>], rule: FillRule, color: u32) {
+        let Some((edges, area)) = self.prepare_edges(contours) else {
+            return;
+        };
+        let mut crossings = Vec::new();
+        for y in area.y..area.bottom() {
+            for (start, end) in spans(&edges, y as f32 + 0.5, rule, &mut crossings) {
+                let first = (start - 0.5).ceil() as i32;
+                let last = (end - 0.5).ceil() as i32 - 1;
+                if first <= last {
+                    self.draw_span(y, first, last, color);
+                }
+            }
+        }
+    }
+
+    // Exact area coverage from the Rasterizer, under the same fill rules.
+    pub fn fill_contours_aa(&mut self, contours: &[Vec<Vec2>], rule: FillRule, color: u32) {
+        let Some((_, area)) = self.prepare_edges(contours) else {
+            return;
+        };
+        let offset = Vec2::new(area.x as f32, area.y as f32);
+        let mut rasterizer = Rasterizer::new(area.width as usize, area.height as usize);
+        for contour in contours {
+            let local: Vec<Vec2> = contour.iter().map(|point| *point - offset).collect();
+            rasterizer.polygon(&local);
+        }
+        rasterizer.for_each_coverage_with(rule, |x, y, coverage| {
+            self.blend_pixel(area.x + x as i32, area.y + y as i32, color, coverage);
+        });
+    }
+
+    pub fn fill_polygon(&mut self, polygon: &game::Polygon, color: u32) {
+        if polygon.antialiased {
+            self.fill_contours_aa(&polygon.contours(), polygon.fill_rule, color);
+        } else {
+            self.fill_contours(&polygon.contours(), polygon.fill_rule, color);
+        }
+    }
+
+    // Non-horizontal edges of every contour, and the part of the clip rectangle they can touch.
+    fn prepare_edges(&self, contours: &[Vec<Vec2>]) -> Option<(Vec<Edge>, ClipRect)> {
+        let mut edges = Vec::new();
+        let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+        for contour in contours {
+            for (i, from) in contour.iter().enumerate() {
+                let to = contour[(i + 1) % contour.len()];
+                min = min.min(*from);
+                max = max.max(*from);
+                if from.y != to.y {
+                    edges.push(Edge {
+                        from: *from,
+                        to,
+                        winding: if to.y > from.y { 1 } else { -1 }
+                    });
+                }
+            }
+        }
+        if edges.is_empty() {
+            return None;
+        }
+        let bounds = ClipRect::from_edges(min.x.floor() as i32, min.y.floor() as i32, max.x.ceil() as i32, max.y.ceil() as i32);
+        let area = self.clip_rect().intersect(&bounds);
+        if area.is_empty() {
+            None
+        } else {
+            Some((edges, area))
+        }
+    }
+}
+
+// Inside intervals of the scanline at `y`. Edges include their upper end and exclude their
+// lower one, so a vertex shared by two edges is crossed exactly once.
+fn spans(edges: &[Edge], y: f32, rule: FillRule, crossings: &mut Vec<(f32, i32)>) -> Vec<(f32, f32)> {
+    crossings.clear();
+    for edge in edges {
+        let (top, bottom) = if edge.from.y < edge.to.y { (edge.from, edge.to) } else { (edge.to, edge.from) };
+        if y >= top.y && y < bottom.y {
+            let x = top.x + (y - top.y) * (bottom.x - top.x) / (bottom.y - top.y);
+            crossings.push((x, edge.winding));
+        }
+    }
+    crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut result = Vec::new();
+    let mut winding = 0;
+    let mut start = 0.0;
+    for (x, direction) in crossings.iter() {
+        let was_inside = is_inside(winding, rule);
+        winding += direction;
+        let inside = is_inside(winding, rule);
+        if !was_inside && inside {
+            start = *x;
+        } else if was_inside && !inside {
+            result.push((start, *x));
+        }
+    }
+    result
+}
+
+fn is_inside(winding: i32, rule: FillRule) -> bool {
+    match rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    }
+}
+
+// Positive when the points run clockwise on screen, where y grows downwards.
+pub fn signed_area(points: &[Vec2]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        area += points[i].cross(points[(i + 1) % points.len()]);
+    }
+    area / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: u32 = 0xFFFFFFFF;
+
+    fn square(left: f32, top: f32, size: f32) -> Vec<Vec2> {
+        vec![Vec2::new(left, top), Vec2::new(left + size, top), Vec2::new(left + size, top + size), Vec2::new(left, top + size)]
+    }
+
+    fn reversed(mut points: Vec<Vec2>) -> Vec<Vec2> {
+        points.reverse();
+        points
+    }
+
+    // An 8x8 square with a 4x4 square inside it, the hole's orientation chosen by the caller.
+    fn with_hole(same_orientation: bool) -> Vec<Vec<Vec2>> {
+        let hole = square(2.0, 2.0, 4.0);
+        vec![square(0.0, 0.0, 8.0), if same_orientation { hole } else { reversed(hole) }]
+    }
+
+    fn fill(contours: &[Vec<Vec2>], rule: FillRule, antialiased: bool) -> Image {
+        let mut image = Image::new(10, 10);
+        if antialiased {
+            image.fill_contours_aa(contours, rule, COLOR);
+        } else {
+            image.fill_contours(contours, rule, COLOR);
+        }
+        image
+    }
+
+    #[test]
+    fn holes_follow_the_fill_rule() {
+        for antialiased in [false, true] {
+            for (same_orientation, rule, hole_filled) in [
+                (true, FillRule::NonZero, true),
+                (true, FillRule::EvenOdd, false),
+                (false, FillRule::NonZero, false),
+                (false, FillRule::EvenOdd, false),
+            ] {
+                let image = fill(&with_hole(same_orientation), rule, antialiased);
+                let case = format!("{:?} same orientation {} antialiased {}", rule, same_orientation, antialiased);
+                assert_eq!(image.get(1, 1), COLOR, "{}", case);
+                assert_eq!(image.get(7, 4), COLOR, "{}", case);
+                assert_eq!(image.get(4, 4) == COLOR, hole_filled, "{}", case);
+                assert_eq!(image.get(3, 5) == COLOR, hole_filled, "{}", case);
+                assert_eq!(image.get(8, 8), 0, "{}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn self_crossing_star_centre_depends_on_rule() {
+        let star: Vec<Vec<Vec2>> = vec![(0..5).map(|i| {
+            let angle = i as f32 * 4.0 * std::f32::consts::PI / 5.0 - std::f32::consts::FRAC_PI_2;
+            Vec2::new(5.0 + 5.0 * angle.cos(), 5.0 + 5.0 * angle.sin())
+        }).collect()];
+        for antialiased in [false, true] {
+            assert_eq!(fill(&star, FillRule::NonZero, antialiased).get(5, 5), COLOR);
+            assert_eq!(fill(&star, FillRule::EvenOdd, antialiased).get(5, 5), 0);
+        }
+    }
+
+    #[test]
+    fn pixels_are_filled_when_their_centres_are_inside() {
+        let image = fill(&[square(0.4, 0.4, 2.2)], FillRule::NonZero, false);
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(image.get(y, x) == COLOR, x < 3 && y < 3, "{}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn antialiased_edges_get_partial_coverage() {
+        let image = fill(&[square(0.5, 0.5, 3.0)], FillRule::NonZero, true);
+        assert_eq!(image.get(2, 2), COLOR);
+        assert_eq!(crate::engine::color::alpha(image.get(0, 2)), 128);
+        assert_eq!(crate::engine::color::alpha(image.get(0, 0)), 64);
+        assert_eq!(image.get(4, 4), 0);
+    }
+
+    #[test]
+    fn signed_area_is_positive_clockwise() {
+        assert_eq!(signed_area(&square(0.0, 0.0, 2.0)), 4.0);
+        assert_eq!(signed_area(&reversed(square(0.0, 0.0, 2.0))), -4.0);
+    }
+}
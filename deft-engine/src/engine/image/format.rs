@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::Path;
+
+use crate::engine::image::{bmp, png, ppm, Image, ImageError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+    Pgm,
+    Bmp,
+    Tga,
+}
+
+impl ImageFormat {
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" | "pnm" => Some(ImageFormat::Ppm),
+            "pgm" => Some(ImageFormat::Pgm),
+            "bmp" | "dib" => Some(ImageFormat::Bmp),
+            "tga" | "targa" | "icb" | "vda" | "vst" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ImageFormat> {
+        path.as_ref().extension().and_then(|e| e.to_str()).and_then(ImageFormat::from_extension)
+    }
+
+    // TGA has no signature, so it can only be picked by extension.
+    pub fn from_magic(bytes: &[u8]) -> Option<ImageFormat> {
+        if png::is_png(bytes) {
+            Some(ImageFormat::Png)
+        } else if bmp::is_bmp(bytes) {
+            Some(ImageFormat::Bmp)
+        } else if ppm::is_pnm(bytes) {
+            Some(if matches!(bytes[1], b'2' | b'5') { ImageFormat::Pgm } else { ImageFormat::Ppm })
+        } else {
+            None
+        }
+    }
+}
+
+impl Image {
+    // The file's signature wins over its extension, so a mislabelled PNG still loads.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Image, ImageError> {
+        let bytes = fs::read(&path)?;
+        let format = ImageFormat::from_magic(&bytes).or_else(|| ImageFormat::from_path(&path))
+            .ok_or_else(|| ImageError::Unsupported(format!("unknown image format for {}", path.as_ref().display())))?;
+        Image::decode(&bytes, format)
+    }
+
+    pub fn decode(bytes: &[u8], format: ImageFormat) -> Result<Image, ImageError> {
+        match format {
+            ImageFormat::Png => Image::decode_png(bytes),
+            ImageFormat::Ppm | ImageFormat::Pgm => Image::decode_pnm(bytes),
+            ImageFormat::Bmp => Image::decode_bmp(bytes),
+            ImageFormat::Tga => Image::decode_tga(bytes),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        let format = ImageFormat::from_path(&path)
+            .ok_or_else(|| ImageError::Unsupported(format!("unknown image format for {}", path.as_ref().display())))?;
+        fs::write(path, self.encode(format)?)?;
+        Ok(())
+    }
+
+    // Binary Netpbm, BMP with alpha only when needed, and RLE TGA; call the format's encoder
+    // directly for the other variants.
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+        match format {
+            ImageFormat::Png => self.encode_png(),
+            ImageFormat::Ppm => Ok(self.encode_ppm(true)),
+            ImageFormat::Pgm => Ok(self.encode_pgm(true)),
+            ImageFormat::Bmp => Ok(self.encode_bmp(!self.is_opaque())),
+            ImageFormat::Tga => self.encode_tga(true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [ImageFormat; 5] = [ImageFormat::Png, ImageFormat::Ppm, ImageFormat::Pgm, ImageFormat::Bmp, ImageFormat::Tga];
+
+    // Netpbm keeps neither alpha nor, for PGM, colour, so those get an opaque grey image.
+    fn sample(format: ImageFormat) -> Image {
+        match format {
+            ImageFormat::Ppm => Image::from_data(3, 2, vec![0xFF102030, 0xFFFFFFFF, 0xFF000000, 0xFF804020, 0xFF00FF00, 0xFF0000FF]),
+            ImageFormat::Pgm => Image::from_data(3, 2, vec![0xFF000000, 0xFF404040, 0xFF808080, 0xFFC0C0C0, 0xFFFFFFFF, 0xFF101010]),
+            _ => Image::from_data(3, 2, vec![0xFF102030, 0x80FFFFFF, 0x00000000, 0xFF804020, 0x4000FF00, 0xFF0000FF]),
+        }
+    }
+
+    // A path in the temporary directory that no other test run uses.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("deft-format-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn formats_by_extension() {
+        for (extension, format) in [
+            ("png", Some(ImageFormat::Png)),
+            ("PNG", Some(ImageFormat::Png)),
+            ("pnm", Some(ImageFormat::Ppm)),
+            ("pgm", Some(ImageFormat::Pgm)),
+            ("Dib", Some(ImageFormat::Bmp)),
+            ("targa", Some(ImageFormat::Tga)),
+            ("gif", None),
+            ("", None),
+        ] {
+            assert_eq!(ImageFormat::from_extension(extension), format, "{}", extension);
+        }
+        assert_eq!(ImageFormat::from_path("art/a.b.PGM"), Some(ImageFormat::Pgm));
+        assert_eq!(ImageFormat::from_path("art/png"), None);
+        assert_eq!(ImageFormat::from_path("art/.png"), None);
+    }
+
+    #[test]
+    fn formats_by_signature() {
+        for format in FORMATS {
+            let bytes = sample(format).encode(format).unwrap();
+            let expected = if format == ImageFormat::Tga { None } else { Some(format) };
+            assert_eq!(ImageFormat::from_magic(&bytes), expected, "{:?}", format);
+        }
+        assert_eq!(ImageFormat::from_magic(b"P3\n1 1\n255\n0 0 0\n"), Some(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::from_magic(b"P2\n1 1\n255\n0\n"), Some(ImageFormat::Pgm));
+        for bytes in [&b""[..], b"P", b"P7", b"B", b"GIF89a", b"\x89PN"] {
+            assert_eq!(ImageFormat::from_magic(bytes), None, "{:?}", bytes);
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_dispatcher() {
+        for format in FORMATS {
+            let image = sample(format);
+            let decoded = Image::decode(&image.encode(format).unwrap(), format).unwrap();
+            assert_eq!((decoded.width, decoded.height), (3, 2), "{:?}", format);
+            assert_eq!(decoded.pixels.data, image.pixels.data, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn load_prefers_the_signature_to_the_extension() {
+        let image = sample(ImageFormat::Png);
+        let cases = [
+            ("mislabelled.bmp", ImageFormat::Png),
+            ("plain.tga", ImageFormat::Tga),
+            ("signed.unknown", ImageFormat::Bmp),
+        ];
+        for (name, format) in cases {
+            let path = temp_path(name);
+            fs::write(&path, image.encode(format).unwrap()).unwrap();
+            let loaded = Image::load(&path);
+            fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap().pixels.data, image.pixels.data, "{}", name);
+        }
+
+        let path = temp_path("saved.png");
+        image.save(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(ImageFormat::from_magic(&bytes), Some(ImageFormat::Png));
+    }
+
+    #[test]
+    fn unknown_formats_are_unsupported() {
+        let image = sample(ImageFormat::Png);
+        let path = temp_path("image.gif");
+        assert!(matches!(image.save(&path), Err(ImageError::Unsupported(_))));
+        assert!(!path.exists());
+
+        // TGA data has no signature, so without the extension nothing can read it.
+        fs::write(&path, image.encode(ImageFormat::Tga).unwrap()).unwrap();
+        let loaded = Image::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(loaded, Err(ImageError::Unsupported(_))));
+
+        assert!(matches!(Image::load(temp_path("missing.png")), Err(ImageError::Io(_))));
+    }
+}
@@ -0,0 +1,344 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::engine::game;
+use crate::engine::image::{FillRule, Image};
+use crate::linalg::Vec2;
+
+// Furthest a flattened curve may stray from the true curve, in pixels.
+pub const FLATTEN_TOLERANCE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    QuadTo(Vec2, Vec2),
+    CurveTo(Vec2, Vec2, Vec2),
+    Close,
+}
+
+// One flattened subpath. Closed subpaths do not repeat their first point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+// Commands are interpreted as in SVG: drawing without a current point starts from the
+// origin, and a subpath that follows close starts where the closed one did.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path {
+            commands: Vec::new()
+        }
+    }
+
+    pub fn move_to(&mut self, point: Vec2) {
+        self.commands.push(PathCommand::MoveTo(point));
+    }
+
+    pub fn line_to(&mut self, point: Vec2) {
+        self.commands.push(PathCommand::LineTo(point));
+    }
+
+    pub fn quad_to(&mut self, control: Vec2, point: Vec2) {
+        self.commands.push(PathCommand::QuadTo(control, point));
+    }
+
+    pub fn curve_to(&mut self, control1: Vec2, control2: Vec2, point: Vec2) {
+        self.commands.push(PathCommand::CurveTo(control1, control2, point));
+    }
+
+    pub fn close(&mut self) {
+        self.commands.push(PathCommand::Close);
+    }
+
+    // Angles as in Image::draw_arc, but the arc runs from start to end, so a smaller end
+    // angle goes anticlockwise. Joined to the current subpath by a line if there is one.
+    pub fn arc(&mut self, center: Vec2, radius: f32, start_angle: f32, end_angle: f32) {
+        let on_circle = |angle: f32| center + Vec2::new(angle.cos(), angle.sin()) * radius;
+        let tangent = |angle: f32| Vec2::new(-angle.sin(), angle.cos()) * radius;
+        match self.commands.last() {
+            None | Some(PathCommand::Close) => self.move_to(on_circle(start_angle)),
+            _ => self.line_to(on_circle(start_angle)),
+        }
+
+        // One cubic per quarter turn at most; handles of 4/3 tan(a/4) keep the error tiny.
+        let sweep = end_angle - start_angle;
+        let segments = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / segments as f32;
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+        for i in 0..segments {
+            let from = start_angle + step * i as f32;
+            let to = from + step;
+            self.curve_to(on_circle(from) + tangent(from) * handle, on_circle(to) - tangent(to) * handle, on_circle(to));
+        }
+    }
+
+    // Uniform Catmull-Rom spline through every point, as cubic segments. Open splines
+    // repeat their end points to get tangents there.
+    pub fn catmull_rom(points: &[Vec2], closed: bool) -> Path {
+        let mut path = Path::new();
+        let n = points.len();
+        if n == 0 {
+            return path;
+        }
+        path.move_to(points[0]);
+        let segments = if closed { n } else { n - 1 };
+        for i in 0..segments {
+            let (before, after) = if closed {
+                (points[(i + n - 1) % n], points[(i + 2) % n])
+            } else {
+                (points[i.saturating_sub(1)], points[(i + 2).min(n - 1)])
+            };
+            let (from, to) = (points[i], points[(i + 1) % n]);
+            path.curve_to(from + (to - before) / 6.0, to - (after - from) / 6.0, to);
+        }
+        if closed {
+            path.close();
+        }
+        path
+    }
+
+    pub fn translate(&mut self, offset: Vec2) {
+        for command in self.commands.iter_mut() {
+            match command {
+                PathCommand::MoveTo(point) | PathCommand::LineTo(point) => *point += offset,
+                PathCommand::QuadTo(control, point) => {
+                    *control += offset;
+                    *point += offset;
+                }
+                PathCommand::CurveTo(control1, control2, point) => {
+                    *control1 += offset;
+                    *control2 += offset;
+                    *point += offset;
+                }
+                PathCommand::Close => {}
+            }
+        }
+    }
+
+    // Box around every point, control points included, so curves always fit inside.
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        let mut bounds: Option<(Vec2, Vec2)> = None;
+        let mut add = |point: Vec2| {
+            bounds = Some(match bounds {
+                Some((min, max)) => (min.min(point), max.max(point)),
+                None => (point, point),
+            });
+        };
+        for command in self.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(point) | PathCommand::LineTo(point) => add(point),
+                PathCommand::QuadTo(control, point) => {
+                    add(control);
+                    add(point);
+                }
+                PathCommand::CurveTo(control1, control2, point) => {
+                    add(control1);
+                    add(control2);
+                    add(point);
+                }
+                PathCommand::Close => {}
+            }
+        }
+        bounds
+    }
+
+    // Curves become line segments no further than `tolerance` from the curve.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut current: Vec<Vec2> = Vec::new();
+        let mut start = Vec2::ZERO;
+        for command in self.commands.iter() {
+            if current.is_empty() && !matches!(command, PathCommand::MoveTo(_) | PathCommand::Close) {
+                current.push(start);
+            }
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    finish_polyline(&mut polylines, &mut current, false);
+                    current.push(point);
+                    start = point;
+                }
+                PathCommand::LineTo(point) => push_point(&mut current, point),
+                PathCommand::QuadTo(control, point) => {
+                    let from = *current.last().unwrap();
+                    let deviation = (from - control * 2.0 + point).length();
+                    let segments = segment_count(deviation / (4.0 * tolerance));
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        push_point(&mut current, from.lerp(control, t).lerp(control.lerp(point, t), t));
+                    }
+                }
+                PathCommand::CurveTo(control1, control2, point) => {
+                    let from = *current.last().unwrap();
+                    let deviation = (from - control1 * 2.0 + control2).length().max((control1 - control2 * 2.0 + point).length());
+                    let segments = segment_count(0.75 * deviation / tolerance);
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        let u = 1.0 - t;
+                        let point = from * (u * u * u) + control1 * (3.0 * u * u * t) + control2 * (3.0 * u * t * t) + point * (t * t * t);
+                        push_point(&mut current, point);
+                    }
+                }
+                PathCommand::Close => finish_polyline(&mut polylines, &mut current, true),
+            }
+        }
+        finish_polyline(&mut polylines, &mut current, false);
+        polylines
+    }
+
+    // Every subpath as a closed contour, ready for Image::fill_contours.
+    pub fn contours(&self) -> Vec<Vec<Vec2>> {
+        self.flatten(FLATTEN_TOLERANCE).into_iter().map(|polyline| polyline.points).collect()
+    }
+}
+
+impl Image {
+    // Named apart from fill_path_aa, which fills a bare list of points.
+    pub fn fill_path_with_rule(&mut self, path: &Path, rule: FillRule, color: u32) {
+        self.fill_contours(&path.contours(), rule, color);
+    }
+
+    pub fn fill_path_with_rule_aa(&mut self, path: &Path, rule: FillRule, color: u32) {
+        self.fill_contours_aa(&path.contours(), rule, color);
+    }
+
+    // One pixel wide outline with draw_line.
+    pub fn draw_path(&mut self, path: &Path, color: u32) {
+        self.draw_polylines(path, color, true, Image::draw_line);
+    }
+
+    pub fn draw_path_aa(&mut self, path: &Path, color: u32) {
+        self.draw_polylines(path, color, false, Image::draw_line_aa);
+    }
+
+    pub fn draw_quadratic_bezier(&mut self, point1: &game::Point, control: &game::Point, point2: &game::Point, color: u32) {
+        let mut path = Path::new();
+        path.move_to(to_vec2(point1));
+        path.quad_to(to_vec2(control), to_vec2(point2));
+        self.draw_path(&path, color);
+    }
+
+    pub fn draw_cubic_bezier(&mut self, point1: &game::Point, control1: &game::Point, control2: &game::Point, point2: &game::Point, color: u32) {
+        let mut path = Path::new();
+        path.move_to(to_vec2(point1));
+        path.curve_to(to_vec2(control1), to_vec2(control2), to_vec2(point2));
+        self.draw_path(&path, color);
+    }
+
+    pub fn draw_catmull_rom(&mut self, points: &[game::Point], closed: bool, color: u32) {
+        let points: Vec<Vec2> = points.iter().map(to_vec2).collect();
+        self.draw_path(&Path::catmull_rom(&points, closed), color);
+    }
+
+    // draw_line leaves out its last pixel, so open polylines need `plot_end` to finish them.
+    fn draw_polylines<F: FnMut(&mut Image, &game::Point, &game::Point, u32)>(&mut self, path: &Path, color: u32, plot_end: bool, mut line: F) {
+        for polyline in path.flatten(FLATTEN_TOLERANCE) {
+            let points: Vec<game::Point> = polyline.points.iter().map(|point| game::Point::new(point.x, point.y, 0.0)).collect();
+            let segments = if polyline.closed { points.len() } else { points.len() - 1 };
+            for i in 0..segments {
+                line(self, &points[i], &points[(i + 1) % points.len()], color);
+            }
+            if plot_end && !polyline.closed {
+                let end = polyline.points[points.len() - 1];
+                self.plot(end.x.floor() as i32, end.y.floor() as i32, color);
+            }
+        }
+    }
+}
+
+fn to_vec2(point: &game::Point) -> Vec2 {
+    Vec2::new(point.coord.x, point.coord.y)
+}
+
+// Uniform subdivision error falls with the square of the segment count.
+fn segment_count(segments_squared: f32) -> usize {
+    (segments_squared.sqrt().ceil() as usize).clamp(1, 1000)
+}
+
+pub fn push_point(points: &mut Vec<Vec2>, point: Vec2) {
+    if points.last() != Some(&point) {
+        points.push(point);
+    }
+}
+
+fn finish_polyline(polylines: &mut Vec<Polyline>, points: &mut Vec<Vec2>, closed: bool) {
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if !points.is_empty() {
+        polylines.push(Polyline {
+            points: std::mem::take(points),
+            closed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_path(left: f32, top: f32, size: f32) -> Path {
+        let mut path = Path::new();
+        path.move_to(Vec2::new(left, top));
+        path.line_to(Vec2::new(left + size, top));
+        path.line_to(Vec2::new(left + size, top + size));
+        path.line_to(Vec2::new(left, top + size));
+        path.close();
+        path
+    }
+
+    #[test]
+    fn curves_flatten_within_tolerance() {
+        let mut path = Path::new();
+        path.move_to(Vec2::new(0.0, 0.0));
+        path.quad_to(Vec2::new(10.0, 20.0), Vec2::new(20.0, 0.0));
+        let polylines = path.flatten(FLATTEN_TOLERANCE);
+        assert_eq!(polylines.len(), 1);
+        let points = &polylines[0].points;
+        assert!(!polylines[0].closed && points.len() > 2);
+        assert_eq!((points[0], *points.last().unwrap()), (Vec2::new(0.0, 0.0), Vec2::new(20.0, 0.0)));
+        // The curve is y = 2x - x * x / 10, so every point lies on it, and chords stray at
+        // most the tolerance (measured vertically, which overstates the distance).
+        for point in points {
+            assert!((point.y - (2.0 * point.x - point.x * point.x / 10.0)).abs() < 1e-3);
+        }
+        for pair in points.windows(2) {
+            let middle = (pair[0] + pair[1]) / 2.0;
+            assert!((middle.y - (2.0 * middle.x - middle.x * middle.x / 10.0)).abs() <= FLATTEN_TOLERANCE + 1e-4);
+        }
+    }
+
+    #[test]
+    fn subpaths_after_close_start_where_the_last_began() {
+        let mut path = square_path(1.0, 1.0, 2.0);
+        path.line_to(Vec2::new(5.0, 5.0));
+        let polylines = path.flatten(FLATTEN_TOLERANCE);
+        assert_eq!(polylines.len(), 2);
+        assert!(polylines[0].closed);
+        assert_eq!(polylines[0].points.len(), 4);
+        assert_eq!(polylines[1].points, vec![Vec2::new(1.0, 1.0), Vec2::new(5.0, 5.0)]);
+        assert_eq!(path.bounds(), Some((Vec2::new(1.0, 1.0), Vec2::new(5.0, 5.0))));
+    }
+
+    #[test]
+    fn path_fill_matches_point_fill() {
+        let path = square_path(0.5, 1.25, 3.0);
+        let mut from_path = Image::new(6, 6);
+        from_path.fill_path_with_rule_aa(&path, FillRule::NonZero, 0xFFFFFFFF);
+        let mut from_points = Image::new(6, 6);
+        from_points.fill_path_aa(&path.contours()[0], 0xFFFFFFFF);
+        assert_eq!(from_path.pixels.data, from_points.pixels.data);
+
+        let mut solid = Image::new(6, 6);
+        solid.fill_path_with_rule(&path, FillRule::EvenOdd, 0xFFFFFFFF);
+        assert_eq!(solid.get(2, 2), 0xFFFFFFFF);
+        assert_eq!(solid.get(0, 2), 0);
+        assert_eq!(solid.get(4, 2), 0);
+    }
+}
@@ -0,0 +1,194 @@
+use std::fs;
+use std::path::Path;
+
+use crate::engine::image::{Image, ImageError};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+pub fn is_png(bytes: &[u8]) -> bool {
+    bytes.starts_with(&PNG_SIGNATURE)
+}
+
+impl Image {
+    pub fn load_png<P: AsRef<Path>>(path: P) -> Result<Image, ImageError> {
+        Image::decode_png(&fs::read(path)?)
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), ImageError> {
+        fs::write(path, self.encode_png()?)?;
+        Ok(())
+    }
+
+    // Palette, low bit depth and tRNS images are expanded and 16-bit channels are
+    // reduced to 8 bits before mapping to 0xAARRGGBB.
+    pub fn decode_png(bytes: &[u8]) -> Result<Image, ImageError> {
+        if !is_png(bytes) {
+            return Err(ImageError::Unsupported("missing PNG signature".to_string()));
+        }
+
+        let mut decoder = ::png::Decoder::new(bytes);
+        decoder.set_transformations(::png::Transformations::EXPAND | ::png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(decoding_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(decoding_error)?;
+        let (width, height) = (info.width as usize, info.height as usize);
+
+        if info.bit_depth != ::png::BitDepth::Eight {
+            return Err(ImageError::Unsupported(format!("{:?} bit depth after expansion", info.bit_depth)));
+        }
+        let channels = match info.color_type {
+            ::png::ColorType::Grayscale => 1,
+            ::png::ColorType::GrayscaleAlpha => 2,
+            ::png::ColorType::Rgb => 3,
+            ::png::ColorType::Rgba => 4,
+            ::png::ColorType::Indexed => {
+                return Err(ImageError::Unsupported("palette was not expanded".to_string()));
+            }
+        };
+
+        let mut data = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let line = &buffer[row * info.line_size..row * info.line_size + width * channels];
+            for pixel in line.chunks_exact(channels) {
+                let (r, g, b, a) = match channels {
+                    1 => (pixel[0], pixel[0], pixel[0], 255),
+                    2 => (pixel[0], pixel[0], pixel[0], pixel[1]),
+                    3 => (pixel[0], pixel[1], pixel[2], 255),
+                    _ => (pixel[0], pixel[1], pixel[2], pixel[3]),
+                };
+                data.push(Image::rgba(r, g, b, a));
+            }
+        }
+
+        Ok(Image::from_data(width, height, data))
+    }
+
+    // RGBA only when some pixel is not fully opaque.
+    pub fn encode_png(&self) -> Result<Vec<u8>, ImageError> {
+        let alpha = !self.is_opaque();
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = ::png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+            encoder.set_color(if alpha { ::png::ColorType::Rgba } else { ::png::ColorType::Rgb });
+            encoder.set_depth(::png::BitDepth::Eight);
+            let mut writer = encoder.write_header().map_err(encoding_error)?;
+            let mut data = Vec::with_capacity(self.width * self.height * 4);
+            for pixel in self.pixels.data.iter() {
+                let [a, r, g, b] = pixel.to_be_bytes();
+                data.extend_from_slice(&[r, g, b]);
+                if alpha {
+                    data.push(a);
+                }
+            }
+            writer.write_image_data(&data).map_err(encoding_error)?;
+        }
+        Ok(bytes)
+    }
+}
+
+fn decoding_error(e: ::png::DecodingError) -> ImageError {
+    match e {
+        ::png::DecodingError::IoError(e) => ImageError::Io(e),
+        e => ImageError::Decode(e.to_string()),
+    }
+}
+
+fn encoding_error(e: ::png::EncodingError) -> ImageError {
+    match e {
+        ::png::EncodingError::IoError(e) => ImageError::Io(e),
+        e => ImageError::Encode(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(width: u32, height: u32, color: ::png::ColorType, depth: ::png::BitDepth, palette: Option<(&[u8], &[u8])>, data: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = ::png::Encoder::new(&mut bytes, width, height);
+            encoder.set_color(color);
+            encoder.set_depth(depth);
+            if let Some((palette, trns)) = palette {
+                encoder.set_palette(palette.to_vec());
+                encoder.set_trns(trns.to_vec());
+            }
+            encoder.write_header().unwrap().write_image_data(data).unwrap();
+        }
+        bytes
+    }
+
+    fn sample(alpha: bool) -> Image {
+        let mut image = Image::new(3, 2);
+        for row in 0..2 {
+            for col in 0..3 {
+                let a = if alpha { (col * 100 + row) as u8 } else { 255 };
+                image.set(row, col, Image::rgba((row * 200) as u8, (col * 80) as u8, 9, a));
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn round_trips_rgb_and_rgba() {
+        for alpha in [false, true] {
+            let image = sample(alpha);
+            let bytes = image.encode_png().unwrap();
+            assert!(is_png(&bytes));
+            let decoded = Image::decode_png(&bytes).unwrap();
+            assert_eq!((decoded.width, decoded.height), (3, 2));
+            assert_eq!(decoded.pixels.data, image.pixels.data, "alpha {}", alpha);
+        }
+    }
+
+    #[test]
+    fn decodes_alpha_into_the_top_byte() {
+        let bytes = encode(1, 1, ::png::ColorType::Rgba, ::png::BitDepth::Eight, None, &[0x11, 0x22, 0x33, 0x44]);
+        assert_eq!(Image::decode_png(&bytes).unwrap().get(0, 0), 0x44112233);
+    }
+
+    #[test]
+    fn decodes_grayscale_and_grayscale_alpha() {
+        let gray = encode(2, 1, ::png::ColorType::Grayscale, ::png::BitDepth::Eight, None, &[0x10, 0xF0]);
+        assert_eq!(Image::decode_png(&gray).unwrap().pixels.data, vec![0xFF101010, 0xFFF0F0F0]);
+
+        let gray_alpha = encode(2, 1, ::png::ColorType::GrayscaleAlpha, ::png::BitDepth::Eight, None, &[0x10, 0x80, 0xF0, 0x00]);
+        assert_eq!(Image::decode_png(&gray_alpha).unwrap().pixels.data, vec![0x80101010, 0x00F0F0F0]);
+
+        // Two pixels in one byte, expanded to 8 bits.
+        let low_depth = encode(2, 1, ::png::ColorType::Grayscale, ::png::BitDepth::Four, None, &[0x0F]);
+        assert_eq!(Image::decode_png(&low_depth).unwrap().pixels.data, vec![0xFF000000, 0xFFFFFFFF]);
+    }
+
+    #[test]
+    fn decodes_palette_with_transparency() {
+        let palette = [0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF];
+        let bytes = encode(3, 1, ::png::ColorType::Indexed, ::png::BitDepth::Eight, Some((&palette, &[0x40])), &[0, 1, 0]);
+        assert_eq!(Image::decode_png(&bytes).unwrap().pixels.data, vec![0x40FF0000, 0xFF0000FF, 0x40FF0000]);
+    }
+
+    #[test]
+    fn reduces_16_bit_channels() {
+        let data = [0x12, 0x34, 0xAB, 0xCD, 0xFF, 0xFF, 0x80, 0x00];
+        let bytes = encode(1, 1, ::png::ColorType::Rgba, ::png::BitDepth::Sixteen, None, &data);
+        assert_eq!(Image::decode_png(&bytes).unwrap().pixels.data, vec![0x8012ABFF]);
+    }
+
+    #[test]
+    fn bad_input_is_an_error() {
+        assert!(matches!(Image::decode_png(b"not a png"), Err(ImageError::Unsupported(_))));
+        assert!(Image::decode_png(&[]).is_err());
+
+        let bytes = sample(true).encode_png().unwrap();
+        // Cut inside the header and inside the image data.
+        for len in [8, 20, 40, bytes.len() / 2] {
+            assert!(Image::decode_png(&bytes[..len]).is_err(), "truncated to {}", len);
+        }
+
+        let mut corrupt = bytes.clone();
+        // Inside the IHDR chunk, so its CRC no longer matches.
+        corrupt[20] ^= 0xFF;
+        assert!(Image::decode_png(&corrupt).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::engine::image::{Image, ImageError};
+
+// Netpbm limits plain (ASCII) files to 70 characters per line.
+const ASCII_LINE_LIMIT: usize = 70;
+
+pub fn is_pnm(bytes: &[u8]) -> bool {
+    bytes.len() >= 2 && bytes[0] == b'P' && matches!(bytes[1], b'2' | b'3' | b'5' | b'6')
+}
+
+impl Image {
+    // Reads P2/P5 (PGM) and P3/P6 (PPM) with any maxval up to 65535, scaled to 8 bits.
+    pub fn decode_pnm(bytes: &[u8]) -> Result<Image, ImageError> {
+        if !is_pnm(bytes) {
+            return Err(ImageError::Unsupported("not a PGM or PPM file".to_string()));
+        }
+        let magic = bytes[1];
+        let mut reader = Reader { bytes, pos: 2 };
+        let width = reader.header_value()?;
+        let height = reader.header_value()?;
+        let maxval = reader.header_value()?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(ImageError::Decode(format!("invalid maxval {}", maxval)));
+        }
+
+        let channels = if matches!(magic, b'2' | b'5') { 1 } else { 3 };
+        let count = width.checked_mul(height).and_then(|n| n.checked_mul(channels))
+            .ok_or_else(|| ImageError::Decode("image dimensions overflow".to_string()))?;
+        let samples = if matches!(magic, b'2' | b'3') {
+            (0..count).map(|_| reader.header_value()).collect::<Result<Vec<_>, _>>()?
+        } else {
+            reader.pos += 1;
+            reader.binary_samples(count, maxval > 255)?
+        };
+
+        let scale = |value: usize| -> Result<u8, ImageError> {
+            if value > maxval {
+                return Err(ImageError::Decode(format!("sample {} exceeds maxval {}", value, maxval)));
+            }
+            Ok(((value * 255 + maxval / 2) / maxval) as u8)
+        };
+        let mut data = Vec::with_capacity(width * height);
+        for pixel in samples.chunks_exact(channels) {
+            data.push(if channels == 1 {
+                let v = scale(pixel[0])?;
+                Image::rgb(v, v, v)
+            } else {
+                Image::rgb(scale(pixel[0])?, scale(pixel[1])?, scale(pixel[2])?)
+            });
+        }
+        Ok(Image::from_data(width, height, data))
+    }
+
+    pub fn encode_ppm(&self, binary: bool) -> Vec<u8> {
+        let samples: Vec<u8> = self.pixels.data.iter()
+            .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+            .collect();
+        self.encode_pnm(if binary { "P6" } else { "P3" }, &samples, binary)
+    }
+
+    // Colour is reduced to Rec. 601 luma; PGM and PPM have no alpha, so it is dropped.
+    pub fn encode_pgm(&self, binary: bool) -> Vec<u8> {
+        let samples: Vec<u8> = self.pixels.data.iter().map(|pixel| luma(*pixel)).collect();
+        self.encode_pnm(if binary { "P5" } else { "P2" }, &samples, binary)
+    }
+
+    fn encode_pnm(&self, magic: &str, samples: &[u8], binary: bool) -> Vec<u8> {
+        let mut bytes = format!("{}\n{} {}\n255\n", magic, self.width, self.height).into_bytes();
+        if binary {
+            bytes.extend_from_slice(samples);
+            return bytes;
+        }
+        let mut line = String::new();
+        for sample in samples {
+            let value = sample.to_string();
+            if !line.is_empty() && line.len() + 1 + value.len() > ASCII_LINE_LIMIT {
+                bytes.extend_from_slice(line.as_bytes());
+                bytes.push(b'\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&value);
+        }
+        if !line.is_empty() {
+            bytes.extend_from_slice(line.as_bytes());
+            bytes.push(b'\n');
+        }
+        bytes
+    }
+}
+
+pub fn luma(pixel: u32) -> u8 {
+    let (r, g, b) = ((pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF);
+    ((r * 299 + g * 587 + b * 114 + 500) / 1000) as u8
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    // Skips whitespace and `#` comments, then parses one decimal number.
+    fn header_value(&mut self) -> Result<usize, ImageError> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'#') => {
+                    while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.pos]).ok()
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(|| ImageError::Decode(format!("expected a number at byte {}", start)))
+    }
+
+    fn binary_samples(&self, count: usize, wide: bool) -> Result<Vec<usize>, ImageError> {
+        let size = if wide { 2 } else { 1 };
+        let data = self.bytes.get(self.pos..).unwrap_or(&[]);
+        if data.len() / size < count {
+            return Err(ImageError::Decode(format!("expected {} samples of pixel data, got {}", count, data.len() / size)));
+        }
+        Ok(if wide {
+            data.chunks_exact(2).take(count).map(|s| u16::from_be_bytes([s[0], s[1]]) as usize).collect()
+        } else {
+            data[..count].iter().map(|s| *s as usize).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colour() -> Image {
+        let mut image = Image::new(4, 2);
+        for (i, pixel) in image.pixels.data.iter_mut().enumerate() {
+            *pixel = Image::rgb((i * 30) as u8, 255 - (i * 30) as u8, (i * 7) as u8);
+        }
+        image
+    }
+
+    fn gray() -> Image {
+        let data = (0..8).map(|i| Image::rgb(i * 36, i * 36, i * 36)).collect();
+        Image::from_data(4, 2, data)
+    }
+
+    fn assert_same(decoded: &Image, expected: &Image) {
+        assert_eq!((decoded.width, decoded.height), (expected.width, expected.height));
+        assert_eq!(decoded.pixels.data, expected.pixels.data);
+    }
+
+    #[test]
+    fn round_trips_ppm_and_pgm() {
+        for binary in [false, true] {
+            assert_same(&Image::decode_pnm(&colour().encode_ppm(binary)).unwrap(), &colour());
+            assert_same(&Image::decode_pnm(&gray().encode_pgm(binary)).unwrap(), &gray());
+            assert_same(&Image::decode_pnm(&gray().encode_ppm(binary)).unwrap(), &gray());
+        }
+    }
+
+    #[test]
+    fn pgm_keeps_luma_and_drops_alpha() {
+        let image = Image::from_data(2, 1, vec![0x80FF0000, 0xFF00FF00]);
+        let decoded = Image::decode_pnm(&image.encode_pgm(true)).unwrap();
+        let (red, green) = (luma(0xFFFF0000), luma(0xFF00FF00));
+        assert_eq!(decoded.pixels.data, vec![Image::rgb(red, red, red), Image::rgb(green, green, green)]);
+    }
+
+    #[test]
+    fn ascii_lines_stay_short() {
+        let image = Image::from_data(40, 1, vec![0xFFFFFFFF; 40]);
+        let bytes = image.encode_ppm(false);
+        assert!(bytes.split(|b| *b == b'\n').all(|line| line.len() <= ASCII_LINE_LIMIT));
+    }
+
+    #[test]
+    fn reads_comments_and_wide_samples() {
+        let plain = b"P2 # comment\n2 1\n# another\n1000\n0 1000\n";
+        assert_eq!(Image::decode_pnm(plain).unwrap().pixels.data, vec![0xFF000000, 0xFFFFFFFF]);
+        let mut wide = b"P5\n2 1\n65535\n".to_vec();
+        wide.extend_from_slice(&[0x80, 0x00, 0xFF, 0xFF]);
+        assert_eq!(Image::decode_pnm(&wide).unwrap().pixels.data, vec![0xFF808080, 0xFFFFFFFF]);
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        for bytes in [colour().encode_ppm(true), gray().encode_pgm(true)] {
+            for length in 0..bytes.len() {
+                assert!(Image::decode_pnm(&bytes[..length]).is_err(), "length {}", length);
+            }
+        }
+        // Plain files end with a newline; dropping the last line loses a row of samples.
+        let plain = colour().encode_ppm(false);
+        let last_line = plain[..plain.len() - 1].iter().rposition(|b| *b == b'\n').unwrap();
+        assert!(Image::decode_pnm(&plain[..last_line]).is_err());
+    }
+
+    #[test]
+    fn corrupt_input_is_an_error() {
+        for bytes in [
+            &b"P7\n1 1\n255\n0"[..],
+            b"P3\n1 1\n0\n0 0 0",
+            b"P3\n1 1\n70000\n0 0 0",
+            b"P3\n1 1\n255\n0 256 0",
+            b"P3\n1 x\n255\n0 0 0",
+            b"P6\n99999999999 99999999999\n255\n",
+            b"P5\n18446744073709551615 2\n65535\n",
+            b"P5\n9223372036854775808 1\n65535\n",
+        ] {
+            assert!(Image::decode_pnm(bytes).is_err(), "{}", String::from_utf8_lossy(bytes));
+        }
+    }
+}
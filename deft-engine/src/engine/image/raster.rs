@@ -0,0 +1,127 @@
+use crate::engine::image::FillRule;
+use crate::linalg::Vec2;
+
+// Signed-area accumulation rasterizer. Each edge adds the area it covers to the cells it
+// crosses, and a running sum along the buffer then gives every pixel's coverage. Pixel
+// (x, y) is the unit square [x, x + 1) x [y, y + 1); closed outlines must be fed edge by edge.
+pub struct Rasterizer {
+    pub width: usize,
+    pub height: usize,
+    accumulation: Vec<f32>,
+}
+
+impl Rasterizer {
+    pub fn new(width: usize, height: usize) -> Rasterizer {
+        Rasterizer {
+            width,
+            height,
+            accumulation: vec![0.0; width * height + 2],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.accumulation.fill(0.0);
+    }
+
+    pub fn polygon(&mut self, points: &[Vec2]) {
+        for i in 0..points.len() {
+            self.line(points[i], points[(i + 1) % points.len()]);
+        }
+    }
+
+    // Parts of the edge left of the buffer are pushed onto x = 0 so the area they enclose
+    // still counts; parts right of it are pushed onto x = width, where they add nothing.
+    pub fn line(&mut self, from: Vec2, to: Vec2) {
+        let width = self.width as f32;
+        let mut cuts = vec![0.0, 1.0];
+        for edge in [0.0, width] {
+            let t = (edge - from.x) / (to.x - from.x);
+            if t > 0.0 && t < 1.0 {
+                cuts.push(t);
+            }
+        }
+        cuts.sort_by(|a, b| a.total_cmp(b));
+        for pair in cuts.windows(2) {
+            let mut start = from.lerp(to, pair[0]);
+            let mut end = from.lerp(to, pair[1]);
+            start.x = start.x.clamp(0.0, width);
+            end.x = end.x.clamp(0.0, width);
+            self.clipped_line(start, end);
+        }
+    }
+
+    fn clipped_line(&mut self, from: Vec2, to: Vec2) {
+        if (from.y - to.y).abs() <= f32::EPSILON {
+            return;
+        }
+        let (direction, from, to) = if from.y < to.y { (1.0, from, to) } else { (-1.0, to, from) };
+        let dxdy = (to.x - from.x) / (to.y - from.y);
+        let first_row = from.y.max(0.0) as usize;
+        let last_row = (to.y.ceil().max(0.0) as usize).min(self.height);
+        let mut x = from.x + (from.y.max(0.0) - from.y) * dxdy;
+
+        for row in first_row..last_row {
+            let line_start = row * self.width;
+            let dy = ((row + 1) as f32).min(to.y) - (row as f32).max(from.y);
+            // Clamped so rounding never pushes an index past the end of the row.
+            let x_next = (x + dxdy * dy).clamp(0.0, self.width as f32);
+            let d = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+
+            if x1i <= x0i + 1 {
+                let middle = 0.5 * (x + x_next) - x0_floor;
+                self.accumulation[line_start + x0i] += d - d * middle;
+                self.accumulation[line_start + x0i + 1] += d * middle;
+            } else {
+                let s = 1.0 / (x1 - x0);
+                let x0_fract = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0_fract) * (1.0 - x0_fract);
+                let x1_fract = x1 - x1_ceil + 1.0;
+                let am = 0.5 * s * x1_fract * x1_fract;
+                self.accumulation[line_start + x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    self.accumulation[line_start + x0i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0_fract);
+                    self.accumulation[line_start + x0i + 1] += d * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.accumulation[line_start + xi] += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.accumulation[line_start + x1i - 1] += d * (1.0 - a2 - am);
+                }
+                self.accumulation[line_start + x1i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+
+    // Calls f(x, y, coverage) for every pixel with non-zero coverage, in row order.
+    pub fn for_each_coverage<F: FnMut(usize, usize, f32)>(&self, f: F) {
+        self.for_each_coverage_with(FillRule::NonZero, f);
+    }
+
+    // The accumulated sum is the winding number where a pixel is wholly inside, and blends
+    // between windings along edges. NonZero caps its size at 1; EvenOdd folds it so odd
+    // windings are inside and even ones outside.
+    pub fn for_each_coverage_with<F: FnMut(usize, usize, f32)>(&self, rule: FillRule, mut f: F) {
+        let mut sum = 0.0;
+        for (i, value) in self.accumulation[..self.width * self.height].iter().enumerate() {
+            sum += value;
+            let coverage = match rule {
+                FillRule::NonZero => sum.abs().min(1.0),
+                FillRule::EvenOdd => {
+                    let folded = sum.abs() % 2.0;
+                    if folded > 1.0 { 2.0 - folded } else { folded }
+                }
+            };
+            if coverage > 1.0 / 512.0 {
+                f(i % self.width, i / self.width, coverage);
+            }
+        }
+    }
+}
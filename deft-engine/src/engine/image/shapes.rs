@@ -0,0 +1,303 @@
+use std::f32::consts::TAU;
+
+use crate::engine::game;
+use crate::engine::image::Image;
+
+impl Image {
+    // Midpoint circle around the pixel containing `center`.
+    pub fn draw_circle(&mut self, center: &game::Point, radius: u32, color: u32) {
+        let (cx, cy) = pixel(center);
+        for_each_octant_step(radius, |x, y| {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.plot(cx + dx, cy + dy, color);
+            }
+        });
+    }
+
+    pub fn fill_circle(&mut self, center: &game::Point, radius: u32, color: u32) {
+        let (cx, cy) = pixel(center);
+        for_each_octant_step(radius, |x, y| {
+            self.draw_span(cy + y, cx - x, cx + x, color);
+            self.draw_span(cy - y, cx - x, cx + x, color);
+            self.draw_span(cy + x, cx - y, cx + y, color);
+            self.draw_span(cy - x, cx - y, cx + y, color);
+        });
+    }
+
+    pub fn draw_ellipse(&mut self, center: &game::Point, radius_x: u32, radius_y: u32, color: u32) {
+        let (cx, cy) = pixel(center);
+        for_each_ellipse_step(radius_x, radius_y, |x, y| {
+            for (dx, dy) in [(x, y), (-x, y), (x, -y), (-x, -y)] {
+                self.plot(cx + dx, cy + dy, color);
+            }
+        });
+    }
+
+    pub fn fill_ellipse(&mut self, center: &game::Point, radius_x: u32, radius_y: u32, color: u32) {
+        let (cx, cy) = pixel(center);
+        for_each_ellipse_step(radius_x, radius_y, |x, y| {
+            self.draw_span(cy + y, cx - x, cx + x, color);
+            self.draw_span(cy - y, cx - x, cx + x, color);
+        });
+    }
+
+    // Angles are in radians from +x towards +y, which is clockwise on screen. The arc runs
+    // from the smaller angle to the larger; a sweep of a full turn or more draws the circle.
+    pub fn draw_arc(&mut self, center: &game::Point, radius: u32, start_angle: f32, end_angle: f32, color: u32) {
+        let (cx, cy) = pixel(center);
+        let start = start_angle.min(end_angle);
+        let sweep = (end_angle - start_angle).abs();
+        for_each_octant_step(radius, |x, y| {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                // The tolerance keeps end points on the axes from being lost to rounding.
+                let angle = (dy as f32).atan2(dx as f32);
+                if sweep >= TAU || (angle - start).rem_euclid(TAU) <= sweep + 1e-5 {
+                    self.plot(cx + dx, cy + dy, color);
+                }
+            }
+        });
+    }
+
+    // Corners are quarter circles of `radius`, clamped to fit in half the shorter side.
+    pub fn draw_rounded_rect(&mut self, x: i32, y: i32, width: u32, height: u32, radius: u32, color: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (left, top, right, bottom, r) = rounded_corners(x, y, width, height, radius);
+        self.draw_span(top - r, left, right, color);
+        self.draw_span(bottom + r, left, right, color);
+        for row in top..=bottom {
+            self.plot(left - r, row, color);
+            self.plot(right + r, row, color);
+        }
+        for_each_octant_step(r as u32, |dx, dy| {
+            for (dx, dy) in [(dx, dy), (dy, dx)] {
+                self.plot(right + dx, bottom + dy, color);
+                self.plot(left - dx, bottom + dy, color);
+                self.plot(right + dx, top - dy, color);
+                self.plot(left - dx, top - dy, color);
+            }
+        });
+    }
+
+    pub fn fill_rounded_rect(&mut self, x: i32, y: i32, width: u32, height: u32, radius: u32, color: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (left, top, right, bottom, r) = rounded_corners(x, y, width, height, radius);
+        for row in top..=bottom {
+            self.draw_span(row, left - r, right + r, color);
+        }
+        for_each_octant_step(r as u32, |dx, dy| {
+            for (dx, dy) in [(dx, dy), (dy, dx)] {
+                self.draw_span(top - dy, left - dx, right + dx, color);
+                self.draw_span(bottom + dy, left - dx, right + dx, color);
+            }
+        });
+    }
+}
+
+fn pixel(point: &game::Point) -> (i32, i32) {
+    (point.coord.x.floor() as i32, point.coord.y.floor() as i32)
+}
+
+// Centres of the four corner circles, plus the clamped radius.
+fn rounded_corners(x: i32, y: i32, width: u32, height: u32, radius: u32) -> (i32, i32, i32, i32, i32) {
+    let r = radius.min((width - 1) / 2).min((height - 1) / 2) as i32;
+    (x + r, y + r, x + width as i32 - 1 - r, y + height as i32 - 1 - r, r)
+}
+
+// Calls f(x, y) for the first octant (x >= y >= 0) of a midpoint circle.
+fn for_each_octant_step<F: FnMut(i32, i32)>(radius: u32, mut f: F) {
+    let (mut x, mut y) = (radius as i32, 0);
+    let mut error = 1 - x;
+    while x >= y {
+        f(x, y);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+// Calls f(x, y) for every step of the first quadrant of a midpoint ellipse.
+fn for_each_ellipse_step<F: FnMut(i32, i32)>(radius_x: u32, radius_y: u32, mut f: F) {
+    if radius_y == 0 {
+        (0..=radius_x as i32).for_each(|x| f(x, 0));
+        return;
+    }
+    let (a2, b2) = ((radius_x as i64).pow(2), (radius_y as i64).pow(2));
+    let (mut x, mut y) = (0i64, radius_y as i64);
+    let (mut dx, mut dy) = (0, 2 * a2 * y);
+
+    // Region 1: slope shallower than -1, step in x. Decisions are scaled by 4 to stay integral.
+    let mut decision = 4 * b2 - 4 * a2 * radius_y as i64 + a2;
+    while dx < dy {
+        f(x as i32, y as i32);
+        x += 1;
+        dx += 2 * b2;
+        if decision < 0 {
+            decision += 4 * (dx + b2);
+        } else {
+            y -= 1;
+            dy -= 2 * a2;
+            decision += 4 * (dx - dy + b2);
+        }
+    }
+
+    // Region 2: step in y.
+    let mut decision = b2 * (2 * x + 1) * (2 * x + 1) + 4 * a2 * (y - 1) * (y - 1) - 4 * a2 * b2;
+    while y >= 0 {
+        f(x as i32, y as i32);
+        y -= 1;
+        dy -= 2 * a2;
+        if decision > 0 {
+            decision += 4 * (a2 - dy);
+        } else {
+            x += 1;
+            dx += 2 * b2;
+            decision += 4 * (dx - dy + a2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn center(x: i32, y: i32) -> game::Point {
+        game::Point::new(x as f32 + 0.5, y as f32 + 0.5, 0.0)
+    }
+
+    // (x, y) of every pixel that was drawn.
+    fn lit(image: &Image) -> Vec<(i32, i32)> {
+        let mut pixels = Vec::new();
+        for row in 0..image.height {
+            for col in 0..image.width {
+                if image.get(row, col) != 0 {
+                    pixels.push((col as i32, row as i32));
+                }
+            }
+        }
+        pixels
+    }
+
+    // Checks the drawing is unchanged by mirroring about (8, 8), and by swapping axes when `square`.
+    fn assert_symmetric(draw: impl Fn(&mut Image), square: bool) {
+        let mut image = Image::new(17, 17);
+        draw(&mut image);
+        let pixels = lit(&image);
+        assert!(!pixels.is_empty());
+        for &(x, y) in &pixels {
+            assert!(pixels.contains(&(16 - x, y)) && pixels.contains(&(x, 16 - y)), "({x}, {y})");
+            assert!(!square || pixels.contains(&(y, x)), "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn shapes_are_symmetric() {
+        for radius in [1, 2, 5, 8] {
+            assert_symmetric(|image| image.draw_circle(&center(8, 8), radius, 1), true);
+            assert_symmetric(|image| image.fill_circle(&center(8, 8), radius, 1), true);
+            assert_symmetric(|image| image.draw_arc(&center(8, 8), radius, 0.0, TAU, 1), true);
+        }
+        assert_symmetric(|image| image.draw_ellipse(&center(8, 8), 7, 3, 1), false);
+        assert_symmetric(|image| image.fill_ellipse(&center(8, 8), 2, 6, 1), false);
+        assert_symmetric(|image| image.draw_rounded_rect(1, 3, 15, 11, 4, 1), false);
+        assert_symmetric(|image| image.fill_rounded_rect(2, 2, 13, 13, 5, 1), true);
+    }
+
+    #[test]
+    fn circle_outline_is_on_the_radius() {
+        let mut image = Image::new(17, 17);
+        image.draw_circle(&center(8, 8), 5, 1);
+        for (x, y) in lit(&image) {
+            let distance = (((x - 8).pow(2) + (y - 8).pow(2)) as f32).sqrt();
+            assert!((distance - 5.0).abs() < 0.5, "({x}, {y}) is {distance} away");
+        }
+        for (x, y) in [(13, 8), (3, 8), (8, 13), (8, 3)] {
+            assert_eq!(image.get(y as usize, x as usize), 1);
+        }
+    }
+
+    #[test]
+    fn zero_radius_is_one_pixel() {
+        let draws: [&dyn Fn(&mut Image); 5] = [
+            &|image| image.draw_circle(&center(2, 3), 0, 1),
+            &|image| image.fill_circle(&center(2, 3), 0, 1),
+            &|image| image.draw_ellipse(&center(2, 3), 0, 0, 1),
+            &|image| image.fill_ellipse(&center(2, 3), 0, 0, 1),
+            &|image| image.draw_arc(&center(2, 3), 0, 0.0, 1.0, 1),
+        ];
+        for draw in draws {
+            let mut image = Image::new(5, 5);
+            draw(&mut image);
+            assert_eq!(lit(&image), vec![(2, 3)]);
+        }
+
+        let mut image = Image::new(8, 5);
+        image.draw_ellipse(&center(3, 2), 3, 0, 1);
+        assert_eq!(lit(&image), (0..7).map(|x| (x, 2)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn rounded_rect_sizes() {
+        let mut image = Image::new(6, 6);
+        image.draw_rounded_rect(1, 1, 0, 4, 2, 1);
+        image.fill_rounded_rect(1, 1, 4, 0, 2, 1);
+        assert!(lit(&image).is_empty());
+
+        image.fill_rounded_rect(2, 3, 1, 1, 3, 1);
+        assert_eq!(lit(&image), vec![(2, 3)]);
+
+        // Radius 0 is a plain rectangle.
+        let mut outline = Image::new(6, 6);
+        outline.draw_rounded_rect(1, 1, 4, 3, 0, 1);
+        let mut expected: Vec<(i32, i32)> = (1..5).flat_map(|x| [(x, 1), (x, 3)]).chain([(1, 2), (4, 2)]).collect();
+        expected.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(lit(&outline), expected);
+
+        let mut filled = Image::new(6, 6);
+        filled.fill_rounded_rect(1, 1, 4, 3, 0, 1);
+        assert_eq!(lit(&filled).len(), 12);
+
+        // The radius is clamped, so corners are cut but the sides still touch the bounds.
+        let mut clamped = Image::new(8, 8);
+        clamped.fill_rounded_rect(0, 0, 8, 5, 100, 1);
+        let pixels = lit(&clamped);
+        assert!(!pixels.contains(&(0, 0)) && !pixels.contains(&(7, 4)));
+        assert!(pixels.contains(&(0, 2)) && pixels.contains(&(4, 0)) && pixels.contains(&(7, 2)) && pixels.contains(&(4, 4)));
+    }
+
+    #[test]
+    fn arc_endpoints() {
+        let quarter = |start: f32, end: f32| {
+            let mut image = Image::new(17, 17);
+            image.draw_arc(&center(8, 8), 5, start, end, 1);
+            lit(&image)
+        };
+
+        // From +x towards +y is clockwise on screen, so the first quarter is bottom right.
+        let pixels = quarter(0.0, FRAC_PI_2);
+        assert!(pixels.contains(&(13, 8)) && pixels.contains(&(8, 13)));
+        assert!(pixels.iter().all(|&(x, y)| x >= 8 && y >= 8));
+        assert_eq!(quarter(FRAC_PI_2, 0.0), pixels);
+
+        let pixels = quarter(PI, PI + FRAC_PI_2);
+        assert!(pixels.contains(&(3, 8)) && pixels.contains(&(8, 3)));
+        assert!(pixels.iter().all(|&(x, y)| x <= 8 && y <= 8));
+
+        // Negative angles wrap around through +x.
+        let pixels = quarter(-FRAC_PI_2, 0.1);
+        assert!(pixels.contains(&(8, 3)) && pixels.contains(&(13, 8)));
+        assert!(pixels.iter().all(|&(x, y)| x >= 8 && y <= 9));
+
+        let mut circle = Image::new(17, 17);
+        circle.draw_circle(&center(8, 8), 5, 1);
+        assert_eq!(quarter(1.0, 1.0 + 2.0 * TAU), lit(&circle));
+    }
+}
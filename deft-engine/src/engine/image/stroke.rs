@@ -0,0 +1,454 @@
+use std::f32::consts::TAU;
+
+use crate::engine::game;
+use crate::engine::image::fill::signed_area;
+use crate::engine::image::path::{push_point, Path, FLATTEN_TOLERANCE};
+use crate::engine::image::{FillRule, Image};
+use crate::linalg::Vec2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+// Miters longer than miter_limit times the width fall back to bevels, as in SVG. Dashes
+// alternate drawn and skipped lengths, starting dash_offset into the pattern; an odd
+// count is repeated to make it even, and no dashes means a solid line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f32,
+    pub dashes: Vec<f32>,
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> StrokeStyle {
+        StrokeStyle::new(1.0)
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> StrokeStyle {
+        StrokeStyle {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0
+        }
+    }
+
+    pub fn with_dashes(mut self, dashes: &[f32], offset: f32) -> StrokeStyle {
+        self.dashes = dashes.to_vec();
+        self.dash_offset = offset;
+        self
+    }
+
+    // How far past the centre line the stroke can reach, for sizing images.
+    pub fn reach(&self) -> f32 {
+        let half = self.width / 2.0;
+        match self.join {
+            LineJoin::Miter => half * self.miter_limit.max(std::f32::consts::SQRT_2),
+            _ => half * std::f32::consts::SQRT_2,
+        }
+    }
+}
+
+impl Image {
+    // Integer coordinates are pixel centres, as with draw_line.
+    pub fn stroke_line(&mut self, point1: &game::Point, point2: &game::Point, style: &StrokeStyle, color: u32) {
+        self.stroke_path(&centered_line(point1, point2), style, color);
+    }
+
+    pub fn stroke_line_aa(&mut self, point1: &game::Point, point2: &game::Point, style: &StrokeStyle, color: u32) {
+        self.stroke_path_aa(&centered_line(point1, point2), style, color);
+    }
+
+    pub fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, color: u32) {
+        self.fill_contours(&stroke_outline(path, style), FillRule::NonZero, color);
+    }
+
+    pub fn stroke_path_aa(&mut self, path: &Path, style: &StrokeStyle, color: u32) {
+        self.fill_contours_aa(&stroke_outline(path, style), FillRule::NonZero, color);
+    }
+}
+
+// The stroke as overlapping pieces: a quad per segment plus joins and caps. Every piece
+// winds the same way, so filling them with the non-zero rule paints their union once.
+pub fn stroke_outline(path: &Path, style: &StrokeStyle) -> Vec<Vec<Vec2>> {
+    let mut pieces = Vec::new();
+    for polyline in path.flatten(FLATTEN_TOLERANCE) {
+        if dashed(style) {
+            for dash in dash_polyline(&polyline.points, polyline.closed, &style.dashes, style.dash_offset) {
+                stroke_polyline(&mut pieces, &dash, false, style);
+            }
+        } else {
+            stroke_polyline(&mut pieces, &polyline.points, polyline.closed, style);
+        }
+    }
+    for piece in pieces.iter_mut() {
+        if signed_area(piece) < 0.0 {
+            piece.reverse();
+        }
+    }
+    pieces
+}
+
+pub fn stroke_polyline(pieces: &mut Vec<Vec<Vec2>>, points: &[Vec2], closed: bool, style: &StrokeStyle) {
+    let half = style.width / 2.0;
+    if half <= 0.0 || points.is_empty() {
+        return;
+    }
+    if points.len() == 1 {
+        match style.cap {
+            LineCap::Butt => {}
+            LineCap::Round => pieces.push(circle(points[0], half)),
+            LineCap::Square => {
+                let (corner, size) = (points[0] - Vec2::splat(half), Vec2::splat(half * 2.0));
+                pieces.push(vec![corner, corner + Vec2::new(size.x, 0.0), corner + size, corner + Vec2::new(0.0, size.y)]);
+            }
+        }
+        return;
+    }
+
+    let n = points.len();
+    let segments = if closed { n } else { n - 1 };
+    let direction = |i: usize| (points[(i + 1) % n] - points[i]).normalize();
+    for i in 0..segments {
+        let (from, to) = (points[i], points[(i + 1) % n]);
+        let offset = direction(i).perp() * half;
+        pieces.push(vec![from + offset, to + offset, to - offset, from - offset]);
+    }
+
+    let joins = if closed { 0..n } else { 1..n - 1 };
+    for i in joins {
+        let before = direction((i + n - 1) % n);
+        join(pieces, points[i], before, direction(i), style);
+    }
+
+    if !closed {
+        cap(pieces, points[0], -direction(0), style);
+        cap(pieces, points[n - 1], direction(n - 2), style);
+    }
+}
+
+fn join(pieces: &mut Vec<Vec<Vec2>>, point: Vec2, before: Vec2, after: Vec2, style: &StrokeStyle) {
+    let half = style.width / 2.0;
+    let turn = before.cross(after);
+    if turn.abs() < 1e-6 && before.dot(after) > 0.0 {
+        return;
+    }
+    // The gap to fill is on the outside of the turn.
+    let side = if turn > 0.0 { -half } else { half };
+    let (outer1, outer2) = (point + before.perp() * side, point + after.perp() * side);
+    match style.join {
+        LineJoin::Round => pieces.push(circle(point, half)),
+        LineJoin::Bevel => pieces.push(vec![point, outer1, outer2]),
+        LineJoin::Miter => {
+            let middle = (outer1 + outer2 - point * 2.0).normalize();
+            let cos = middle.dot(before.perp() * side) / half;
+            if cos > 0.0 && 1.0 / cos <= style.miter_limit {
+                pieces.push(vec![point, outer1, point + middle * (half / cos), outer2]);
+            } else {
+                pieces.push(vec![point, outer1, outer2]);
+            }
+        }
+    }
+}
+
+fn dashed(style: &StrokeStyle) -> bool {
+    style.dashes.iter().all(|length| *length >= 0.0) && style.dashes.iter().sum::<f32>() > 0.0
+}
+
+// Splits a polyline into the open runs that fall on drawn dashes. A zero length dash
+// gives a single point, which round or square caps turn into a dot.
+pub fn dash_polyline(points: &[Vec2], closed: bool, dashes: &[f32], offset: f32) -> Vec<Vec<Vec2>> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let pattern: Vec<f32> = if dashes.len() % 2 == 1 { dashes.repeat(2) } else { dashes.to_vec() };
+    let total: f32 = pattern.iter().sum();
+    let mut index = 0;
+    let mut remaining = offset.rem_euclid(total);
+    while remaining > 0.0 && remaining >= pattern[index] {
+        remaining -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    remaining = pattern[index] - remaining;
+
+    let mut runs = Vec::new();
+    let mut current = if index % 2 == 0 { vec![points[0]] } else { Vec::new() };
+    let segments = if closed { points.len() } else { points.len() - 1 };
+    for i in 0..segments {
+        let (from, to) = (points[i], points[(i + 1) % points.len()]);
+        let length = (to - from).length();
+        let mut travelled = 0.0;
+        while length - travelled > remaining {
+            travelled += remaining;
+            push_point(&mut current, from.lerp(to, travelled / length));
+            if index % 2 == 0 {
+                runs.push(std::mem::take(&mut current));
+            }
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+        remaining -= length - travelled;
+        if index % 2 == 0 {
+            push_point(&mut current, to);
+        }
+    }
+    if index % 2 == 0 && !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
+
+fn centered_line(point1: &game::Point, point2: &game::Point) -> Path {
+    let mut path = Path::new();
+    path.move_to(Vec2::new(point1.coord.x + 0.5, point1.coord.y + 0.5));
+    path.line_to(Vec2::new(point2.coord.x + 0.5, point2.coord.y + 0.5));
+    path
+}
+
+// `direction` points away from the line.
+fn cap(pieces: &mut Vec<Vec<Vec2>>, point: Vec2, direction: Vec2, style: &StrokeStyle) {
+    let half = style.width / 2.0;
+    let (ahead, side) = (direction * half, direction.perp() * half);
+    match style.cap {
+        LineCap::Butt => {}
+        LineCap::Round => pieces.push(circle(point, half)),
+        LineCap::Square => pieces.push(vec![point + side, point + side + ahead, point - side + ahead, point - side]),
+    }
+}
+
+// Enough sides that no edge strays more than the flattening tolerance from the circle,
+// with the corners pushed out so the polygon has the circle's area.
+pub fn circle(center: Vec2, radius: f32) -> Vec<Vec2> {
+    let sides = if radius > FLATTEN_TOLERANCE {
+        (TAU / (2.0 * (1.0 - FLATTEN_TOLERANCE / radius).acos())).ceil() as usize
+    } else {
+        8
+    };
+    let sides = sides.clamp(8, 256);
+    let corner = radius * (TAU / (sides as f32 * (TAU / sides as f32).sin())).sqrt();
+    (0..sides).map(|i| {
+        let angle = TAU * i as f32 / sides as f32;
+        center + Vec2::new(angle.cos(), angle.sin()) * corner
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y)
+    }
+
+    fn polyline(points: &[Vec2], closed: bool, style: &StrokeStyle) -> Vec<Vec<Vec2>> {
+        let mut pieces = Vec::new();
+        stroke_polyline(&mut pieces, points, closed, style);
+        pieces
+    }
+
+    fn styled(width: f32, cap: LineCap, join: LineJoin) -> StrokeStyle {
+        StrokeStyle {
+            cap,
+            join,
+            ..StrokeStyle::new(width)
+        }
+    }
+
+    // Same corners in any order or direction.
+    fn assert_corners(piece: &[Vec2], expected: &[Vec2]) {
+        assert_eq!(piece.len(), expected.len(), "{piece:?}");
+        for corner in expected {
+            assert!(piece.iter().any(|point| (*point - *corner).length() < 1e-4), "{corner:?} not in {piece:?}");
+        }
+    }
+
+    fn bounds(pieces: &[Vec<Vec2>]) -> (Vec2, Vec2) {
+        let points = pieces.iter().flatten();
+        let min = points.clone().fold(Vec2::splat(f32::MAX), |min, point| v(min.x.min(point.x), min.y.min(point.y)));
+        let max = points.fold(Vec2::splat(f32::MIN), |max, point| v(max.x.max(point.x), max.y.max(point.y)));
+        (min, max)
+    }
+
+    fn runs_x(runs: &[Vec<Vec2>]) -> Vec<Vec<f32>> {
+        runs.iter().map(|run| run.iter().map(|point| point.x).collect()).collect()
+    }
+
+    const LINE: [Vec2; 2] = [Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 10.0, y: 0.0 }];
+    const ELL: [Vec2; 3] = [Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 10.0, y: 0.0 }, Vec2 { x: 10.0, y: 10.0 }];
+
+    #[test]
+    fn caps() {
+        let pieces = polyline(&LINE, false, &styled(2.0, LineCap::Butt, LineJoin::Miter));
+        assert_eq!(pieces.len(), 1);
+        assert_corners(&pieces[0], &[v(0.0, 1.0), v(10.0, 1.0), v(10.0, -1.0), v(0.0, -1.0)]);
+
+        let pieces = polyline(&LINE, false, &styled(2.0, LineCap::Square, LineJoin::Miter));
+        assert_eq!(pieces.len(), 3);
+        assert_corners(&pieces[1], &[v(0.0, -1.0), v(-1.0, -1.0), v(-1.0, 1.0), v(0.0, 1.0)]);
+        assert_corners(&pieces[2], &[v(10.0, 1.0), v(11.0, 1.0), v(11.0, -1.0), v(10.0, -1.0)]);
+
+        let pieces = polyline(&LINE, false, &styled(2.0, LineCap::Round, LineJoin::Miter));
+        assert_eq!(pieces.len(), 3);
+        assert_eq!((pieces[1].clone(), pieces[2].clone()), (circle(v(0.0, 0.0), 1.0), circle(v(10.0, 0.0), 1.0)));
+        // Circle corners sit a little outside the radius to keep its area.
+        let (min, max) = bounds(&pieces);
+        assert!((min.x + 1.0).abs() < 0.1 && (max.x - 11.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn single_points() {
+        assert!(polyline(&[v(3.0, 4.0)], false, &styled(2.0, LineCap::Butt, LineJoin::Miter)).is_empty());
+        let square = polyline(&[v(3.0, 4.0)], false, &styled(2.0, LineCap::Square, LineJoin::Miter));
+        assert_corners(&square[0], &[v(2.0, 3.0), v(4.0, 3.0), v(4.0, 5.0), v(2.0, 5.0)]);
+        let round = polyline(&[v(3.0, 4.0)], false, &styled(2.0, LineCap::Round, LineJoin::Miter));
+        assert_eq!(round, vec![circle(v(3.0, 4.0), 1.0)]);
+
+        assert!(polyline(&LINE, false, &StrokeStyle::new(0.0)).is_empty());
+        assert!(polyline(&[], false, &StrokeStyle::new(1.0)).is_empty());
+    }
+
+    #[test]
+    fn joins() {
+        // The turn at (10, 0) is to the right on screen, so the gap is above and to the right.
+        let miter = polyline(&ELL, false, &styled(2.0, LineCap::Butt, LineJoin::Miter));
+        assert_eq!(miter.len(), 3);
+        assert_corners(&miter[2], &[v(10.0, 0.0), v(10.0, -1.0), v(11.0, -1.0), v(11.0, 0.0)]);
+
+        let bevel = polyline(&ELL, false, &styled(2.0, LineCap::Butt, LineJoin::Bevel));
+        assert_corners(&bevel[2], &[v(10.0, 0.0), v(10.0, -1.0), v(11.0, 0.0)]);
+
+        let round = polyline(&ELL, false, &styled(2.0, LineCap::Butt, LineJoin::Round));
+        assert_eq!(round[2], circle(v(10.0, 0.0), 1.0));
+
+        // Straight runs need no join.
+        let straight = [v(0.0, 0.0), v(5.0, 0.0), v(10.0, 0.0)];
+        assert_eq!(polyline(&straight, false, &styled(2.0, LineCap::Butt, LineJoin::Round)).len(), 2);
+    }
+
+    #[test]
+    fn sharp_miters_fall_back_to_bevels() {
+        // The miter is 1 / sin(half the angle) half widths long, about 10.15 here.
+        let sharp = [v(0.0, 0.0), v(10.0, 0.0), v(0.0, 2.0)];
+        let mut style = styled(2.0, LineCap::Butt, LineJoin::Miter);
+        let pieces = polyline(&sharp, false, &style);
+        assert_eq!(pieces[2].len(), 3);
+        assert_eq!(pieces[2][0], v(10.0, 0.0));
+
+        style.miter_limit = 11.0;
+        let pieces = polyline(&sharp, false, &style);
+        assert_eq!(pieces[2].len(), 4);
+        let tip = pieces[2][2];
+        assert!(((tip - v(10.0, 0.0)).length() - 10.15).abs() < 0.01, "{tip:?}");
+
+        // Doubling straight back has no miter at all.
+        let back = polyline(&[v(0.0, 0.0), v(10.0, 0.0), v(0.0, 0.0)], false, &style);
+        assert_eq!(back[2].len(), 3);
+    }
+
+    #[test]
+    fn closed_polylines_join_every_corner() {
+        let square = [v(0.0, 0.0), v(4.0, 0.0), v(4.0, 4.0), v(0.0, 4.0)];
+        let pieces = polyline(&square, true, &styled(2.0, LineCap::Round, LineJoin::Miter));
+        assert_eq!(pieces.len(), 8);
+        assert_eq!(bounds(&pieces), (v(-1.0, -1.0), v(5.0, 5.0)));
+    }
+
+    #[test]
+    fn outline_pieces_wind_the_same_way() {
+        let mut path = Path::new();
+        path.move_to(v(10.0, 10.0));
+        path.line_to(v(0.0, 10.0));
+        path.line_to(v(0.0, 0.0));
+        path.line_to(v(8.0, 3.0));
+        let style = styled(3.0, LineCap::Square, LineJoin::Bevel);
+        let pieces = stroke_outline(&path, &style);
+        assert_eq!(pieces.len(), 7);
+        assert!(pieces.iter().all(|piece| signed_area(piece) > 0.0));
+    }
+
+    #[test]
+    fn dash_runs() {
+        assert_eq!(runs_x(&dash_polyline(&LINE, false, &[2.0, 3.0], 0.0)), vec![vec![0.0, 2.0], vec![5.0, 7.0]]);
+
+        // Runs carry on around corners.
+        let runs = dash_polyline(&ELL, false, &[13.0, 2.0], 0.0);
+        assert_eq!(runs, vec![vec![v(0.0, 0.0), v(10.0, 0.0), v(10.0, 3.0)], vec![v(10.0, 5.0), v(10.0, 10.0)]]);
+
+        // Closed polylines dash the closing edge too.
+        let square = [v(0.0, 0.0), v(4.0, 0.0), v(4.0, 4.0), v(0.0, 4.0)];
+        let runs = dash_polyline(&square, true, &[2.0, 2.0], 0.0);
+        assert_eq!(runs.len(), 4);
+        assert_eq!(runs[3], vec![v(0.0, 4.0), v(0.0, 2.0)]);
+    }
+
+    #[test]
+    fn odd_dash_patterns_are_doubled() {
+        // [3] is read as [3, 3].
+        assert_eq!(runs_x(&dash_polyline(&LINE, false, &[3.0], 0.0)), vec![vec![0.0, 3.0], vec![6.0, 9.0]]);
+        // [1, 2, 3] is read as [1, 2, 3, 1, 2, 3].
+        assert_eq!(runs_x(&dash_polyline(&LINE, false, &[1.0, 2.0, 3.0], 0.0)), vec![vec![0.0, 1.0], vec![3.0, 6.0], vec![7.0, 9.0]]);
+    }
+
+    #[test]
+    fn dash_offsets() {
+        let runs = |offset: f32| runs_x(&dash_polyline(&LINE, false, &[2.0, 3.0], offset));
+        assert_eq!(runs(1.0), vec![vec![0.0, 1.0], vec![4.0, 6.0], vec![9.0, 10.0]]);
+        assert_eq!(runs(2.0), vec![vec![3.0, 5.0], vec![8.0, 10.0]]);
+        assert_eq!(runs(-1.0), vec![vec![1.0, 3.0], vec![6.0, 8.0]]);
+        assert_eq!(runs(11.0), runs(1.0));
+    }
+
+    #[test]
+    fn zero_length_dashes_are_dots() {
+        let runs = dash_polyline(&[v(0.0, 0.0), v(5.0, 0.0)], false, &[0.0, 2.0], 0.0);
+        assert_eq!(runs, vec![vec![v(0.0, 0.0)], vec![v(2.0, 0.0)], vec![v(4.0, 0.0)]]);
+
+        let mut path = Path::new();
+        path.move_to(v(0.0, 0.0));
+        path.line_to(v(5.0, 0.0));
+        let dotted = styled(1.0, LineCap::Round, LineJoin::Miter).with_dashes(&[0.0, 2.0], 0.0);
+        let pieces = stroke_outline(&path, &dotted);
+        assert_eq!(pieces, [0.0, 2.0, 4.0].map(|x| circle(v(x, 0.0), 0.5)).to_vec());
+
+        let butt = StrokeStyle::new(1.0).with_dashes(&[0.0, 2.0], 0.0);
+        assert!(stroke_outline(&path, &butt).is_empty());
+    }
+
+    #[test]
+    fn unusable_dash_patterns_are_solid() {
+        let mut path = Path::new();
+        path.move_to(v(0.0, 0.0));
+        path.line_to(v(10.0, 0.0));
+        for dashes in [&[0.0, 0.0][..], &[2.0, -1.0], &[]] {
+            let pieces = stroke_outline(&path, &StrokeStyle::new(2.0).with_dashes(dashes, 1.0));
+            assert_eq!(pieces.len(), 1, "{dashes:?}");
+        }
+    }
+
+    #[test]
+    fn circles_keep_their_area() {
+        for radius in [0.5, 3.0, 40.0] {
+            let points = circle(v(1.0, 2.0), radius);
+            let area = signed_area(&points);
+            assert!((area - std::f32::consts::PI * radius * radius).abs() < area * 1e-3, "{radius}");
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::engine::font::{Font, TextLayout};
+use crate::engine::image::Image;
+
+impl Image {
+    // (x, y) is the top-left of the first line.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, font: &dyn Font, color: u32) {
+        self.draw_text_scaled(x, y, text, font, color, 1);
+    }
+
+    // Every font pixel becomes a `scale` by `scale` block.
+    pub fn draw_text_scaled(&mut self, x: i32, y: i32, text: &str, font: &dyn Font, color: u32, scale: u32) {
+        let layout = TextLayout {
+            scale,
+            ..TextLayout::default()
+        };
+        self.draw_text_layout(x, y, text, font, &layout, color);
+    }
+
+    pub fn draw_text_layout(&mut self, x: i32, y: i32, text: &str, font: &dyn Font, layout: &TextLayout, color: u32) {
+        let scale = layout.scale.max(1) as i32;
+        font.layout(text, layout, &mut |glyph_x, glyph_y, glyph| {
+            for row in 0..glyph.height {
+                for col in 0..glyph.width {
+                    let coverage = glyph.coverage_at(col, row);
+                    if coverage == 0 {
+                        continue;
+                    }
+                    let left = x + (glyph_x + col as i32) * scale;
+                    let top = y + (glyph_y + row as i32) * scale;
+                    for py in top..top + scale {
+                        for px in left..left + scale {
+                            self.blend_pixel(px, py, color, coverage as f32 / 255.0);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::color;
+    use crate::engine::font::builtin;
+
+    const A: [&str; 8] = [
+        "..##....",
+        ".####...",
+        "##..##..",
+        "##..##..",
+        "######..",
+        "##..##..",
+        "##..##..",
+        "........",
+    ];
+
+    // '#' for every pixel in the block at (x, y), '.' for the rest.
+    fn pixels(image: &Image, x: usize, y: usize, width: usize, height: usize) -> Vec<String> {
+        (y..y + height).map(|row| {
+            (x..x + width).map(|col| if image.get(row, col) == 0 { '.' } else { '#' }).collect()
+        }).collect()
+    }
+
+    #[test]
+    fn draws_builtin_glyphs() {
+        let mut image = Image::new(20, 12);
+        image.draw_text(1, 2, "A", builtin(), color::WHITE);
+        assert_eq!(pixels(&image, 1, 2, 8, 8), A);
+        assert_eq!(image.get(3, 3), color::WHITE);
+        // Nothing lands outside the glyph's cell.
+        let drawn = image.pixels.data.iter().filter(|pixel| **pixel != 0).count();
+        assert_eq!(drawn, A.concat().matches('#').count());
+
+        // The second character starts one advance to the right.
+        let mut image = Image::new(20, 8);
+        image.draw_text(0, 0, " A", builtin(), color::WHITE);
+        assert_eq!(pixels(&image, 0, 0, 8, 8), vec!["........"; 8]);
+        assert_eq!(pixels(&image, 8, 0, 8, 8), A);
+    }
+
+    #[test]
+    fn scale_doubles_every_pixel() {
+        let mut image = Image::new(20, 20);
+        image.draw_text_scaled(2, 1, "A", builtin(), color::WHITE, 2);
+        let doubled: Vec<String> = A.iter()
+            .flat_map(|row| {
+                let row: String = row.chars().flat_map(|pixel| [pixel, pixel]).collect();
+                [row.clone(), row]
+            })
+            .collect();
+        assert_eq!(pixels(&image, 2, 1, 16, 16), doubled);
+
+        // Scale 0 draws at the font's size.
+        let (mut zero, mut one) = (Image::new(8, 8), Image::new(8, 8));
+        zero.draw_text_scaled(0, 0, "A", builtin(), color::WHITE, 0);
+        one.draw_text(0, 0, "A", builtin(), color::WHITE);
+        assert_eq!(zero.pixels.data, one.pixels.data);
+    }
+
+    #[test]
+    fn missing_characters_are_drawn_as_question_marks() {
+        let (mut missing, mut question) = (Image::new(16, 8), Image::new(16, 8));
+        missing.draw_text(0, 0, "\u{e9}\u{20ac}", builtin(), color::WHITE);
+        question.draw_text(0, 0, "??", builtin(), color::WHITE);
+        assert_eq!(missing.pixels.data, question.pixels.data);
+        assert!(question.pixels.data.iter().any(|pixel| *pixel != 0));
+    }
+
+    #[test]
+    fn text_is_clipped() {
+        let mut image = Image::new(4, 4);
+        image.draw_text(-2, -3, "A", builtin(), color::WHITE);
+        let expected: Vec<String> = A[3..7].iter().map(|row| row[2..6].to_string()).collect();
+        assert_eq!(pixels(&image, 0, 0, 4, 4), expected);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::engine::image::{Image, ImageError};
+
+const HEADER_SIZE: usize = 18;
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE: u8 = 8;
+const ALPHA_BITS: u8 = 0x0F;
+const RIGHT_TO_LEFT: u8 = 0x10;
+const TOP_TO_BOTTOM: u8 = 0x20;
+const MAX_PACKET: usize = 128;
+
+impl Image {
+    // Colour-mapped, true-colour and grayscale images, raw or RLE, in any origin corner.
+    pub fn decode_tga(bytes: &[u8]) -> Result<Image, ImageError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(ImageError::Decode("truncated TGA header".to_string()));
+        }
+        let id_length = bytes[0] as usize;
+        let map_type = bytes[1];
+        let image_type = bytes[2];
+        let map_first = u16::from_le_bytes([bytes[3], bytes[4]]) as usize;
+        let map_length = u16::from_le_bytes([bytes[5], bytes[6]]) as usize;
+        let map_depth = bytes[7];
+        let width = u16::from_le_bytes([bytes[12], bytes[13]]) as usize;
+        let height = u16::from_le_bytes([bytes[14], bytes[15]]) as usize;
+        let depth = bytes[16];
+        let descriptor = bytes[17];
+
+        let kind = image_type & !RLE;
+        let valid = match kind {
+            COLOR_MAPPED => map_type == 1 && matches!(depth, 8 | 16) && matches!(map_depth, 15 | 16 | 24 | 32),
+            TRUE_COLOR => matches!(depth, 15 | 16 | 24 | 32),
+            GRAYSCALE => matches!(depth, 8 | 16),
+            _ => false,
+        };
+        if !valid {
+            return Err(ImageError::Unsupported(format!("TGA type {} with {}-bit pixels", image_type, depth)));
+        }
+
+        let mut pos = HEADER_SIZE + id_length;
+        let mut palette = Vec::new();
+        if map_type == 1 {
+            let entry_size = (map_depth as usize).div_ceil(8);
+            let map = take(bytes, pos, map_length * entry_size)?;
+            palette = map.chunks_exact(entry_size).map(|entry| read_color(entry, map_depth, false, true)).collect();
+            pos += map_length * entry_size;
+        }
+
+        let has_alpha = descriptor & ALPHA_BITS != 0;
+        let pixel_size = (depth as usize).div_ceil(8);
+        let count = width * height;
+        let raw = if image_type & RLE != 0 {
+            decode_rle(bytes, pos, pixel_size, count)?
+        } else {
+            take(bytes, pos, count * pixel_size)?.to_vec()
+        };
+
+        let mut data = vec![0; count];
+        for (i, pixel) in raw.chunks_exact(pixel_size).enumerate() {
+            let (row, col) = (i / width, i % width);
+            let row = if descriptor & TOP_TO_BOTTOM != 0 { row } else { height - 1 - row };
+            let col = if descriptor & RIGHT_TO_LEFT != 0 { width - 1 - col } else { col };
+            data[row * width + col] = if kind == COLOR_MAPPED {
+                let index = if pixel_size == 1 { pixel[0] as usize } else { u16::from_le_bytes([pixel[0], pixel[1]]) as usize };
+                *index.checked_sub(map_first).and_then(|index| palette.get(index))
+                    .ok_or_else(|| ImageError::Decode(format!("colour map index {} out of range", index)))?
+            } else {
+                read_color(pixel, depth, kind == GRAYSCALE, has_alpha)
+            };
+        }
+        Ok(Image::from_data(width, height, data))
+    }
+
+    // True colour stored top to bottom, 32-bit if any pixel is translucent, otherwise 24-bit.
+    // Rows are run-length encoded when `rle` is set.
+    pub fn encode_tga(&self, rle: bool) -> Result<Vec<u8>, ImageError> {
+        if self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+            return Err(ImageError::Encode(format!("{}x{} is too large for TGA", self.width, self.height)));
+        }
+        let mut bytes = vec![0; HEADER_SIZE];
+        bytes[2] = if rle { TRUE_COLOR | RLE } else { TRUE_COLOR };
+        bytes[12..14].copy_from_slice(&(self.width as u16).to_le_bytes());
+        bytes[14..16].copy_from_slice(&(self.height as u16).to_le_bytes());
+        let alpha = !self.is_opaque();
+        bytes[16] = if alpha { 32 } else { 24 };
+        bytes[17] = if alpha { TOP_TO_BOTTOM | 8 } else { TOP_TO_BOTTOM };
+
+        let push_pixel = |bytes: &mut Vec<u8>, pixel: u32| {
+            let [b, g, r, a] = pixel.to_le_bytes();
+            bytes.extend_from_slice(&[b, g, r]);
+            if alpha {
+                bytes.push(a);
+            }
+        };
+        for row in self.pixels.rows_iter() {
+            if !rle {
+                row.iter().for_each(|pixel| push_pixel(&mut bytes, *pixel));
+                continue;
+            }
+            let mut i = 0;
+            while i < row.len() {
+                let run = row[i..].iter().take(MAX_PACKET).take_while(|pixel| **pixel == row[i]).count();
+                if run > 1 {
+                    bytes.push(0x80 | (run - 1) as u8);
+                    push_pixel(&mut bytes, row[i]);
+                    i += run;
+                    continue;
+                }
+                let mut end = i + 1;
+                while end < row.len() && end - i < MAX_PACKET && (end + 1 == row.len() || row[end] != row[end + 1]) {
+                    end += 1;
+                }
+                bytes.push((end - i - 1) as u8);
+                row[i..end].iter().for_each(|pixel| push_pixel(&mut bytes, *pixel));
+                i = end;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+fn take(bytes: &[u8], pos: usize, len: usize) -> Result<&[u8], ImageError> {
+    bytes.get(pos..pos + len).ok_or_else(|| ImageError::Decode("truncated TGA data".to_string()))
+}
+
+// Packets may run across rows, so the whole image is expanded as one stream.
+fn decode_rle(bytes: &[u8], mut pos: usize, pixel_size: usize, count: usize) -> Result<Vec<u8>, ImageError> {
+    // Each packet header expands to at most MAX_PACKET pixels, so a short file cannot ask
+    // for a huge buffer up front.
+    let remaining = bytes.len().saturating_sub(pos);
+    let mut raw = Vec::with_capacity((count * pixel_size).min(remaining * MAX_PACKET * pixel_size));
+    while raw.len() < count * pixel_size {
+        let header = take(bytes, pos, 1)?[0];
+        let length = (header & 0x7F) as usize + 1;
+        pos += 1;
+        if header & 0x80 != 0 {
+            let pixel = take(bytes, pos, pixel_size)?;
+            for _ in 0..length {
+                raw.extend_from_slice(pixel);
+            }
+            pos += pixel_size;
+        } else {
+            raw.extend_from_slice(take(bytes, pos, length * pixel_size)?);
+            pos += length * pixel_size;
+        }
+    }
+    raw.truncate(count * pixel_size);
+    Ok(raw)
+}
+
+fn read_color(pixel: &[u8], depth: u8, gray: bool, alpha: bool) -> u32 {
+    match (depth, gray) {
+        (8, true) => Image::rgb(pixel[0], pixel[0], pixel[0]),
+        (16, true) => Image::rgba(pixel[0], pixel[0], pixel[0], if alpha { pixel[1] } else { 255 }),
+        (15, _) | (16, _) => {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            let channel = |shift: u16| (((value >> shift) & 0x1F) as u32 * 255 / 31) as u8;
+            Image::rgb(channel(10), channel(5), channel(0))
+        }
+        (24, _) => Image::rgb(pixel[2], pixel[1], pixel[0]),
+        _ => Image::rgba(pixel[2], pixel[1], pixel[0], if alpha { pixel[3] } else { 255 }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs, lone pixels and a run crossing from the first row into the second.
+    fn opaque() -> Image {
+        let row = [0xFF102030, 0xFF102030, 0xFF102030, 0xFFFF0000, 0xFF00FF00, 0xFF0000FF, 0xFF0000FF];
+        let data = [row, row, [0xFF0000FF; 7]].concat();
+        Image::from_data(7, 3, data)
+    }
+
+    fn translucent() -> Image {
+        let mut image = opaque();
+        image.set(1, 4, 0x80FFFFFF);
+        image.set(2, 6, 0x00000000);
+        image
+    }
+
+    fn assert_same(decoded: &Image, expected: &Image) {
+        assert_eq!((decoded.width, decoded.height), (expected.width, expected.height));
+        assert_eq!(decoded.pixels.data, expected.pixels.data);
+    }
+
+    #[test]
+    fn round_trips_raw_and_rle() {
+        for image in [opaque(), translucent(), Image::new(0, 0), Image::from_data(200, 1, vec![0xFF123456; 200])] {
+            for rle in [false, true] {
+                let bytes = image.encode_tga(rle).unwrap();
+                assert_same(&Image::decode_tga(&bytes).unwrap(), &image);
+            }
+        }
+    }
+
+    #[test]
+    fn long_runs_compress() {
+        let image = Image::from_data(300, 2, vec![0xFF123456; 600]);
+        let bytes = image.encode_tga(true).unwrap();
+        // Three packets per row: 128, 128 and 44 pixels.
+        assert_eq!(bytes.len(), HEADER_SIZE + 6 * 4);
+    }
+
+    #[test]
+    fn reads_bottom_up_and_right_to_left() {
+        let image = translucent();
+        let mut bytes = image.encode_tga(false).unwrap();
+        bytes[17] = (bytes[17] & !TOP_TO_BOTTOM) | RIGHT_TO_LEFT;
+        let decoded = Image::decode_tga(&bytes).unwrap();
+        assert_eq!(decoded.get(0, 0), image.get(2, 6));
+        assert_eq!(decoded.get(2, 6), image.get(0, 0));
+    }
+
+    #[test]
+    fn reads_grayscale_and_colour_mapped() {
+        let mut gray = vec![0; HEADER_SIZE];
+        gray[2] = GRAYSCALE;
+        gray[12] = 2;
+        gray[14] = 1;
+        gray[16] = 8;
+        gray[17] = TOP_TO_BOTTOM;
+        gray.extend_from_slice(&[0, 200]);
+        assert_eq!(Image::decode_tga(&gray).unwrap().pixels.data, vec![0xFF000000, 0xFFC8C8C8]);
+
+        let mut mapped = vec![0; HEADER_SIZE];
+        mapped[1] = 1;
+        mapped[2] = COLOR_MAPPED;
+        mapped[3] = 1;
+        mapped[5] = 2;
+        mapped[7] = 24;
+        mapped[12] = 2;
+        mapped[14] = 1;
+        mapped[16] = 8;
+        mapped[17] = TOP_TO_BOTTOM;
+        mapped.extend_from_slice(&[0, 0, 255, 255, 0, 0, 2, 1]);
+        assert_eq!(Image::decode_tga(&mapped).unwrap().pixels.data, vec![0xFF0000FF, 0xFFFF0000]);
+        // Index 0 is below the first map entry.
+        mapped[HEADER_SIZE + 7] = 0;
+        assert!(matches!(Image::decode_tga(&mapped), Err(ImageError::Decode(_))));
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        for rle in [false, true] {
+            let bytes = translucent().encode_tga(rle).unwrap();
+            for length in 0..bytes.len() {
+                assert!(Image::decode_tga(&bytes[..length]).is_err(), "rle {} length {}", rle, length);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt_input_is_an_error() {
+        let mut bytes = opaque().encode_tga(true).unwrap();
+        bytes[16] = 12;
+        assert!(matches!(Image::decode_tga(&bytes), Err(ImageError::Unsupported(_))));
+        bytes[16] = 24;
+        bytes[2] = 5;
+        assert!(matches!(Image::decode_tga(&bytes), Err(ImageError::Unsupported(_))));
+
+        // The largest image TGA can describe, with one RLE packet of data.
+        let mut huge = vec![0; HEADER_SIZE];
+        huge[2] = TRUE_COLOR | RLE;
+        huge[12..16].copy_from_slice(&[0xFF; 4]);
+        huge[16] = 32;
+        huge.extend_from_slice(&[0xFF, 1, 2, 3, 4]);
+        assert!(matches!(Image::decode_tga(&huge), Err(ImageError::Decode(_))));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::engine::color;
+use crate::engine::game::DrawMode;
+use crate::engine::image::{ClipRect, Image};
+use crate::linalg::{Mat3, Vec2};
+
+// Bicubic uses the Catmull-Rom kernel, which passes through the original pixels but can
+// overshoot a little at hard edges; results are clamped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+    Bicubic,
+}
+
+impl Image {
+    // Colour at (x, y), where pixel (col, row) covers [col, col + 1) x [row, row + 1).
+    // Points beyond the edges take the nearest edge pixel. Filtering works on premultiplied
+    // colour so transparent pixels do not darken their neighbours.
+    pub fn sample(&self, x: f32, y: f32, filter: Filter) -> u32 {
+        if self.width == 0 || self.height == 0 {
+            return color::TRANSPARENT;
+        }
+        match filter {
+            Filter::Nearest => self.get_clamped(x.floor() as i32, y.floor() as i32),
+            Filter::Bilinear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (left, top) = (x.floor(), y.floor());
+                let (tx, ty) = (x - left, y - top);
+                self.weighted_sum(left as i32, top as i32, &[1.0 - tx, tx], &[1.0 - ty, ty])
+            }
+            Filter::Bicubic => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (left, top) = (x.floor(), y.floor());
+                self.weighted_sum(left as i32 - 1, top as i32 - 1, &catmull_rom(x - left), &catmull_rom(y - top))
+            }
+        }
+    }
+
+    // The whole image resampled to width by height.
+    pub fn scaled(&self, width: usize, height: usize, filter: Filter) -> Image {
+        let mut scaled = Image::new(width, height);
+        if self.width > 0 && self.height > 0 {
+            let scale = Vec2::new(width as f32 / self.width as f32, height as f32 / self.height as f32);
+            scaled.blit_transformed(self, &Mat3::scale_2d(scale), filter, DrawMode::Override);
+        }
+        scaled
+    }
+
+    // Rotated about its centre into an image just big enough to hold it. Angles are in
+    // radians, clockwise on screen as with Mat3::rotation_2d.
+    pub fn rotated(&self, angle: f32, filter: Filter) -> Image {
+        let center = Vec2::new(self.width as f32, self.height as f32) / 2.0;
+        let rotation = Mat3::rotation_2d(angle) * Mat3::translation_2d(-center);
+        let (min, max) = transformed_bounds(self, &rotation);
+        // Rounding error should not add a row or column at right angles.
+        let size = max - min;
+        let size = Vec2::new((size.x - 1e-3).ceil().max(0.0), (size.y - 1e-3).ceil().max(0.0));
+        let mut rotated = Image::new(size.x as usize, size.y as usize);
+        let transform = Mat3::translation_2d(size / 2.0) * rotation;
+        rotated.blit_transformed(self, &transform, filter, DrawMode::Override);
+        rotated
+    }
+
+    // Draws `source` turned by `angle` about its point `pivot`, with the pivot landing on
+    // `position`.
+    pub fn draw_rotated(&mut self, source: &Image, position: Vec2, pivot: Vec2, angle: f32, filter: Filter, mode: DrawMode) {
+        let transform = Mat3::translation_2d(position) * Mat3::rotation_2d(angle) * Mat3::translation_2d(-pivot);
+        self.blit_transformed(source, &transform, filter, mode);
+    }
+
+    // `transform` maps source pixel space into this image. Every destination pixel whose
+    // centre maps back inside the source is sampled and blended with `mode`, within the
+    // clip rectangle. Transforms that cannot be inverted draw nothing.
+    pub fn blit_transformed(&mut self, source: &Image, transform: &Mat3, filter: Filter, mode: DrawMode) {
+        let Some(inverse) = transform.inverse() else {
+            return;
+        };
+        let (min, max) = transformed_bounds(source, transform);
+        if ![min.x, min.y, max.x, max.y].iter().all(|value| value.is_finite()) {
+            return;
+        }
+        // Clamped while still floats, so huge bounds cannot overflow when cast.
+        let clip = self.clip_rect();
+        let horizontal = |value: f32| value.clamp(clip.x as f32, clip.right() as f32) as i32;
+        let vertical = |value: f32| value.clamp(clip.y as f32, clip.bottom() as f32) as i32;
+        let visible = ClipRect::from_edges(horizontal(min.x.floor()), vertical(min.y.floor()), horizontal(max.x.ceil()), vertical(max.y.ceil()));
+        let (width, height) = (source.width as f32, source.height as f32);
+        for y in visible.y..visible.bottom() {
+            for x in visible.x..visible.right() {
+                let point = inverse.transform_point2(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+                if point.x < 0.0 || point.y < 0.0 || point.x >= width || point.y >= height {
+                    continue;
+                }
+                let (row, col) = (y as u32, x as u32);
+                let blended = mode.blend(self.pixels.get(row, col), source.sample(point.x, point.y, filter));
+                self.pixels.set(row, col, blended);
+            }
+        }
+    }
+
+    fn get_clamped(&self, x: i32, y: i32) -> u32 {
+        let x = x.clamp(0, self.width as i32 - 1) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.get(y, x)
+    }
+
+    // Sum of the pixels from (left, top) onwards weighted by column and row weights.
+    fn weighted_sum(&self, left: i32, top: i32, column_weights: &[f32], row_weights: &[f32]) -> u32 {
+        let mut sum = [0.0; 4];
+        for (j, row_weight) in row_weights.iter().enumerate() {
+            for (i, column_weight) in column_weights.iter().enumerate() {
+                let weight = row_weight * column_weight;
+                let (a, r, g, b) = color::channels(self.get_clamped(left + i as i32, top + j as i32));
+                let alpha = a as f32 / 255.0;
+                sum[0] += weight * a as f32;
+                sum[1] += weight * r as f32 * alpha;
+                sum[2] += weight * g as f32 * alpha;
+                sum[3] += weight * b as f32 * alpha;
+            }
+        }
+        let alpha = sum[0].clamp(0.0, 255.0);
+        if alpha < 0.5 {
+            return color::TRANSPARENT;
+        }
+        let channel = |premultiplied: f32| (premultiplied * 255.0 / alpha).round().clamp(0.0, 255.0) as u8;
+        color::argb(alpha.round() as u8, channel(sum[1]), channel(sum[2]), channel(sum[3]))
+    }
+}
+
+// Weights of the four pixels around a point `t` of the way from the second to the third.
+fn catmull_rom(t: f32) -> [f32; 4] {
+    let (t2, t3) = (t * t, t * t * t);
+    [
+        (-t3 + 2.0 * t2 - t) / 2.0,
+        (3.0 * t3 - 5.0 * t2 + 2.0) / 2.0,
+        (-3.0 * t3 + 4.0 * t2 + t) / 2.0,
+        (t3 - t2) / 2.0,
+    ]
+}
+
+// Box around the source's corners once transformed.
+fn transformed_bounds(source: &Image, transform: &Mat3) -> (Vec2, Vec2) {
+    let (width, height) = (source.width as f32, source.height as f32);
+    let corners = [Vec2::ZERO, Vec2::new(width, 0.0), Vec2::new(0.0, height), Vec2::new(width, height)]
+        .map(|corner| transform.transform_point2(corner));
+    corners[1..].iter().fold((corners[0], corners[0]), |(min, max), corner| (min.min(*corner), max.max(*corner)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 2x2 image with a different opaque colour in each pixel.
+    fn quad() -> Image {
+        let mut image = Image::new(2, 2);
+        image.set(0, 0, 0xFFFF0000);
+        image.set(0, 1, 0xFF00FF00);
+        image.set(1, 0, 0xFF0000FF);
+        image.set(1, 1, 0xFFFFFFFF);
+        image
+    }
+
+    #[test]
+    fn identity_resampling_is_exact() {
+        let source = quad();
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic] {
+            let copy = source.scaled(2, 2, filter);
+            assert_eq!(copy.pixels.data, source.pixels.data, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn nearest_upscale_repeats_pixels() {
+        let scaled = quad().scaled(4, 4, Filter::Nearest);
+        assert_eq!(scaled.get(0, 1), 0xFFFF0000);
+        assert_eq!(scaled.get(0, 2), 0xFF00FF00);
+        assert_eq!(scaled.get(3, 3), 0xFFFFFFFF);
+    }
+
+    #[test]
+    fn bilinear_ignores_transparent_colour() {
+        let mut source = Image::new(2, 1);
+        source.set(0, 0, 0xFFFF0000);
+        source.set(0, 1, 0x0000FF00);
+        let middle = source.sample(1.0, 0.5, Filter::Bilinear);
+        assert_eq!(middle, 0x80FF0000);
+    }
+
+    #[test]
+    fn quarter_turn_swaps_size_and_moves_pixels() {
+        let mut source = Image::new(3, 1);
+        source.set(0, 0, 0xFFFF0000);
+        let rotated = source.rotated(std::f32::consts::FRAC_PI_2, Filter::Nearest);
+        assert_eq!((rotated.width, rotated.height), (1, 3));
+        assert_eq!(rotated.get(0, 0), 0xFFFF0000);
+        assert_eq!(rotated.get(2, 0), color::TRANSPARENT);
+    }
+
+    #[test]
+    fn huge_transforms_do_not_overflow() {
+        let mut image = Image::new(4, 4);
+        let source = quad();
+        for scale in [1e12, -1e12, 3e9] {
+            let transform = Mat3::scale_2d(Vec2::splat(scale));
+            image.blit_transformed(&source, &transform, Filter::Nearest, DrawMode::Override);
+        }
+        let far = Mat3::translation_2d(Vec2::new(-3e9, 5e9));
+        image.blit_transformed(&source, &far, Filter::Nearest, DrawMode::Override);
+        let covering = Mat3::translation_2d(Vec2::splat(-1e10)) * Mat3::scale_2d(Vec2::splat(1e10));
+        image.blit_transformed(&source, &covering, Filter::Nearest, DrawMode::Override);
+        assert_eq!(image.get(0, 0), 0xFFFFFFFF);
+    }
+}
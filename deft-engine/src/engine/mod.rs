@@ -1,11 +1,17 @@
-use minifb::{Key, Window, WindowOptions};
+use minifb::Key;
 
+use crate::linalg::Vec3;
+
+pub mod backend;
+pub mod color;
+pub mod font;
 pub mod game;
 pub mod image;
 pub mod physics;
+pub mod sprite;
 
 pub struct DWindow {
-    pub window: Window,
+    pub backend: Box<dyn backend::Backend>,
     pub image: image::Image,
     pub width: usize,
     pub height: usize,
@@ -16,15 +22,16 @@ pub struct DWindow {
 
 impl DWindow {
     pub fn new(title: &str, width: usize, height: usize) -> DWindow {
+        DWindow::with_backend(Box::new(backend::MinifbBackend::new(title, width, height)), width, height)
+    }
+
+    pub fn headless(width: usize, height: usize) -> DWindow {
+        DWindow::with_backend(Box::new(backend::HeadlessBackend::new()), width, height)
+    }
+
+    pub fn with_backend(backend: Box<dyn backend::Backend>, width: usize, height: usize) -> DWindow {
         DWindow {
-            window: Window::new(
-                title,
-                width,
-                height,
-                WindowOptions::default()
-            ).unwrap_or_else(|e| {
-                panic!("{}", e)
-            }),
+            backend,
             image: image::Image::new(width, height),
             width,
             height,
@@ -34,16 +41,21 @@ impl DWindow {
         }
     }
 
+    // The backend of a window made with `headless`, for scripting input and reading frames.
+    pub fn headless_backend(&self) -> Option<&backend::HeadlessBackend> {
+        self.backend.as_any().downcast_ref()
+    }
+
+    pub fn headless_backend_mut(&mut self) -> Option<&mut backend::HeadlessBackend> {
+        self.backend.as_any_mut().downcast_mut()
+    }
+
     pub fn clear(&mut self) {
-        for i in self.image.pixels.data.iter_mut() {
-            *i = 0x000000;
-        }
+        self.image.fill(color::TRANSPARENT);
     }
 
     pub fn set_color(&mut self, color: u32) {
-        for i in self.image.pixels.data.iter_mut() {
-            *i = color;
-        }
+        self.image.fill(color);
     }
 
     pub fn buffer(&self) -> Vec<u32> {
@@ -58,23 +70,24 @@ impl DWindow {
                 self.image.draw_object_2d_hollow(obj);
             }
         }
-        self.window.update_with_buffer(&self.buffer(), self.width, self.height).unwrap();
+        self.backend.present(&self.image.pixels.data, self.width, self.height);
     }
 
     pub fn is_open(&self) -> bool {
-        self.window.is_open()
+        self.backend.is_open()
     }
 
     pub fn is_key_down(&self, key: Key) -> bool {
-        self.window.is_key_down(key)
+        self.backend.is_key_down(key)
     }
 
     pub fn set_fps(&mut self, fps: u64) {
-        self.window.limit_update_rate(Some(std::time::Duration::from_micros(1/fps * 1000 * 1000)));
+        let frame_time = (1000 * 1000_u64).checked_div(fps).map(std::time::Duration::from_micros);
+        self.backend.set_frame_rate(frame_time);
     }
 
     pub fn set_pixel(&mut self, x: u32, y: u32, color: u32) {
-        self.image.pixels.set(y, x, color);
+        self.image.plot(x as i32, y as i32, color);
     }
 
     pub fn get_pixel(&self, x: u32, y: u32) -> u32 {
@@ -97,30 +110,9 @@ impl DWindow {
 }
 
 impl DWindow {
-    pub fn set_velocity(&mut self, index: usize, x: f32, y: f32, z: f32) {
-        let future_x = self.objects[index].coord().0 as f32 + x;
-        let future_y = self.objects[index].coord().1 as f32 + y;
-        let future_z = self.objects[index].coord().2 as f32 + z;
-
-        let width = self.objects[index as usize].size().0 as f32;
-        let height = self.objects[index as usize].size().1 as f32;
-        let depth = self.objects[index as usize].size().2 as f32;
-
-        if future_x >= 0.0 && future_x + width <= self.width as f32 - 1.0 {
-            println!("{}, {}, {}", self.objects[index as usize].coord().0, self.objects[index as usize].coord().1, self.objects[index as usize].coord().2);
-            self.objects[index].set_velocity(x, 0.0, 0.0);
-            self.objects[index].update();
-        }
-        if future_y >= 0.0 && future_y + height <= self.height as f32 - 1.0 {
-            println!("{}, {}, {}", self.objects[index as usize].coord().0, self.objects[index as usize].coord().1, self.objects[index as usize].coord().2);
-            self.objects[index].set_velocity(0.0, y, 0.0);
-            self.objects[index].update();
-        }
-        if future_z >= 0.0 && future_z + depth <= self.depth as f32 - 1.0 {
-            println!("{}, {}, {}", self.objects[index as usize].coord().0, self.objects[index as usize].coord().1, self.objects[index as usize].coord().2);
-            self.objects[index].set_velocity(0.0, 0.0, z);
-            self.objects[index].update();
-        }
+    pub fn set_velocity(&mut self, index: usize, velocity: Vec3) {
+        self.objects[index].set_velocity(velocity);
+        self.objects[index].update();
     }
 }
 
@@ -132,9 +124,39 @@ impl DWindow {
 
 pub fn main_loop(title: &str, fps: u64, width: usize, height: usize) {
     let mut new_window = DWindow::new(title, width, height);
-    new_window.set_fps(fps);
-    while new_window.is_open() && !new_window.is_key_down(Key::Escape) {
-        new_window.clear();
-        new_window.update();
+    run(&mut new_window, fps);
+}
+
+pub fn run(window: &mut DWindow, fps: u64) {
+    window.set_fps(fps);
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        window.clear();
+        window.update();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_run_ends_and_frames_can_be_read() {
+        let mut window = DWindow::headless(8, 8);
+        window.add_object(Box::new(game::Rect::new2d(2.0, 2.0, 3, 3, color::WHITE, game::DrawMode::Override, true)));
+        window.headless_backend_mut().unwrap().close_after(3);
+        run(&mut window, 0);
+        let backend = window.headless_backend().unwrap();
+        assert_eq!(backend.frame_count, 3);
+        let frame = backend.last_frame().unwrap();
+        assert_eq!(frame[3 * 8 + 3], color::WHITE);
+        assert_eq!(frame[0], color::TRANSPARENT);
+    }
+
+    #[test]
+    fn escape_in_the_script_stops_run() {
+        let mut window = DWindow::headless(4, 4);
+        window.headless_backend_mut().unwrap().script_input(vec![vec![], vec![], vec![Key::Escape]]);
+        run(&mut window, 0);
+        assert_eq!(window.headless_backend().unwrap().frame_count, 2);
+    }
+}
@@ -1,18 +1,49 @@
+use std::rc::Rc;
+
+use crate::engine::color;
 use crate::engine::game;
+use crate::engine::image::Image;
+use crate::linalg::{Vec2, Vec3};
+
+// Boxes are the axis-aligned bounds of coord and size; a box with no depth is flat. Ellipses
+// are flat, axis-aligned and tested in the xy plane only, as are masks, whose top-left pixel
+// sits at `min`. Anything flat is tested in the xy plane alone.
+pub enum CollisionShape {
+    Box {
+        min: Vec3,
+        max: Vec3
+    },
+    Ellipse {
+        center: Vec3,
+        radii: Vec2
+    },
+    Mask {
+        min: Vec3,
+        mask: Rc<CollisionMask>
+    },
+}
 
 pub trait CollisionObjectCommon {
-    fn coord(&self) -> (i32, i32, i32);
+    fn coord(&self) -> Vec3;
     fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool;
     fn size(&self) -> (u32, u32, u32);
     fn out_of_bounds(&self, width: usize, height: usize, _depth: usize) -> bool {
-        let (x, y, _z) = self.coord();
+        let coord = self.coord();
         let (w, h, _d) = self.size();
-        if x - 5 < 0 || x + w as i32 + 5 > width as i32 || y - 5 < 0 || y + h as i32 + 5 > height as i32 {
+        if coord.x - 5.0 < 0.0 || coord.x + w as f32 + 5.0 > width as f32 || coord.y - 5.0 < 0.0 || coord.y + h as f32 + 5.0 > height as f32 {
             println!("Out of bounds");
             return true;
         }
         false
     }
+
+    fn shape(&self) -> CollisionShape {
+        let min = self.coord();
+        CollisionShape::Box {
+            min,
+            max: min + size_vec(self.size())
+        }
+    }
 }
 
 pub struct PolygonCollision {
@@ -20,7 +51,7 @@ pub struct PolygonCollision {
 }
 
 impl CollisionObjectCommon for PolygonCollision {
-    fn coord(&self) -> (i32, i32, i32) {
+    fn coord(&self) -> Vec3 {
         self.points.coord()
     }
 
@@ -29,24 +60,19 @@ impl CollisionObjectCommon for PolygonCollision {
     }
 
     fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool {
-        self.points.min_x() < other.coord().0 + other.size().0 as i32 &&
-        self.points.max_x() > other.coord().0 &&
-        self.points.min_y() < other.coord().1 + other.size().1 as i32 &&
-        self.points.max_y() > other.coord().1 &&
-        self.points.min_z() < other.coord().2 + other.size().2 as i32 &&
-        self.points.max_z() > other.coord().2
+        shapes_overlap(&self.shape(), &other.shape())
     }
 }
 
 pub struct RectCollision {
-    pub coord: (i32, i32, i32),
+    pub coord: Vec3,
     pub width: u32,
     pub height: u32,
     pub depth: u32
 }
 
 impl CollisionObjectCommon for RectCollision {
-    fn coord(&self) -> (i32, i32, i32) {
+    fn coord(&self) -> Vec3 {
         self.coord
     }
 
@@ -55,24 +81,14 @@ impl CollisionObjectCommon for RectCollision {
     }
 
     fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool {
-        let (x1, y1, z1) = other.coord();
-        let (x2, y2, z2) = self.coord();
-        let (w1, h1, d1) = other.size();
-        let (w2, h2, d2) = self.size();
-
-        if x1 < x2 + w2 as i32 && x1 + w1 as i32 > x2 &&
-           y1 < y2 + h2 as i32 && y1 + h1 as i32 > y2 &&
-           z1 < z2 + d2 as i32 && z1 + d1 as i32 > z2 {
-            return true;
-        }
-        false
+        shapes_overlap(&self.shape(), &other.shape())
     }
 }
 
 impl RectCollision {
-    pub fn new(x: i32, y: i32, z: i32, width: u32, height: u32, depth: u32) -> RectCollision {
+    pub fn new(x: f32, y: f32, z: f32, width: u32, height: u32, depth: u32) -> RectCollision {
         RectCollision {
-            coord: (x, y, z),
+            coord: Vec3::new(x, y, z),
             width,
             height,
             depth
@@ -80,6 +96,347 @@ impl RectCollision {
     }
 }
 
+// Which pixels of an image are solid, for pixel-perfect collision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMask {
+    pub width: u32,
+    pub height: u32,
+    pub solid: Vec<bool>,
+}
+
+impl CollisionMask {
+    // Pixels whose alpha is above `alpha_threshold` are solid.
+    pub fn from_image(image: &Image, alpha_threshold: u8) -> CollisionMask {
+        let solid = image.pixels.data.iter().map(|pixel| color::alpha(*pixel) > alpha_threshold).collect();
+        CollisionMask {
+            width: image.width as u32,
+            height: image.height as u32,
+            solid
+        }
+    }
+
+    // Everything outside the mask is empty.
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height
+            && self.solid[(y as u32 * self.width + x as u32) as usize]
+    }
+}
+
+// A box that, given a mask, only collides where the mask is solid.
+pub struct MaskCollision {
+    pub coord: Vec3,
+    pub width: u32,
+    pub height: u32,
+    pub mask: Option<Rc<CollisionMask>>,
+}
+
+impl CollisionObjectCommon for MaskCollision {
+    fn coord(&self) -> Vec3 {
+        self.coord
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        (self.width, self.height, 0)
+    }
+
+    fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool {
+        shapes_overlap(&self.shape(), &other.shape())
+    }
+
+    fn shape(&self) -> CollisionShape {
+        match &self.mask {
+            Some(mask) => CollisionShape::Mask {
+                min: self.coord,
+                mask: mask.clone()
+            },
+            None => CollisionShape::Box {
+                min: self.coord,
+                max: self.coord + size_vec(self.size())
+            },
+        }
+    }
+}
+
+impl MaskCollision {
+    pub fn new(x: f32, y: f32, z: f32, width: u32, height: u32, mask: Option<Rc<CollisionMask>>) -> MaskCollision {
+        MaskCollision {
+            coord: Vec3::new(x, y, z),
+            width,
+            height,
+            mask
+        }
+    }
+}
+
 pub fn check_collision(obj1: &dyn CollisionObjectCommon, obj2: &dyn CollisionObjectCommon) -> bool {
     obj1.check_collision(obj2)
-}
\ No newline at end of file
+}
+
+pub fn size_vec(size: (u32, u32, u32)) -> Vec3 {
+    Vec3::new(size.0 as f32, size.1 as f32, size.2 as f32)
+}
+
+pub struct CircleCollision {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl CollisionObjectCommon for CircleCollision {
+    fn coord(&self) -> Vec3 {
+        self.center - Vec3::new(self.radius, self.radius, 0.0)
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        let diameter = (2.0 * self.radius) as u32;
+        (diameter, diameter, 0)
+    }
+
+    fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool {
+        shapes_overlap(&self.shape(), &other.shape())
+    }
+
+    fn shape(&self) -> CollisionShape {
+        CollisionShape::Ellipse {
+            center: self.center,
+            radii: Vec2::splat(self.radius)
+        }
+    }
+}
+
+pub struct EllipseCollision {
+    pub center: Vec3,
+    pub radius_x: f32,
+    pub radius_y: f32,
+}
+
+impl CollisionObjectCommon for EllipseCollision {
+    fn coord(&self) -> Vec3 {
+        self.center - Vec3::new(self.radius_x, self.radius_y, 0.0)
+    }
+
+    fn size(&self) -> (u32, u32, u32) {
+        ((2.0 * self.radius_x) as u32, (2.0 * self.radius_y) as u32, 0)
+    }
+
+    fn check_collision(&self, other: &dyn CollisionObjectCommon) -> bool {
+        shapes_overlap(&self.shape(), &other.shape())
+    }
+
+    fn shape(&self) -> CollisionShape {
+        CollisionShape::Ellipse {
+            center: self.center,
+            radii: Vec2::new(self.radius_x, self.radius_y)
+        }
+    }
+}
+
+pub fn shapes_overlap(a: &CollisionShape, b: &CollisionShape) -> bool {
+    match (a, b) {
+        (CollisionShape::Box { min: min1, max: max1 }, CollisionShape::Box { min: min2, max: max2 }) => {
+            let flat = min1.z == max1.z || min2.z == max2.z;
+            min1.x < max2.x && max1.x > min2.x &&
+            min1.y < max2.y && max1.y > min2.y &&
+            (flat || (min1.z < max2.z && max1.z > min2.z))
+        }
+        (CollisionShape::Ellipse { center, radii }, CollisionShape::Box { min, max }) |
+        (CollisionShape::Box { min, max }, CollisionShape::Ellipse { center, radii }) => {
+            // Scaling by 1 / radii turns the ellipse into a unit circle and keeps the box a box.
+            let closest = Vec2::new(center.x.clamp(min.x, max.x), center.y.clamp(min.y, max.y));
+            let offset = (closest - center.truncate()).div_elements(*radii);
+            offset.length_squared() < 1.0
+        }
+        (CollisionShape::Ellipse { center: center1, radii: radii1 }, CollisionShape::Ellipse { center: center2, radii: radii2 }) => {
+            // In the space where the first ellipse is the unit circle, they overlap when the
+            // origin is inside the second ellipse or closer than 1 to its boundary.
+            let offset = (center1.truncate() - center2.truncate()).div_elements(*radii1);
+            let radii = radii2.div_elements(*radii1);
+            offset.div_elements(radii).length_squared() < 1.0 || distance_to_ellipse(radii, offset) < 1.0
+        }
+        (CollisionShape::Mask { min: origin, mask }, CollisionShape::Box { min, max }) |
+        (CollisionShape::Box { min, max }, CollisionShape::Mask { min: origin, mask }) => {
+            let columns = pixel_range(min.x - origin.x, max.x - origin.x, mask.width);
+            let rows = pixel_range(min.y - origin.y, max.y - origin.y, mask.height);
+            rows.flat_map(|y| columns.clone().map(move |x| (x, y))).any(|(x, y)| mask.is_solid(x, y))
+        }
+        (CollisionShape::Mask { min: origin, mask }, ellipse @ CollisionShape::Ellipse { center, radii }) |
+        (ellipse @ CollisionShape::Ellipse { center, radii }, CollisionShape::Mask { min: origin, mask }) => {
+            let columns = pixel_range(center.x - radii.x - origin.x, center.x + radii.x - origin.x, mask.width);
+            let rows = pixel_range(center.y - radii.y - origin.y, center.y + radii.y - origin.y, mask.height);
+            rows.flat_map(|y| columns.clone().map(move |x| (x, y))).any(|(x, y)| {
+                let pixel = *origin + Vec3::new(x as f32, y as f32, 0.0);
+                mask.is_solid(x, y) && shapes_overlap(&CollisionShape::Box { min: pixel, max: pixel + Vec3::new(1.0, 1.0, 0.0) }, ellipse)
+            })
+        }
+        (CollisionShape::Mask { min: min1, mask: mask1 }, CollisionShape::Mask { min: min2, mask: mask2 }) => {
+            // Compared at the nearest whole-pixel offset.
+            let (dx, dy) = ((min2.x - min1.x).round() as i32, (min2.y - min1.y).round() as i32);
+            let columns = dx.max(0)..(dx + mask2.width as i32).min(mask1.width as i32);
+            let rows = dy.max(0)..(dy + mask2.height as i32).min(mask1.height as i32);
+            rows.flat_map(|y| columns.clone().map(move |x| (x, y)))
+                .any(|(x, y)| mask1.is_solid(x, y) && mask2.is_solid(x - dx, y - dy))
+        }
+    }
+}
+
+// The pixels of a mask row or column whose unit squares overlap the open interval (min, max).
+fn pixel_range(min: f32, max: f32, size: u32) -> std::ops::Range<i32> {
+    (min.floor().max(0.0) as i32)..(max.ceil().min(size as f32) as i32)
+}
+
+// Distance from a point outside an axis-aligned ellipse centred on the origin to its
+// boundary, by bisection (Eberly, "Distance from a Point to an Ellipse").
+fn distance_to_ellipse(radii: Vec2, point: Vec2) -> f32 {
+    let (e0, e1, y0, y1) = if radii.x >= radii.y {
+        (radii.x, radii.y, point.x.abs(), point.y.abs())
+    } else {
+        (radii.y, radii.x, point.y.abs(), point.x.abs())
+    };
+
+    if y1 > 0.0 {
+        if y0 > 0.0 {
+            let (z0, z1) = (y0 / e0, y1 / e1);
+            let ratio = (e0 / e1) * (e0 / e1);
+            let scaled = ratio * z0;
+            let (mut low, mut high) = (z1 - 1.0, Vec2::new(scaled, z1).length() - 1.0);
+            let mut s = low;
+            for _ in 0..64 {
+                s = 0.5 * (low + high);
+                if s == low || s == high {
+                    break;
+                }
+                let g = (scaled / (s + ratio)).powi(2) + (z1 / (s + 1.0)).powi(2) - 1.0;
+                if g > 0.0 {
+                    low = s;
+                } else if g < 0.0 {
+                    high = s;
+                } else {
+                    break;
+                }
+            }
+            let x0 = ratio * y0 / (s + ratio);
+            let x1 = y1 / (s + 1.0);
+            Vec2::new(x0 - y0, x1 - y1).length()
+        } else {
+            (y1 - e1).abs()
+        }
+    } else {
+        let numerator = e0 * y0;
+        let denominator = e0 * e0 - e1 * e1;
+        if numerator < denominator {
+            let x = numerator / denominator;
+            Vec2::new(e0 * x - y0, e1 * (1.0 - x * x).sqrt()).length()
+        } else {
+            (y0 - e0).abs()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat_box(x: f32, y: f32, width: f32, height: f32) -> CollisionShape {
+        CollisionShape::Box {
+            min: Vec3::new(x, y, 0.0),
+            max: Vec3::new(x + width, y + height, 0.0)
+        }
+    }
+
+    fn ellipse(x: f32, y: f32, radius_x: f32, radius_y: f32) -> CollisionShape {
+        CollisionShape::Ellipse {
+            center: Vec3::new(x, y, 0.0),
+            radii: Vec2::new(radius_x, radius_y)
+        }
+    }
+
+    // A 4x4 mask at (x, y) whose only solid pixels are the 2x2 block in its bottom-right.
+    fn corner_mask(x: f32, y: f32) -> CollisionShape {
+        let solid = (0..16).map(|i| i % 4 >= 2 && i / 4 >= 2).collect();
+        CollisionShape::Mask {
+            min: Vec3::new(x, y, 0.0),
+            mask: Rc::new(CollisionMask {
+                width: 4,
+                height: 4,
+                solid
+            })
+        }
+    }
+
+    fn overlap_both_ways(a: &CollisionShape, b: &CollisionShape) -> bool {
+        let forward = shapes_overlap(a, b);
+        assert_eq!(forward, shapes_overlap(b, a));
+        forward
+    }
+
+    #[test]
+    fn flat_boxes_overlap_in_xy() {
+        assert!(overlap_both_ways(&flat_box(0.0, 0.0, 10.0, 10.0), &flat_box(5.0, 5.0, 10.0, 10.0)));
+        assert!(!overlap_both_ways(&flat_box(0.0, 0.0, 10.0, 10.0), &flat_box(10.0, 0.0, 10.0, 10.0)));
+        assert!(!overlap_both_ways(&flat_box(0.0, 0.0, 10.0, 10.0), &flat_box(0.0, 20.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn deep_boxes_also_need_z_overlap() {
+        let deep = |z: f32| CollisionShape::Box {
+            min: Vec3::new(0.0, 0.0, z),
+            max: Vec3::new(10.0, 10.0, z + 5.0)
+        };
+        assert!(overlap_both_ways(&deep(0.0), &deep(4.0)));
+        assert!(!overlap_both_ways(&deep(0.0), &deep(5.0)));
+        // A flat box meets a deep one wherever their xy footprints overlap.
+        assert!(overlap_both_ways(&deep(50.0), &flat_box(5.0, 5.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn ellipse_and_box() {
+        let circle = ellipse(0.0, 0.0, 10.0, 5.0);
+        assert!(overlap_both_ways(&circle, &flat_box(8.0, -1.0, 5.0, 2.0)));
+        assert!(!overlap_both_ways(&circle, &flat_box(8.0, 4.0, 5.0, 5.0)));
+        assert!(!overlap_both_ways(&circle, &flat_box(0.0, 5.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn ellipse_and_ellipse() {
+        assert!(overlap_both_ways(&ellipse(0.0, 0.0, 10.0, 2.0), &ellipse(19.0, 0.0, 10.0, 2.0)));
+        assert!(!overlap_both_ways(&ellipse(0.0, 0.0, 10.0, 2.0), &ellipse(0.0, 5.0, 10.0, 2.0)));
+        // Diagonal neighbours whose bounding boxes overlap but whose curves do not.
+        assert!(!overlap_both_ways(&ellipse(0.0, 0.0, 5.0, 5.0), &ellipse(8.0, 8.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn mask_and_box() {
+        let mask = corner_mask(0.0, 0.0);
+        assert!(!overlap_both_ways(&mask, &flat_box(0.0, 0.0, 2.0, 2.0)));
+        assert!(overlap_both_ways(&mask, &flat_box(1.5, 1.5, 1.0, 1.0)));
+        assert!(!overlap_both_ways(&mask, &flat_box(4.0, 2.0, 3.0, 3.0)));
+    }
+
+    #[test]
+    fn mask_and_ellipse() {
+        let mask = corner_mask(0.0, 0.0);
+        assert!(!overlap_both_ways(&mask, &ellipse(0.0, 0.0, 2.0, 2.0)));
+        assert!(overlap_both_ways(&mask, &ellipse(0.0, 0.0, 3.5, 3.5)));
+        assert!(overlap_both_ways(&mask, &ellipse(3.0, 3.0, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn mask_and_mask() {
+        let mask = corner_mask(0.0, 0.0);
+        assert!(overlap_both_ways(&mask, &corner_mask(1.0, 1.0)));
+        assert!(!overlap_both_ways(&mask, &corner_mask(-2.0, -2.0)));
+        assert!(!overlap_both_ways(&mask, &corner_mask(2.0, 2.0)));
+    }
+
+    #[test]
+    fn flat_rect_collisions_meet() {
+        let a = RectCollision::new(0.0, 0.0, 0.0, 10, 10, 0);
+        let b = RectCollision::new(5.0, 5.0, 0.0, 10, 10, 0);
+        assert!(check_collision(&a, &b));
+        let circle = CircleCollision {
+            center: Vec3::new(12.0, 12.0, 0.0),
+            radius: 2.5
+        };
+        assert!(check_collision(&b, &circle));
+        assert!(!check_collision(&a, &circle));
+    }
+}
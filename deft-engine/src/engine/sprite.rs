@@ -0,0 +1,351 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::engine::image::{ClipRect, Image};
+use crate::engine::physics::CollisionMask;
+
+pub mod atlas;
+pub mod error;
+
+pub use error::SpriteSheetError;
+
+// How long a frame lasts when neither the caller nor the atlas says.
+pub const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+// Longest side of a frame once untrimmed.
+pub const MAX_FRAME_SIZE: u32 = 4096;
+
+// Where a frame's pixels are in the sheet. Atlases may trim transparent borders, so the
+// stored pixels sit at offset_x, offset_y inside a width by height frame, and may store
+// them turned 90 degrees clockwise, in which case `region` is the turned rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    pub region: ClipRect,
+    pub rotated: bool,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub duration: Duration,
+}
+
+impl Frame {
+    // An untrimmed, unrotated frame.
+    pub fn new(region: ClipRect, duration: Duration) -> Frame {
+        Frame {
+            region,
+            rotated: false,
+            offset_x: 0,
+            offset_y: 0,
+            width: region.width as u32,
+            height: region.height as u32,
+            duration
+        }
+    }
+
+    // Whether the frame can be drawn straight from its region of the sheet.
+    pub fn is_plain(&self) -> bool {
+        !self.rotated && self.offset_x == 0 && self.offset_y == 0
+            && self.region.width as u32 == self.width && self.region.height as u32 == self.height
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
+// A named run of frame indices into the sheet; a frame may appear more than once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clip {
+    pub frames: Vec<usize>,
+    pub mode: PlayMode,
+}
+
+impl Clip {
+    pub fn new(frames: Vec<usize>, mode: PlayMode) -> Clip {
+        Clip {
+            frames,
+            mode
+        }
+    }
+}
+
+// Frames and clips are checked as they are added, so every clip's frames exist. Trimmed or
+// turned frames are rebuilt upright once, when added; pixel masks are built the first time
+// they are asked for. Either way, showing a frame again only shares what was built.
+pub struct SpriteSheet {
+    image: Rc<Image>,
+    frames: Vec<Frame>,
+    upright: Vec<Option<Rc<Image>>>,
+    masks: RefCell<HashMap<MaskKey, Rc<CollisionMask>>>,
+    names: HashMap<String, usize>,
+    clips: HashMap<String, Clip>,
+}
+
+// Frame index, alpha threshold, and whether the frame is flipped in x and in y.
+type MaskKey = (usize, u8, bool, bool);
+
+impl SpriteSheet {
+    pub fn new(image: Rc<Image>) -> SpriteSheet {
+        SpriteSheet {
+            image,
+            frames: Vec::new(),
+            upright: Vec::new(),
+            masks: RefCell::new(HashMap::new()),
+            names: HashMap::new(),
+            clips: HashMap::new()
+        }
+    }
+
+    // Frames left to right, then top to bottom. `margin` surrounds the grid and `spacing`
+    // separates cells; partial cells at the edges are left out.
+    pub fn from_grid(image: Rc<Image>, frame_width: u32, frame_height: u32, margin: u32, spacing: u32, duration: Duration) -> SpriteSheet {
+        let mut sheet = SpriteSheet::new(image);
+        if frame_width == 0 || frame_height == 0 {
+            return sheet;
+        }
+        let cells = |size: usize, frame: u32| {
+            let usable = (size as u32).saturating_sub(2 * margin) + spacing;
+            usable / (frame + spacing)
+        };
+        let (columns, rows) = (cells(sheet.image.width, frame_width), cells(sheet.image.height, frame_height));
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = margin + column * (frame_width + spacing);
+                let y = margin + row * (frame_height + spacing);
+                let region = ClipRect::new(x as i32, y as i32, frame_width as i32, frame_height as i32);
+                // Whole cells lie inside the image, so they are always accepted.
+                sheet.push_frame(None, Frame::new(region, duration));
+            }
+        }
+        sheet
+    }
+
+    pub fn image(&self) -> &Rc<Image> {
+        &self.image
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    // The frame's stored pixels must lie inside the sheet image, and inside the frame once
+    // untrimmed; frames may be at most MAX_FRAME_SIZE on a side.
+    pub fn add_frame(&mut self, name: Option<&str>, frame: Frame) -> Result<usize, SpriteSheetError> {
+        let region = frame.region;
+        if region.width <= 0 || region.height <= 0 || region.intersect(&self.image.bounds()) != region {
+            return Err(SpriteSheetError::Invalid(format!("frame at {}, {} lies outside the image", region.x, region.y)));
+        }
+        if frame.width > MAX_FRAME_SIZE || frame.height > MAX_FRAME_SIZE {
+            return Err(SpriteSheetError::Invalid(format!("frame size {}x{} is over {}", frame.width, frame.height, MAX_FRAME_SIZE)));
+        }
+        let (width, height) = if frame.rotated {
+            (region.height, region.width)
+        } else {
+            (region.width, region.height)
+        };
+        let inside = frame.offset_x >= 0 && frame.offset_y >= 0
+            && frame.offset_x as i64 + width as i64 <= frame.width as i64
+            && frame.offset_y as i64 + height as i64 <= frame.height as i64;
+        if !inside {
+            return Err(SpriteSheetError::Invalid(format!("trimmed frame at {}, {} does not fit its full size", region.x, region.y)));
+        }
+        Ok(self.push_frame(name, frame))
+    }
+
+    fn push_frame(&mut self, name: Option<&str>, frame: Frame) -> usize {
+        let index = self.frames.len();
+        self.frames.push(frame);
+        self.upright.push(None);
+        if !frame.is_plain() {
+            self.upright[index] = Some(Rc::new(self.frame_image(index)));
+        }
+        if let Some(name) = name {
+            self.names.insert(name.to_string(), index);
+        }
+        index
+    }
+
+    // Every frame in the clip must already be in the sheet.
+    pub fn add_clip(&mut self, name: &str, clip: Clip) -> Result<(), SpriteSheetError> {
+        if let Some(index) = clip.frames.iter().find(|index| **index >= self.frames.len()) {
+            return Err(SpriteSheetError::Invalid(format!("clip {} uses missing frame {}", name, index)));
+        }
+        self.clips.insert(name.to_string(), clip);
+        Ok(())
+    }
+
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&Clip> {
+        self.clips.get(name)
+    }
+
+    // The image a frame is drawn from and its region of it: the sheet itself for plain
+    // frames, or the frame's upright copy.
+    pub fn frame_source(&self, index: usize) -> (Rc<Image>, ClipRect) {
+        match &self.upright[index] {
+            Some(upright) => (upright.clone(), upright.bounds()),
+            None => (self.image.clone(), self.frames[index].region),
+        }
+    }
+
+    // The frame's pixel mask, flipped as given; see CollisionMask::from_image.
+    pub fn frame_mask(&self, index: usize, alpha_threshold: u8, flip_x: bool, flip_y: bool) -> Rc<CollisionMask> {
+        let key = (index, alpha_threshold, flip_x, flip_y);
+        if let Some(mask) = self.masks.borrow().get(&key) {
+            return mask.clone();
+        }
+        let (source, region) = self.frame_source(index);
+        let (width, height) = (region.width as usize, region.height as usize);
+        let mut frame = Image::new(width, height);
+        for row in 0..height {
+            let source_row = region.y as usize + if flip_y { height - 1 - row } else { row };
+            for col in 0..width {
+                let source_col = region.x as usize + if flip_x { width - 1 - col } else { col };
+                frame.set(row, col, source.get(source_row, source_col));
+            }
+        }
+        let mask = Rc::new(CollisionMask::from_image(&frame, alpha_threshold));
+        self.masks.borrow_mut().insert(key, mask.clone());
+        mask
+    }
+
+    // The frame at its full size, untrimmed and turned back upright.
+    pub fn frame_image(&self, index: usize) -> Image {
+        let frame = &self.frames[index];
+        let mut image = Image::new(frame.width as usize, frame.height as usize);
+        let region = frame.region.intersect(&self.image.bounds());
+        // Stored size, before any turn.
+        let (width, height) = if frame.rotated {
+            (frame.region.height, frame.region.width)
+        } else {
+            (frame.region.width, frame.region.height)
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let (source_x, source_y) = if frame.rotated {
+                    (frame.region.x + frame.region.width - 1 - y, frame.region.y + x)
+                } else {
+                    (frame.region.x + x, frame.region.y + y)
+                };
+                if region.contains(source_x, source_y) {
+                    let pixel = self.image.get(source_y as usize, source_x as usize);
+                    image.plot(frame.offset_x + x, frame.offset_y + y, pixel);
+                }
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: u32 = 0xFFFF0000;
+    const BLUE: u32 = 0xFF0000FF;
+
+    // 4x2: a red column and a blue column on the left, transparent on the right.
+    fn sheet_image() -> Rc<Image> {
+        let mut image = Image::new(4, 2);
+        for row in 0..2 {
+            image.set(row, 0, RED);
+            image.set(row, 1, BLUE);
+        }
+        Rc::new(image)
+    }
+
+    #[test]
+    fn grid_cuts_whole_cells() {
+        let sheet = SpriteSheet::from_grid(sheet_image(), 1, 2, 0, 1, DEFAULT_FRAME_DURATION);
+        let regions: Vec<ClipRect> = sheet.frames().iter().map(|frame| frame.region).collect();
+        assert_eq!(regions, vec![ClipRect::new(0, 0, 1, 2), ClipRect::new(2, 0, 1, 2)]);
+        let (source, region) = sheet.frame_source(1);
+        assert!(Rc::ptr_eq(&source, sheet.image()));
+        assert_eq!(region, ClipRect::new(2, 0, 1, 2));
+    }
+
+    #[test]
+    fn add_frame_checks_the_frame() {
+        let mut sheet = SpriteSheet::new(sheet_image());
+        let outside = Frame::new(ClipRect::new(3, 0, 2, 2), DEFAULT_FRAME_DURATION);
+        assert!(matches!(sheet.add_frame(None, outside), Err(SpriteSheetError::Invalid(_))));
+        let mut huge = Frame::new(ClipRect::new(0, 0, 1, 1), DEFAULT_FRAME_DURATION);
+        huge.width = 100000;
+        huge.height = 100000;
+        assert!(matches!(sheet.add_frame(None, huge), Err(SpriteSheetError::Invalid(_))));
+        let mut spilling = Frame::new(ClipRect::new(0, 0, 2, 2), DEFAULT_FRAME_DURATION);
+        spilling.offset_x = 1;
+        assert!(matches!(sheet.add_frame(None, spilling), Err(SpriteSheetError::Invalid(_))));
+        assert!(sheet.frames().is_empty());
+    }
+
+    #[test]
+    fn add_clip_needs_existing_frames() {
+        let mut sheet = SpriteSheet::from_grid(sheet_image(), 1, 1, 0, 0, DEFAULT_FRAME_DURATION);
+        assert_eq!(sheet.frames().len(), 8);
+        assert!(sheet.add_clip("walk", Clip::new(vec![0, 7, 0], PlayMode::Loop)).is_ok());
+        assert!(matches!(sheet.add_clip("jump", Clip::new(vec![1, 8], PlayMode::Once)), Err(SpriteSheetError::Invalid(_))));
+        assert!(sheet.clip("walk").is_some());
+        assert!(sheet.clip("jump").is_none());
+    }
+
+    #[test]
+    fn trimmed_and_turned_frames_are_built_once() {
+        let mut sheet = SpriteSheet::new(sheet_image());
+        // Stored turned as a 1x2 column; upright it is red then blue in a 3x2 frame, one
+        // pixel in from the left.
+        let frame = Frame {
+            region: ClipRect::new(0, 0, 1, 2),
+            rotated: true,
+            offset_x: 1,
+            offset_y: 1,
+            width: 3,
+            height: 2,
+            duration: DEFAULT_FRAME_DURATION
+        };
+        let index = sheet.add_frame(Some("turned"), frame).unwrap();
+        let (first, region) = sheet.frame_source(index);
+        let (second, _) = sheet.frame_source(index);
+        assert!(Rc::ptr_eq(&first, &second));
+        assert_eq!(region, ClipRect::new(0, 0, 3, 2));
+        assert_eq!(first.pixels.data, vec![0, 0, 0, 0, RED, RED]);
+
+        let mask = sheet.frame_mask(index, 0, false, false);
+        assert!(Rc::ptr_eq(&mask, &sheet.frame_mask(index, 0, false, false)));
+        assert_eq!(mask.solid, vec![false, false, false, false, true, true]);
+        let flipped = sheet.frame_mask(index, 0, true, true);
+        assert_eq!(flipped.solid, vec![true, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn atlas_frames_tags_and_limits() {
+        let atlas = r#"{
+            "frames": {
+                "a": { "frame": { "x": 0, "y": 0, "w": 1, "h": 2 } },
+                "b": { "frame": { "x": 1, "y": 0, "w": 1, "h": 2 }, "duration": 50 }
+            },
+            "meta": { "frameTags": [{ "name": "idle", "from": 0, "to": 1, "direction": "pingpong" }] }
+        }"#;
+        let sheet = SpriteSheet::parse_json(atlas, sheet_image()).unwrap();
+        assert_eq!(sheet.frame_index("b"), Some(1));
+        assert_eq!(sheet.frames()[1].duration, Duration::from_millis(50));
+        assert_eq!(sheet.clip("idle"), Some(&Clip::new(vec![0, 1], PlayMode::PingPong)));
+
+        let huge = r#"{ "frames": [{ "frame": { "x": 0, "y": 0, "w": 1, "h": 1 }, "sourceSize": { "w": 100000, "h": 100000 } }] }"#;
+        assert!(matches!(SpriteSheet::parse_json(huge, sheet_image()), Err(SpriteSheetError::Invalid(_))));
+        let past_end = r#"{ "frames": [{ "frame": { "x": 0, "y": 0, "w": 1, "h": 1 } }],
+            "meta": { "frameTags": [{ "name": "idle", "from": 0, "to": 3 }] } }"#;
+        assert!(SpriteSheet::parse_json(past_end, sheet_image()).is_err());
+        let outside = r#"{ "frames": [{ "frame": { "x": 3, "y": 0, "w": 2, "h": 1 } }] }"#;
+        assert!(SpriteSheet::parse_json(outside, sheet_image()).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::engine::image::{ClipRect, Image};
+use crate::engine::sprite::{Clip, Frame, PlayMode, SpriteSheet, SpriteSheetError, DEFAULT_FRAME_DURATION};
+
+impl SpriteSheet {
+    // JSON atlases as exported by TexturePacker and Aseprite, with frames as an array or a
+    // hash. The sheet image named in meta.image is found next to the .json file.
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<SpriteSheet, SpriteSheetError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let atlas: Value = serde_json::from_str(&text)?;
+        let file = atlas["meta"]["image"].as_str()
+            .ok_or_else(|| SpriteSheetError::Parse("missing meta.image".to_string()))?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let image = Image::load(directory.join(file))?;
+        SpriteSheet::from_atlas(&atlas, Rc::new(image))
+    }
+
+    pub fn parse_json(text: &str, image: Rc<Image>) -> Result<SpriteSheet, SpriteSheetError> {
+        SpriteSheet::from_atlas(&serde_json::from_str(text)?, image)
+    }
+
+    // Aseprite's frame tags become clips. They loop unless exported with a repeat of 1.
+    fn from_atlas(atlas: &Value, image: Rc<Image>) -> Result<SpriteSheet, SpriteSheetError> {
+        let mut sheet = SpriteSheet::new(image);
+        match &atlas["frames"] {
+            Value::Object(frames) => {
+                for (name, entry) in frames {
+                    sheet.add_frame(Some(name), parse_frame(entry)?)?;
+                }
+            }
+            Value::Array(frames) => {
+                for entry in frames {
+                    sheet.add_frame(entry["filename"].as_str(), parse_frame(entry)?)?;
+                }
+            }
+            _ => return Err(SpriteSheetError::Parse("missing frames".to_string())),
+        }
+
+        if let Some(tags) = atlas["meta"]["frameTags"].as_array() {
+            for tag in tags {
+                let name = tag["name"].as_str()
+                    .ok_or_else(|| SpriteSheetError::Parse("frame tag without a name".to_string()))?;
+                let (from, to) = (integer(tag, "from")? as usize, integer(tag, "to")? as usize);
+                if from > to {
+                    return Err(SpriteSheetError::Parse(format!("frame tag {} is out of range", name)));
+                }
+                let mut frames: Vec<usize> = (from..=to).collect();
+                let direction = tag["direction"].as_str().unwrap_or("forward");
+                if direction.ends_with("reverse") {
+                    frames.reverse();
+                }
+                let mode = if direction.starts_with("pingpong") {
+                    PlayMode::PingPong
+                } else if tag["repeat"].as_str() == Some("1") {
+                    PlayMode::Once
+                } else {
+                    PlayMode::Loop
+                };
+                sheet.add_clip(name, Clip::new(frames, mode))?;
+            }
+        }
+        Ok(sheet)
+    }
+}
+
+// Where the frame lies in the image and how big it is are checked by SpriteSheet::add_frame.
+fn parse_frame(entry: &Value) -> Result<Frame, SpriteSheetError> {
+    let stored = &entry["frame"];
+    let (x, y) = (integer(stored, "x")?, integer(stored, "y")?);
+    let (width, height) = (integer(stored, "w")?, integer(stored, "h")?);
+    let rotated = entry["rotated"].as_bool().unwrap_or(false);
+    // The frame's w and h are its upright size, so a turned frame is stored h wide and w tall.
+    let region = if rotated {
+        ClipRect::new(x as i32, y as i32, height as i32, width as i32)
+    } else {
+        ClipRect::new(x as i32, y as i32, width as i32, height as i32)
+    };
+
+    let (offset_x, offset_y) = match entry.get("spriteSourceSize") {
+        Some(trim) => (integer(trim, "x")?, integer(trim, "y")?),
+        None => (0, 0),
+    };
+    let (full_width, full_height) = match entry.get("sourceSize") {
+        Some(size) => (integer(size, "w")?, integer(size, "h")?),
+        None => (width, height),
+    };
+    let duration = match entry.get("duration") {
+        Some(_) => Duration::from_millis(integer(entry, "duration")? as u64),
+        None => DEFAULT_FRAME_DURATION,
+    };
+    Ok(Frame {
+        region,
+        rotated,
+        offset_x: offset_x as i32,
+        offset_y: offset_y as i32,
+        width: full_width,
+        height: full_height,
+        duration
+    })
+}
+
+// Non-negative whole numbers only; atlas coordinates and sizes are never negative.
+fn integer(object: &Value, key: &str) -> Result<u32, SpriteSheetError> {
+    let value = object.get(key)
+        .ok_or_else(|| SpriteSheetError::Parse(format!("missing {}", key)))?;
+    value.as_u64()
+        .and_then(|number| u32::try_from(number).ok())
+        .ok_or_else(|| SpriteSheetError::Parse(format!("{} is not a non-negative integer: {}", key, value)))
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::io;
+
+use crate::engine::image::ImageError;
+
+#[derive(Debug)]
+pub enum SpriteSheetError {
+    Io(io::Error),
+    Image(ImageError),
+    Json(serde_json::Error),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for SpriteSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpriteSheetError::Io(e) => write!(f, "I/O error: {}", e),
+            SpriteSheetError::Image(e) => write!(f, "Failed to load sprite sheet image: {}", e),
+            SpriteSheetError::Json(e) => write!(f, "Invalid atlas JSON: {}", e),
+            SpriteSheetError::Parse(message) => write!(f, "Failed to parse atlas: {}", message),
+            SpriteSheetError::Invalid(message) => write!(f, "Invalid sprite sheet: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SpriteSheetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpriteSheetError::Io(e) => Some(e),
+            SpriteSheetError::Image(e) => Some(e),
+            SpriteSheetError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SpriteSheetError {
+    fn from(e: io::Error) -> SpriteSheetError {
+        SpriteSheetError::Io(e)
+    }
+}
+
+impl From<ImageError> for SpriteSheetError {
+    fn from(e: ImageError) -> SpriteSheetError {
+        SpriteSheetError::Image(e)
+    }
+}
+
+impl From<serde_json::Error> for SpriteSheetError {
+    fn from(e: serde_json::Error) -> SpriteSheetError {
+        SpriteSheetError::Json(e)
+    }
+}
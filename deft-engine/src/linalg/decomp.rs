@@ -0,0 +1,409 @@
+use crate::linalg::numeric::Float;
+use crate::linalg::{LinalgError, Matrix};
+
+const MAX_JACOBI_SWEEPS: usize = 100;
+
+// PA = LU with L unit lower triangular, both packed into `lu`.
+// `permutation[i]` is the row of A that ended up in row i.
+pub struct Lu<T: Float> {
+    pub lu: Matrix<T>,
+    pub permutation: Vec<usize>,
+    pub sign: T,
+}
+
+// A = QR with Q orthogonal (rows x rows) and R upper triangular (rows x cols).
+pub struct Qr<T: Float> {
+    pub q: Matrix<T>,
+    pub r: Matrix<T>,
+}
+
+// Eigenvalues in ascending order; column i of `vectors` belongs to `values[i]`.
+pub struct SymmetricEigen<T: Float> {
+    pub values: Vec<T>,
+    pub vectors: Matrix<T>,
+}
+
+impl<T: Float> Lu<T> {
+    pub fn size(&self) -> usize {
+        self.lu.rows as usize
+    }
+
+    pub fn determinant(&self) -> T {
+        let n = self.size();
+        let mut det = self.sign;
+        for i in 0..n {
+            det *= self.lu.data[i * n + i];
+        }
+        det
+    }
+
+    pub fn solve(&self, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        let n = self.size();
+        if b.rows as usize != n {
+            return Err(LinalgError::DimensionMismatch {
+                op: "solve",
+                left: (self.lu.rows, self.lu.cols),
+                right: (b.rows, b.cols)
+            });
+        }
+        let k = b.cols as usize;
+        let lu = &self.lu.data;
+        let mut x = Matrix::zeros(b.rows, b.cols);
+        for i in 0..n {
+            let src = self.permutation[i];
+            x.data[i * k..(i + 1) * k].copy_from_slice(&b.data[src * k..(src + 1) * k]);
+        }
+        for col in 0..k {
+            for i in 0..n {
+                let mut sum = x.data[i * k + col];
+                for j in 0..i {
+                    sum -= lu[i * n + j] * x.data[j * k + col];
+                }
+                x.data[i * k + col] = sum;
+            }
+            for i in (0..n).rev() {
+                let mut sum = x.data[i * k + col];
+                for j in i + 1..n {
+                    sum -= lu[i * n + j] * x.data[j * k + col];
+                }
+                x.data[i * k + col] = sum / lu[i * n + i];
+            }
+        }
+        Ok(x)
+    }
+
+    pub fn inverse(&self) -> Matrix<T> {
+        let n = self.lu.rows;
+        self.solve(&Matrix::identity(n)).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<T: Float> Matrix<T> {
+    pub fn lu(&self) -> Result<Lu<T>, LinalgError> {
+        self.check_square()?;
+        let n = self.rows as usize;
+        let mut lu = self.clone();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut sign = T::one();
+        let tolerance = self.singular_tolerance();
+
+        for k in 0..n {
+            let mut pivot = k;
+            for i in k + 1..n {
+                if lu.data[i * n + k].abs() > lu.data[pivot * n + k].abs() {
+                    pivot = i;
+                }
+            }
+            if lu.data[pivot * n + k].abs() <= tolerance {
+                return Err(LinalgError::Singular);
+            }
+            if pivot != k {
+                for j in 0..n {
+                    lu.data.swap(k * n + j, pivot * n + j);
+                }
+                permutation.swap(k, pivot);
+                sign = -sign;
+            }
+            let diagonal = lu.data[k * n + k];
+            for i in k + 1..n {
+                let factor = lu.data[i * n + k] / diagonal;
+                lu.data[i * n + k] = factor;
+                for j in k + 1..n {
+                    let upper = lu.data[k * n + j];
+                    lu.data[i * n + j] -= factor * upper;
+                }
+            }
+        }
+
+        Ok(Lu {
+            lu,
+            permutation,
+            sign
+        })
+    }
+
+    pub fn solve(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        a.lu()?.solve(b)
+    }
+
+    pub fn inverse(&self) -> Result<Matrix<T>, LinalgError> {
+        Ok(self.lu()?.inverse())
+    }
+
+    pub fn determinant(&self) -> Result<T, LinalgError> {
+        match self.lu() {
+            Ok(lu) => Ok(lu.determinant()),
+            Err(LinalgError::Singular) => Ok(T::zero()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Householder reflections; works for any shape.
+    pub fn qr(&self) -> Qr<T> {
+        let (m, n) = (self.rows as usize, self.cols as usize);
+        let two = T::from_f64(2.0);
+        let mut r = self.clone();
+        let mut q = Matrix::identity(self.rows);
+
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let mut v: Vec<T> = (k..m).map(|i| r.data[i * n + k]).collect();
+            let mut norm = T::zero();
+            for value in v.iter() {
+                norm += *value * *value;
+            }
+            let norm = norm.sqrt();
+            if norm == T::zero() {
+                continue;
+            }
+            let alpha = if v[0] > T::zero() { -norm } else { norm };
+            v[0] -= alpha;
+            let mut v_norm = T::zero();
+            for value in v.iter() {
+                v_norm += *value * *value;
+            }
+            if v_norm == T::zero() {
+                continue;
+            }
+
+            for j in k..n {
+                let mut sum = T::zero();
+                for (l, value) in v.iter().enumerate() {
+                    sum += *value * r.data[(k + l) * n + j];
+                }
+                let factor = two * sum / v_norm;
+                for (l, value) in v.iter().enumerate() {
+                    r.data[(k + l) * n + j] -= factor * *value;
+                }
+            }
+            for i in 0..m {
+                let mut sum = T::zero();
+                for (l, value) in v.iter().enumerate() {
+                    sum += q.data[i * m + k + l] * *value;
+                }
+                let factor = two * sum / v_norm;
+                for (l, value) in v.iter().enumerate() {
+                    q.data[i * m + k + l] -= factor * *value;
+                }
+            }
+            for i in k + 1..m {
+                r.data[i * n + k] = T::zero();
+            }
+        }
+
+        Qr { q, r }
+    }
+
+    // Cyclic Jacobi rotations.
+    pub fn symmetric_eigen(&self) -> Result<SymmetricEigen<T>, LinalgError> {
+        self.check_square()?;
+        let n = self.rows as usize;
+        let tolerance = self.singular_tolerance();
+        for i in 0..n {
+            for j in i + 1..n {
+                if (self.data[i * n + j] - self.data[j * n + i]).abs() > tolerance {
+                    return Err(LinalgError::NotSymmetric);
+                }
+            }
+        }
+
+        let mut a = self.clone();
+        let mut v = Matrix::identity(self.rows);
+        let mut total = T::zero();
+        for value in a.data.iter() {
+            total += *value * *value;
+        }
+        let threshold = T::epsilon() * T::epsilon() * total;
+        let mut converged = false;
+
+        for _ in 0..MAX_JACOBI_SWEEPS {
+            let mut off = T::zero();
+            for i in 0..n {
+                for j in i + 1..n {
+                    off += a.data[i * n + j] * a.data[i * n + j];
+                }
+            }
+            if off <= threshold {
+                converged = true;
+                break;
+            }
+
+            for p in 0..n {
+                for q in p + 1..n {
+                    let apq = a.data[p * n + q];
+                    if apq == T::zero() {
+                        continue;
+                    }
+                    let theta = (a.data[q * n + q] - a.data[p * n + p]) / (T::from_f64(2.0) * apq);
+                    let t = if theta >= T::zero() {
+                        T::one() / (theta + (theta * theta + T::one()).sqrt())
+                    } else {
+                        -T::one() / (-theta + (theta * theta + T::one()).sqrt())
+                    };
+                    let c = T::one() / (t * t + T::one()).sqrt();
+                    let s = t * c;
+
+                    for k in 0..n {
+                        let akp = a.data[k * n + p];
+                        let akq = a.data[k * n + q];
+                        a.data[k * n + p] = c * akp - s * akq;
+                        a.data[k * n + q] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let apk = a.data[p * n + k];
+                        let aqk = a.data[q * n + k];
+                        a.data[p * n + k] = c * apk - s * aqk;
+                        a.data[q * n + k] = s * apk + c * aqk;
+                    }
+                    for k in 0..n {
+                        let vkp = v.data[k * n + p];
+                        let vkq = v.data[k * n + q];
+                        v.data[k * n + p] = c * vkp - s * vkq;
+                        v.data[k * n + q] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+
+        if !converged {
+            return Err(LinalgError::NoConvergence { iterations: MAX_JACOBI_SWEEPS });
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|i, j| a.data[*i * n + *i].partial_cmp(&a.data[*j * n + *j]).unwrap_or(std::cmp::Ordering::Equal));
+        let values = order.iter().map(|i| a.data[*i * n + *i]).collect();
+        let mut vectors = Matrix::zeros(self.rows, self.cols);
+        for (new_col, old_col) in order.iter().enumerate() {
+            for k in 0..n {
+                vectors.data[k * n + new_col] = v.data[k * n + *old_col];
+            }
+        }
+
+        Ok(SymmetricEigen { values, vectors })
+    }
+
+    fn check_square(&self) -> Result<(), LinalgError> {
+        if self.rows != self.cols {
+            return Err(LinalgError::NotSquare {
+                rows: self.rows,
+                cols: self.cols
+            });
+        }
+        Ok(())
+    }
+
+    // Pivots at or below this are treated as zero, scaled to the matrix's magnitude.
+    fn singular_tolerance(&self) -> T {
+        let mut scale = T::zero();
+        for value in self.data.iter() {
+            if value.abs() > scale {
+                scale = value.abs();
+            }
+        }
+        T::epsilon() * T::from_f64(self.rows.max(1) as f64) * scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-10;
+
+    fn matrix(rows: u32, cols: u32, data: &[f64]) -> Matrix<f64> {
+        Matrix::from_data(rows, cols, data.to_vec())
+    }
+
+    fn assert_close(actual: &Matrix<f64>, expected: &Matrix<f64>) {
+        assert_eq!((actual.rows, actual.cols), (expected.rows, expected.cols));
+        for (a, e) in actual.data.iter().zip(expected.data.iter()) {
+            assert!((a - e).abs() < TOLERANCE, "{:?} != {:?}", actual.data, expected.data);
+        }
+    }
+
+    // Needs a row swap on the first pivot.
+    fn system() -> Matrix<f64> {
+        matrix(3, 3, &[0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 3.0])
+    }
+
+    #[test]
+    fn solve_satisfies_the_system() {
+        let a = system();
+        let b = matrix(3, 2, &[5.0, 1.0, 5.0, 0.0, 12.0, 2.0]);
+        let x = Matrix::solve(&a, &b).unwrap();
+        assert_close(&Matrix::mul(&a, &x), &b);
+        assert_close(&x.get_col(0), &matrix(3, 1, &[1.0, 1.0, 3.0]));
+    }
+
+    #[test]
+    fn inverse_and_determinant() {
+        let a = system();
+        let inverse = a.inverse().unwrap();
+        assert_close(&Matrix::mul(&a, &inverse), &Matrix::identity(3));
+        assert_close(&Matrix::mul(&inverse, &a), &Matrix::identity(3));
+        assert!((a.determinant().unwrap() - -3.0).abs() < TOLERANCE);
+        let lu = a.lu().unwrap();
+        assert!((lu.determinant() - -3.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn singular_and_non_square_matrices() {
+        let singular = matrix(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 0.0, 1.0, 1.0]);
+        assert!(matches!(singular.lu(), Err(LinalgError::Singular)));
+        assert!(matches!(singular.inverse(), Err(LinalgError::Singular)));
+        assert_eq!(singular.determinant().unwrap(), 0.0);
+        let wide = matrix(2, 3, &[1.0; 6]);
+        assert!(matches!(wide.lu(), Err(LinalgError::NotSquare { rows: 2, cols: 3 })));
+        let b = matrix(2, 1, &[1.0, 2.0]);
+        assert!(matches!(system().lu().unwrap().solve(&b), Err(LinalgError::DimensionMismatch { .. })));
+    }
+
+    #[test]
+    fn qr_is_orthogonal_times_upper_triangular() {
+        let tall = matrix(4, 3, &[12.0, -51.0, 4.0, 6.0, 167.0, -68.0, -4.0, 24.0, -41.0, 1.0, 2.0, 3.0]);
+        for a in [tall, system(), matrix(2, 3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])] {
+            let Qr { q, r } = a.qr();
+            assert_close(&Matrix::mul(&q.transpose(), &q), &Matrix::identity(a.rows));
+            assert_close(&Matrix::mul(&q, &r), &a);
+            for row in 0..r.rows {
+                for col in 0..row.min(r.cols) {
+                    assert_eq!(r.get(row, col), 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eigenvectors_satisfy_av_equals_lambda_v() {
+        let a = matrix(3, 3, &[4.0, 1.0, -2.0, 1.0, 2.0, 0.0, -2.0, 0.0, 3.0]);
+        let SymmetricEigen { values, vectors } = a.symmetric_eigen().unwrap();
+        assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+        for (i, value) in values.iter().enumerate() {
+            let v = vectors.get_col(i as u32);
+            assert_close(&Matrix::mul(&a, &v), &v.scale(*value));
+        }
+        assert_close(&Matrix::mul(&vectors.transpose(), &vectors), &Matrix::identity(3));
+        let trace: f64 = values.iter().sum();
+        assert!((trace - 9.0).abs() < TOLERANCE);
+
+        let known = matrix(2, 2, &[2.0, 1.0, 1.0, 2.0]).symmetric_eigen().unwrap();
+        assert!((known.values[0] - 1.0).abs() < TOLERANCE && (known.values[1] - 3.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn eigen_rejects_non_symmetric_matrices() {
+        let a = matrix(2, 2, &[1.0, 2.0, 3.0, 4.0]);
+        assert!(matches!(a.symmetric_eigen(), Err(LinalgError::NotSymmetric)));
+        assert!(matches!(matrix(1, 2, &[1.0, 2.0]).symmetric_eigen(), Err(LinalgError::NotSquare { .. })));
+    }
+
+    #[test]
+    fn works_in_single_precision() {
+        let a: Matrix<f32> = system().cast();
+        let inverse = a.inverse().unwrap();
+        let product = Matrix::mul(&a, &inverse);
+        let identity: Matrix<f32> = Matrix::identity(3);
+        for (p, i) in product.data.iter().zip(identity.data.iter()) {
+            assert!((p - i).abs() < 1e-5);
+        }
+    }
+}
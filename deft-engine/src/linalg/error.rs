@@ -0,0 +1,85 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinalgError {
+    DimensionMismatch {
+        op: &'static str,
+        left: (u32, u32),
+        right: (u32, u32),
+    },
+    IndexOutOfBounds {
+        row: u32,
+        col: u32,
+        rows: u32,
+        cols: u32,
+    },
+    InvalidBlock {
+        start: (u32, u32),
+        end: (u32, u32),
+        rows: u32,
+        cols: u32,
+    },
+    NotAVector {
+        rows: u32,
+        cols: u32,
+    },
+    DataLength {
+        expected: usize,
+        actual: usize,
+    },
+    NotSquare {
+        rows: u32,
+        cols: u32,
+    },
+    NotSymmetric,
+    Singular,
+    NoConvergence {
+        iterations: usize,
+    },
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinalgError::DimensionMismatch { op, left, right } => write!(
+                f,
+                "Matrix dimensions must match for {}: {}x{} and {}x{}",
+                op, left.0, left.1, right.0, right.1
+            ),
+            LinalgError::IndexOutOfBounds { row, col, rows, cols } => write!(
+                f,
+                "Index ({}, {}) is out of bounds for a {}x{} matrix",
+                row, col, rows, cols
+            ),
+            LinalgError::InvalidBlock { start, end, rows, cols } => write!(
+                f,
+                "Block ({}, {})..({}, {}) does not fit in a {}x{} matrix",
+                start.0, start.1, end.0, end.1, rows, cols
+            ),
+            LinalgError::NotAVector { rows, cols } => write!(
+                f,
+                "Expected a row vector, got a {}x{} matrix",
+                rows, cols
+            ),
+            LinalgError::DataLength { expected, actual } => write!(
+                f,
+                "Expected {} elements of matrix data, got {}",
+                expected, actual
+            ),
+            LinalgError::NotSquare { rows, cols } => write!(
+                f,
+                "Expected a square matrix, got a {}x{} matrix",
+                rows, cols
+            ),
+            LinalgError::NotSymmetric => write!(f, "Matrix is not symmetric"),
+            LinalgError::Singular => write!(f, "Matrix is singular"),
+            LinalgError::NoConvergence { iterations } => write!(
+                f,
+                "Failed to converge after {} iterations",
+                iterations
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LinalgError {}
@@ -0,0 +1,222 @@
+use std::ops::{Add, Mul, MulAssign, Sub};
+
+use crate::linalg::vector::{Vec3, Vec4};
+use crate::linalg::Matrix;
+
+// Row-major: m[row][col], with vectors treated as columns (M * v).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3 {
+    pub m: [[f32; 3]; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f32; 4]; 4],
+}
+
+macro_rules! impl_square_matrix {
+    ($name:ident, $vec:ident, $size:expr) => {
+        impl $name {
+            pub const ZERO: $name = $name { m: [[0.0; $size]; $size] };
+
+            pub const IDENTITY: $name = {
+                let mut m = [[0.0; $size]; $size];
+                let mut i = 0;
+                while i < $size {
+                    m[i][i] = 1.0;
+                    i += 1;
+                }
+                $name { m }
+            };
+
+            pub fn from_rows(rows: [$vec; $size]) -> $name {
+                let mut m = [[0.0; $size]; $size];
+                for i in 0..$size {
+                    m[i] = rows[i].to_array();
+                }
+                $name { m }
+            }
+
+            pub fn from_cols(cols: [$vec; $size]) -> $name {
+                $name::from_rows(cols).transpose()
+            }
+
+            pub fn row(&self, row: usize) -> $vec {
+                $vec::from_array(self.m[row])
+            }
+
+            pub fn col(&self, col: usize) -> $vec {
+                let mut values = [0.0; $size];
+                for i in 0..$size {
+                    values[i] = self.m[i][col];
+                }
+                $vec::from_array(values)
+            }
+
+            pub fn transpose(&self) -> $name {
+                let mut m = [[0.0; $size]; $size];
+                for i in 0..$size {
+                    for j in 0..$size {
+                        m[j][i] = self.m[i][j];
+                    }
+                }
+                $name { m }
+            }
+
+            pub fn to_matrix(&self) -> Matrix<f32> {
+                Matrix::from_data($size, $size, self.m.iter().flatten().copied().collect())
+            }
+
+            pub fn from_matrix(matrix: &Matrix<f32>) -> Option<$name> {
+                if matrix.rows != $size || matrix.cols != $size {
+                    return None;
+                }
+                let mut m = [[0.0; $size]; $size];
+                for i in 0..$size {
+                    for j in 0..$size {
+                        m[i][j] = matrix.get(i as u32, j as u32);
+                    }
+                }
+                Some($name { m })
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name::IDENTITY
+            }
+        }
+
+        impl Mul for $name {
+            type Output = $name;
+
+            fn mul(self, other: $name) -> $name {
+                let mut m = [[0.0; $size]; $size];
+                for i in 0..$size {
+                    for k in 0..$size {
+                        let a_ik = self.m[i][k];
+                        for j in 0..$size {
+                            m[i][j] += a_ik * other.m[k][j];
+                        }
+                    }
+                }
+                $name { m }
+            }
+        }
+
+        impl MulAssign for $name {
+            fn mul_assign(&mut self, other: $name) {
+                *self = *self * other;
+            }
+        }
+
+        impl Mul<$vec> for $name {
+            type Output = $vec;
+
+            fn mul(self, vector: $vec) -> $vec {
+                let mut values = [0.0; $size];
+                for i in 0..$size {
+                    values[i] = self.row(i).dot(vector);
+                }
+                $vec::from_array(values)
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = $name;
+
+            fn mul(self, scalar: f32) -> $name {
+                let mut m = self.m;
+                for row in m.iter_mut() {
+                    for value in row.iter_mut() {
+                        *value *= scalar;
+                    }
+                }
+                $name { m }
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                let mut m = self.m;
+                for i in 0..$size {
+                    for j in 0..$size {
+                        m[i][j] += other.m[i][j];
+                    }
+                }
+                $name { m }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                let mut m = self.m;
+                for i in 0..$size {
+                    for j in 0..$size {
+                        m[i][j] -= other.m[i][j];
+                    }
+                }
+                $name { m }
+            }
+        }
+    };
+}
+
+impl_square_matrix!(Mat3, Vec3, 3);
+impl_square_matrix!(Mat4, Vec4, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Mat3 {
+        Mat3::from_rows([
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(0.0, 1.0, 4.0),
+            Vec3::new(5.0, 6.0, 0.0),
+        ])
+    }
+
+    #[test]
+    fn rows_cols_and_transpose() {
+        let m = sample();
+        assert_eq!(m.row(2), Vec3::new(5.0, 6.0, 0.0));
+        assert_eq!(m.col(2), Vec3::new(3.0, 4.0, 0.0));
+        assert_eq!(m.transpose().row(2), m.col(2));
+        assert_eq!(Mat3::from_cols([m.col(0), m.col(1), m.col(2)]), m);
+        assert_eq!(Mat4::default(), Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn products_treat_vectors_as_columns() {
+        let m = sample();
+        assert_eq!(m * Vec3::new(1.0, 0.0, 0.0), m.col(0));
+        assert_eq!(m * Vec3::new(1.0, 1.0, 1.0), Vec3::new(6.0, 5.0, 11.0));
+        assert_eq!(m * Mat3::IDENTITY, m);
+        assert_eq!(Mat3::IDENTITY * m, m);
+
+        // (A * B) * v == A * (B * v)
+        let n = m.transpose();
+        let v = Vec3::new(2.0, -1.0, 0.5);
+        assert_eq!((m * n) * v, m * (n * v));
+
+        let mut p = m;
+        p *= n;
+        assert_eq!(p, m * n);
+    }
+
+    #[test]
+    fn agrees_with_matrix() {
+        let m = sample();
+        let n = m.transpose() * 2.0;
+        let product = Matrix::mul(&m.to_matrix(), &n.to_matrix());
+        assert_eq!(Mat3::from_matrix(&product), Some(m * n));
+        assert_eq!(Mat3::from_matrix(&(m + n).to_matrix()), Some(m + n));
+        assert_eq!((m + n) - n, m);
+        assert_eq!(Mat4::from_matrix(&m.to_matrix()), None);
+    }
+}
@@ -1,37 +1,82 @@
 use std::vec::Vec;
 
-pub struct Matrix {
+pub mod decomp;
+pub mod error;
+pub mod mat;
+pub mod numeric;
+pub mod ops;
+pub mod par;
+pub mod quat;
+pub mod transform;
+pub mod vector;
+pub mod view;
+
+pub use error::LinalgError;
+pub use mat::{Mat3, Mat4};
+pub use numeric::{Float, Numeric};
+pub use quat::Quat;
+pub use vector::{Vec2, Vec3, Vec4};
+pub use view::{MatrixView, MatrixViewMut};
+
+#[derive(Debug, Clone)]
+pub struct Matrix<T: Numeric> {
     pub rows: u32,
     pub cols: u32,
-    pub data: Vec<u32>
+    pub data: Vec<T>
 }
 
-impl Matrix {
-    pub fn zeros(rows: u32, cols: u32) -> Matrix {
+impl<T: Numeric> Matrix<T> {
+    pub fn zeros(rows: u32, cols: u32) -> Matrix<T> {
         Matrix {
             rows,
             cols,
-            data: vec![0; (rows * cols) as usize]
+            data: vec![T::zero(); (rows * cols) as usize]
         }
     }
 
-    pub fn ones(rows: u32, cols: u32) -> Matrix {
+    pub fn ones(rows: u32, cols: u32) -> Matrix<T> {
         Matrix {
             rows,
             cols,
-            data: vec![1; (rows * cols) as usize]
+            data: vec![T::one(); (rows * cols) as usize]
         }
     }
 
-    pub fn from_data(rows: u32, cols: u32, data: Vec<u32>) -> Matrix {
+    pub fn filled(rows: u32, cols: u32, value: T) -> Matrix<T> {
         Matrix {
             rows,
             cols,
-            data
+            data: vec![value; (rows * cols) as usize]
+        }
+    }
+
+    pub fn identity(size: u32) -> Matrix<T> {
+        let mut output = Matrix::zeros(size, size);
+        for i in 0..size {
+            output.set(i, i, T::one());
         }
+        output
+    }
+
+    pub fn from_data(rows: u32, cols: u32, data: Vec<T>) -> Matrix<T> {
+        Matrix::try_from_data(rows, cols, data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_data(rows: u32, cols: u32, data: Vec<T>) -> Result<Matrix<T>, LinalgError> {
+        if data.len() != (rows * cols) as usize {
+            return Err(LinalgError::DataLength {
+                expected: (rows * cols) as usize,
+                actual: data.len()
+            });
+        }
+        Ok(Matrix {
+            rows,
+            cols,
+            data
+        })
     }
 
-    pub fn print(data: &Matrix) {
+    pub fn print(data: &Matrix<T>) {
         for i in 0..data.rows {
             for j in 0..data.cols {
                 print!("{} ", data.get(i, j));
@@ -40,67 +85,90 @@ impl Matrix {
         }
     }
 
-    pub fn get(&self, row: u32, col: u32) -> u32 {
-        self.data[(row * self.cols + col) as usize]
+    pub fn get(&self, row: u32, col: u32) -> T {
+        self.try_get(row, col).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn set(&mut self, row: u32, col: u32, value: u32) {
-        self.data[(row * self.cols + col) as usize] = value;
+    pub fn set(&mut self, row: u32, col: u32, value: T) {
+        self.try_set(row, col, value).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn get_block(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Matrix {
-        let mut output = Matrix::zeros(end_row - start_row, end_col - start_col);
-        for i in start_row..end_row {
-            for j in start_col..end_col {
-                output.set(i - start_row, j - start_col, self.get(i, j));
-            }
+    pub fn get_checked(&self, row: u32, col: u32) -> Option<T> {
+        if row < self.rows && col < self.cols {
+            Some(self.data[(row * self.cols + col) as usize])
+        } else {
+            None
         }
-        output
     }
 
-    pub fn add_block(&mut self, start_row: u32, start_col: u32, block: &Matrix) {
-        for i in 0..block.rows {
-            for j in 0..block.cols {
-                self.set(i + start_row, j + start_col, self.get(i + start_row, j + start_col) + block.get(i, j));
-            }
-        }
+    pub fn try_get(&self, row: u32, col: u32) -> Result<T, LinalgError> {
+        self.get_checked(row, col).ok_or(self.index_error(row, col))
     }
 
-    pub fn set_block(&mut self, start_row: u32, start_col: u32, block: &Matrix) {
-        for i in 0..block.rows {
-            for j in 0..block.cols {
-                self.set(i + start_row, j + start_col, block.get(i, j));
-            }
+    pub fn try_set(&mut self, row: u32, col: u32, value: T) -> Result<(), LinalgError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(self.index_error(row, col));
         }
+        self.data[(row * self.cols + col) as usize] = value;
+        Ok(())
     }
-    pub fn overlay_block(&mut self, start_row: u32, start_col: u32, block: &Matrix) {
-        for i in 0..block.rows {
-            for j in 0..block.cols {
-                if block.get(i, j) != 0 {
-                    self.set(i + start_row, j + start_col, block.get(i, j));
-                }
-            }
-        }
+
+    pub fn get_block(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Matrix<T> {
+        self.try_get_block(start_row, start_col, end_row, end_col).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_block(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<Matrix<T>, LinalgError> {
+        Ok(self.try_view_block(start_row, start_col, end_row, end_col)?.to_matrix())
+    }
+
+    pub fn add_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) {
+        self.try_add_block(start_row, start_col, block).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) -> Result<(), LinalgError> {
+        self.block_for(start_row, start_col, block)?.add_from(&block.view())
+    }
+
+    pub fn set_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) {
+        self.try_set_block(start_row, start_col, block).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) -> Result<(), LinalgError> {
+        self.block_for(start_row, start_col, block)?.copy_from(&block.view())
     }
 
-    pub fn get_row(&self, row: u32) -> Matrix {
-        let mut output = Matrix::zeros(1, self.cols);
-        for i in 0..self.cols {
-            output.set(0, i, self.get(row, i));
+    pub fn overlay_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) {
+        self.try_overlay_block(start_row, start_col, block).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_overlay_block(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) -> Result<(), LinalgError> {
+        self.block_for(start_row, start_col, block)?.overlay_from(&block.view())
+    }
+
+    pub fn get_row(&self, row: u32) -> Matrix<T> {
+        self.try_get_row(row).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_row(&self, row: u32) -> Result<Matrix<T>, LinalgError> {
+        if row >= self.rows {
+            return Err(self.index_error(row, 0));
         }
-        output
+        Ok(Matrix::from_data(1, self.cols, self.row(row).to_vec()))
     }
 
-    pub fn get_col(&self, col: u32) -> Matrix {
-        let mut output = Matrix::zeros(self.rows, 1);
-        for i in 0..self.rows {
-            output.set(i, 0, self.get(i, col));
+    pub fn get_col(&self, col: u32) -> Matrix<T> {
+        self.try_get_col(col).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_col(&self, col: u32) -> Result<Matrix<T>, LinalgError> {
+        if col >= self.cols {
+            return Err(self.index_error(0, col));
         }
-        output
+        Ok(Matrix::from_data(self.rows, 1, self.col_iter(col).collect()))
     }
 
-    pub fn transpose(&self) -> Matrix {
-        let mut new_data = vec![0; (self.rows * self.cols) as usize];
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut new_data = vec![T::zero(); (self.rows * self.cols) as usize];
         for i in 0..self.rows {
             for j in 0..self.cols {
                 new_data[(j * self.rows + i) as usize] = self.data[(i * self.cols + j) as usize];
@@ -113,78 +181,222 @@ impl Matrix {
         }
     }
 
-    pub fn add(a: &Matrix, b: &Matrix) -> Matrix {
-        if a.rows != b.rows || a.cols != b.cols {
-            panic!("Matrix dimensions must match for addition");
-        }
-        let mut output = Matrix::zeros(a.rows, a.cols);
-        for i in 0..a.rows {
-            for j in 0..a.cols {
-                output.set(i, j, a.get(i, j) + b.get(i, j));
-            }
-        }
-        output
+    pub fn add(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+        Matrix::try_add(a, b).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn sub(a: &Matrix, b: &Matrix) -> Matrix {
-        if a.rows != b.rows || a.cols != b.cols {
-            panic!("Matrix dimensions must match for subtraction");
-        }
-        let mut output = Matrix::zeros(a.rows, a.cols);
-        for i in 0..a.rows {
-            for j in 0..a.cols {
-                output.set(i, j, a.get(i, j) - b.get(i, j));
-            }
-        }
-        output
+    pub fn try_add(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        Matrix::check_same_shape("addition", a, b)?;
+        Ok(Matrix::zip_with(a, b, |x, y| x + y))
     }
 
-    pub fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    pub fn sub(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+        Matrix::try_sub(a, b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_sub(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        Matrix::check_same_shape("subtraction", a, b)?;
+        Ok(Matrix::zip_with(a, b, |x, y| x - y))
+    }
+
+    pub fn hadamard(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+        Matrix::try_hadamard(a, b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_hadamard(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        Matrix::check_same_shape("Hadamard product", a, b)?;
+        Ok(Matrix::zip_with(a, b, |x, y| x * y))
+    }
+
+    pub fn mul(a: &Matrix<T>, b: &Matrix<T>) -> Matrix<T> {
+        Matrix::try_mul(a, b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_mul(a: &Matrix<T>, b: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
         if a.cols != b.rows {
-            panic!("Matrix dimensions must match for multiplication");
+            return Err(LinalgError::DimensionMismatch {
+                op: "multiplication",
+                left: (a.rows, a.cols),
+                right: (b.rows, b.cols)
+            });
         }
+        let (n, m, p) = (a.rows as usize, a.cols as usize, b.cols as usize);
         let mut output = Matrix::zeros(a.rows, b.cols);
-        for i in 0..a.rows {
-            for j in 0..b.cols {
-                output.set(i, j, a.get(i, j) * b.get(i, j));
+        // i-k-j order walks both b and the output row-contiguously
+        par::for_each_row_mut(&mut output.data, p, n, |i, out_row| {
+            for k in 0..m {
+                let a_ik = a.data[i * m + k];
+                let b_row = &b.data[k * p..(k + 1) * p];
+                for (out, b_kj) in out_row.iter_mut().zip(b_row) {
+                    *out += a_ik * *b_kj;
+                }
             }
-        }
-        output
+        });
+        Ok(output)
     }
 
-    pub fn vector_dot(a: Matrix, b: Matrix) -> u32 {
-        if a.rows != 1 || b.rows != 1 {
-            panic!("Vector dot product requires two vectors");
+    pub fn scale(&self, scalar: T) -> Matrix<T> {
+        self.map(|value| value * scalar)
+    }
+
+    pub fn vector_dot(a: Matrix<T>, b: Matrix<T>) -> T {
+        Matrix::try_vector_dot(&a, &b).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_vector_dot(a: &Matrix<T>, b: &Matrix<T>) -> Result<T, LinalgError> {
+        if a.rows != 1 {
+            return Err(LinalgError::NotAVector { rows: a.rows, cols: a.cols });
         }
-        if a.cols != b.cols {
-            panic!("Vector dot product requires vectors of the same length");
+        if b.rows != 1 {
+            return Err(LinalgError::NotAVector { rows: b.rows, cols: b.cols });
         }
-        let mut sum = 0;
-        for i in 0..a.cols {
-            sum += a.get(0, i) * b.get(0, i);
+        Matrix::check_same_shape("vector dot product", a, b)?;
+        let mut sum = T::zero();
+        for (x, y) in a.data.iter().zip(b.data.iter()) {
+            sum += *x * *y;
         }
-        sum
+        Ok(sum)
+    }
+
+    pub fn dot(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_dot(other).unwrap_or_else(|e| panic!("{}", e))
     }
 
-    pub fn dot(&self, other: &Matrix) -> Matrix {
-        if self.cols != other.rows {
-            panic!("Matrix dimensions must match for dot product");
+    pub fn try_dot(&self, other: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+        Matrix::try_mul(self, other)
+    }
+
+    pub fn check_same_shape(op: &'static str, a: &Matrix<T>, b: &Matrix<T>) -> Result<(), LinalgError> {
+        if a.rows != b.rows || a.cols != b.cols {
+            return Err(LinalgError::DimensionMismatch {
+                op,
+                left: (a.rows, a.cols),
+                right: (b.rows, b.cols)
+            });
         }
-        let mut new_data = Matrix::zeros(self.rows, other.cols);
-        for i in 0..self.rows {
-            for j in 0..other.cols {
-                let mut sum = 0;
-                let row = self.get_row(i);
-                let col = other.get_col(j);
-                sum += Matrix::vector_dot(row, col.transpose()) as u32;
-                new_data.set(i, j, sum);
-            }
+        Ok(())
+    }
+
+    fn block_for(&mut self, start_row: u32, start_col: u32, block: &Matrix<T>) -> Result<MatrixViewMut<'_, T>, LinalgError> {
+        self.try_view_block_mut(start_row, start_col, start_row + block.rows, start_col + block.cols)
+    }
+
+    fn index_error(&self, row: u32, col: u32) -> LinalgError {
+        LinalgError::IndexOutOfBounds {
+            row,
+            col,
+            rows: self.rows,
+            cols: self.cols
         }
-        new_data
     }
 
-    pub fn flatten(&self) -> Vec<u32> {
+    fn zip_with<F: Fn(T, T) -> T + Sync + Send>(a: &Matrix<T>, b: &Matrix<T>, f: F) -> Matrix<T> {
+        Matrix {
+            rows: a.rows,
+            cols: a.cols,
+            data: par::zip_collect(&a.data, &b.data, |x, y| f(*x, *y))
+        }
+    }
+
+    pub fn flatten(&self) -> Vec<T> {
         self.data.clone()
     }
-    
-}
\ No newline at end of file
+
+    pub fn map<U: Numeric, F: Fn(T) -> U + Sync + Send>(&self, f: F) -> Matrix<U> {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: par::map_collect(&self.data, |value| f(*value))
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.view_mut().fill(value);
+    }
+
+    pub fn cast<U: Numeric>(&self) -> Matrix<U> {
+        self.map(|value| U::from_f64(value.to_f64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counting(rows: u32, cols: u32) -> Matrix<i32> {
+        Matrix::from_data(rows, cols, (0..(rows * cols) as i32).collect())
+    }
+
+    #[test]
+    fn from_data_checks_length() {
+        assert_eq!(
+            Matrix::try_from_data(2, 3, vec![1, 2, 3]).unwrap_err(),
+            LinalgError::DataLength { expected: 6, actual: 3 }
+        );
+        assert!(Matrix::try_from_data(2, 3, vec![0; 6]).is_ok());
+    }
+
+    #[test]
+    fn out_of_bounds_access_is_an_error() {
+        let mut m = counting(2, 3);
+        assert_eq!(m.try_get(1, 2), Ok(5));
+        assert_eq!(m.try_get(2, 0), Err(LinalgError::IndexOutOfBounds { row: 2, col: 0, rows: 2, cols: 3 }));
+        assert_eq!(m.try_set(0, 3, 9), Err(LinalgError::IndexOutOfBounds { row: 0, col: 3, rows: 2, cols: 3 }));
+        assert_eq!(m.get_checked(5, 5), None);
+        assert!(m.try_get_row(2).is_err());
+        assert!(m.try_get_col(3).is_err());
+        assert_eq!(m.data, counting(2, 3).data);
+    }
+
+    #[test]
+    fn shape_mismatches_are_errors() {
+        let a = counting(2, 3);
+        let b = counting(3, 2);
+        let mismatch = |op| LinalgError::DimensionMismatch { op, left: (2, 3), right: (3, 2) };
+        assert_eq!(Matrix::try_add(&a, &b).unwrap_err(), mismatch("addition"));
+        assert_eq!(Matrix::try_sub(&a, &b).unwrap_err(), mismatch("subtraction"));
+        assert_eq!(Matrix::try_hadamard(&a, &b).unwrap_err(), mismatch("Hadamard product"));
+        assert_eq!(Matrix::try_mul(&a, &a).unwrap_err(), LinalgError::DimensionMismatch {
+            op: "multiplication",
+            left: (2, 3),
+            right: (2, 3)
+        });
+        assert!(Matrix::try_mul(&a, &b).is_ok());
+    }
+
+    #[test]
+    fn vector_dot_requires_row_vectors() {
+        let a = Matrix::from_data(1, 3, vec![1, 2, 3]);
+        let b = Matrix::from_data(1, 3, vec![4, 5, 6]);
+        assert_eq!(Matrix::try_vector_dot(&a, &b), Ok(32));
+        assert_eq!(Matrix::try_vector_dot(&a.transpose(), &b), Err(LinalgError::NotAVector { rows: 3, cols: 1 }));
+        assert!(Matrix::try_vector_dot(&a, &Matrix::from_data(1, 2, vec![1, 2])).is_err());
+    }
+
+    #[test]
+    fn blocks_are_bounds_checked() {
+        let mut m = counting(3, 3);
+        assert_eq!(m.try_get_block(1, 1, 3, 3).unwrap().data, vec![4, 5, 7, 8]);
+        assert_eq!(m.try_get_block(2, 0, 1, 3).unwrap_err(), LinalgError::InvalidBlock {
+            start: (2, 0),
+            end: (1, 3),
+            rows: 3,
+            cols: 3
+        });
+        assert!(m.try_get_block(0, 0, 4, 1).is_err());
+
+        let block = Matrix::ones(2, 2);
+        assert!(m.try_set_block(2, 2, &block).is_err());
+        assert!(m.try_add_block(0, 2, &block).is_err());
+        assert_eq!(m.data, counting(3, 3).data);
+    }
+
+    #[test]
+    fn block_writes() {
+        let mut m = Matrix::<i32>::zeros(3, 3);
+        m.set_block(0, 1, &Matrix::from_data(2, 2, vec![1, 2, 3, 4]));
+        m.add_block(1, 1, &Matrix::ones(2, 2));
+        m.overlay_block(0, 0, &Matrix::from_data(1, 3, vec![9, 0, 9]));
+        assert_eq!(m.data, vec![9, 1, 9, 0, 4, 5, 0, 1, 1]);
+    }
+}
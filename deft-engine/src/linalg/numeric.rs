@@ -0,0 +1,106 @@
+use std::fmt::{Debug, Display};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+pub trait Numeric:
+    Copy
+    + PartialEq
+    + PartialOrd
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Send
+    + Sync
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_numeric {
+    ($t:ty, $zero:expr, $one:expr) => {
+        impl Numeric for $t {
+            fn zero() -> $t {
+                $zero
+            }
+
+            fn one() -> $t {
+                $one
+            }
+
+            fn from_f64(value: f64) -> $t {
+                value as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    };
+}
+
+impl_numeric!(f32, 0.0, 1.0);
+impl_numeric!(f64, 0.0, 1.0);
+impl_numeric!(i32, 0, 1);
+impl_numeric!(u32, 0, 1);
+
+pub trait Float: Numeric + Neg<Output = Self> {
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn epsilon() -> Self;
+    fn is_finite(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($t:ident) => {
+        impl Float for $t {
+            fn sqrt(self) -> $t {
+                $t::sqrt(self)
+            }
+
+            fn abs(self) -> $t {
+                $t::abs(self)
+            }
+
+            fn epsilon() -> $t {
+                $t::EPSILON
+            }
+
+            fn is_finite(self) -> bool {
+                $t::is_finite(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::linalg::Matrix;
+
+    #[test]
+    fn matrices_of_each_element_type() {
+        assert_eq!(Matrix::<u32>::identity(2).data, vec![1, 0, 0, 1]);
+        assert_eq!(Matrix::<i32>::filled(1, 2, -3).scale(2).data, vec![-6, -6]);
+        assert_eq!(Matrix::<f32>::ones(2, 1).transpose().data, vec![1.0, 1.0]);
+        assert_eq!(Matrix::<f64>::zeros(2, 3).data.len(), 6);
+    }
+
+    #[test]
+    fn cast_truncates_towards_zero() {
+        let m = Matrix::from_data(1, 4, vec![1.9f64, -1.9, 2.5, 0.0]);
+        assert_eq!(m.cast::<i32>().data, vec![1, -1, 2, 0]);
+        assert_eq!(m.cast::<u32>().data, vec![1, 0, 2, 0]);
+        assert_eq!(m.cast::<i32>().cast::<f32>().data, vec![1.0, -1.0, 2.0, 0.0]);
+        assert_eq!(Matrix::from_data(1, 2, vec![3u32, 4]).map(|v| v as f64 / 2.0).data, vec![1.5, 2.0]);
+    }
+}
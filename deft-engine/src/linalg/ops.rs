@@ -0,0 +1,235 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use crate::linalg::{Matrix, Numeric};
+
+macro_rules! impl_binary_op {
+    ($trait:ident, $method:ident, $func:path) => {
+        impl<T: Numeric> $trait<Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: Matrix<T>) -> Matrix<T> {
+                $func(&self, &other)
+            }
+        }
+
+        impl<T: Numeric> $trait<&Matrix<T>> for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: &Matrix<T>) -> Matrix<T> {
+                $func(&self, other)
+            }
+        }
+
+        impl<T: Numeric> $trait<Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: Matrix<T>) -> Matrix<T> {
+                $func(self, &other)
+            }
+        }
+
+        impl<T: Numeric> $trait<&Matrix<T>> for &Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: &Matrix<T>) -> Matrix<T> {
+                $func(self, other)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, Matrix::add);
+impl_binary_op!(Sub, sub, Matrix::sub);
+impl_binary_op!(Mul, mul, Matrix::mul);
+
+macro_rules! impl_assign_op {
+    ($trait:ident, $method:ident, $func:path) => {
+        impl<T: Numeric> $trait<Matrix<T>> for Matrix<T> {
+            fn $method(&mut self, other: Matrix<T>) {
+                *self = $func(self, &other);
+            }
+        }
+
+        impl<T: Numeric> $trait<&Matrix<T>> for Matrix<T> {
+            fn $method(&mut self, other: &Matrix<T>) {
+                *self = $func(self, other);
+            }
+        }
+    };
+}
+
+impl_assign_op!(MulAssign, mul_assign, Matrix::mul);
+
+impl<T: Numeric> AddAssign<&Matrix<T>> for Matrix<T> {
+    fn add_assign(&mut self, other: &Matrix<T>) {
+        Matrix::check_same_shape("addition", self, other).unwrap_or_else(|e| panic!("{}", e));
+        for (x, y) in self.data.iter_mut().zip(other.data.iter()) {
+            *x += *y;
+        }
+    }
+}
+
+impl<T: Numeric> AddAssign<Matrix<T>> for Matrix<T> {
+    fn add_assign(&mut self, other: Matrix<T>) {
+        *self += &other;
+    }
+}
+
+impl<T: Numeric> SubAssign<&Matrix<T>> for Matrix<T> {
+    fn sub_assign(&mut self, other: &Matrix<T>) {
+        Matrix::check_same_shape("subtraction", self, other).unwrap_or_else(|e| panic!("{}", e));
+        for (x, y) in self.data.iter_mut().zip(other.data.iter()) {
+            *x -= *y;
+        }
+    }
+}
+
+impl<T: Numeric> SubAssign<Matrix<T>> for Matrix<T> {
+    fn sub_assign(&mut self, other: Matrix<T>) {
+        *self -= &other;
+    }
+}
+
+impl<T: Numeric + Neg<Output = T>> Neg for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Matrix<T> {
+        self.map(|value| -value)
+    }
+}
+
+impl<T: Numeric + Neg<Output = T>> Neg for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn neg(self) -> Matrix<T> {
+        self.map(|value| -value)
+    }
+}
+
+impl<T: Numeric> Mul<T> for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, scalar: T) -> Matrix<T> {
+        self.scale(scalar)
+    }
+}
+
+impl<T: Numeric> Mul<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, scalar: T) -> Matrix<T> {
+        self.scale(scalar)
+    }
+}
+
+impl<T: Numeric> MulAssign<T> for Matrix<T> {
+    fn mul_assign(&mut self, scalar: T) {
+        for value in self.data.iter_mut() {
+            *value *= scalar;
+        }
+    }
+}
+
+impl<T: Numeric> Div<T> for Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn div(self, scalar: T) -> Matrix<T> {
+        self.map(|value| value / scalar)
+    }
+}
+
+impl<T: Numeric> Div<T> for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn div(self, scalar: T) -> Matrix<T> {
+        self.map(|value| value / scalar)
+    }
+}
+
+impl<T: Numeric> DivAssign<T> for Matrix<T> {
+    fn div_assign(&mut self, scalar: T) {
+        for value in self.data.iter_mut() {
+            *value /= scalar;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Textbook i-j-k product to check the i-k-j loop against.
+    fn naive_mul(a: &Matrix<i32>, b: &Matrix<i32>) -> Matrix<i32> {
+        let mut output = Matrix::zeros(a.rows, b.cols);
+        for i in 0..a.rows {
+            for j in 0..b.cols {
+                let mut sum = 0;
+                for k in 0..a.cols {
+                    sum += a.get(i, k) * b.get(k, j);
+                }
+                output.set(i, j, sum);
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn product_of_non_square_matrices() {
+        let a = Matrix::from_data(2, 3, vec![1, 2, 3, 4, 5, 6]);
+        let b = Matrix::from_data(3, 2, vec![7, 8, 9, 10, 11, 12]);
+        let product = &a * &b;
+        assert_eq!((product.rows, product.cols), (2, 2));
+        assert_eq!(product.data, vec![58, 64, 139, 154]);
+
+        let product = &b * &a;
+        assert_eq!((product.rows, product.cols), (3, 3));
+        assert_eq!(product.data, naive_mul(&b, &a).data);
+    }
+
+    #[test]
+    fn product_matches_naive_loop() {
+        let a = Matrix::from_data(4, 5, (0..20).map(|v| v % 7 - 3).collect());
+        let b = Matrix::from_data(5, 3, (0..15).map(|v| v % 5 - 2).collect());
+        assert_eq!(Matrix::mul(&a, &b).data, naive_mul(&a, &b).data);
+        assert_eq!(Matrix::mul(&Matrix::identity(4), &a).data, a.data);
+        assert_eq!(Matrix::mul(&a, &Matrix::identity(5)).data, a.data);
+    }
+
+    #[test]
+    fn hadamard_is_element_wise() {
+        let a = Matrix::from_data(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let b = Matrix::from_data(2, 2, vec![5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(Matrix::hadamard(&a, &b).data, vec![5.0, 12.0, 21.0, 32.0]);
+        assert_eq!((&a * &b).data, vec![19.0, 22.0, 43.0, 50.0]);
+    }
+
+    #[test]
+    fn operators_for_owned_and_borrowed_operands() {
+        let a = Matrix::from_data(2, 2, vec![1, 2, 3, 4]);
+        let b = Matrix::from_data(2, 2, vec![4, 3, 2, 1]);
+        assert_eq!((&a + &b).data, vec![5, 5, 5, 5]);
+        assert_eq!((a.clone() + &b).data, vec![5, 5, 5, 5]);
+        assert_eq!((&a - b.clone()).data, vec![-3, -1, 1, 3]);
+        assert_eq!((a.clone() * b.clone()).data, vec![8, 5, 20, 13]);
+        assert_eq!((-&a).data, vec![-1, -2, -3, -4]);
+        assert_eq!((&a * 3).data, vec![3, 6, 9, 12]);
+        assert_eq!((&a * 3 / 3).data, a.data);
+    }
+
+    #[test]
+    fn assign_operators_match_binary_operators() {
+        let a = Matrix::from_data(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let b = Matrix::from_data(2, 2, vec![0.5, 0.0, 0.0, 2.0]);
+
+        let mut m = a.clone();
+        m += &b;
+        assert_eq!(m.data, (&a + &b).data);
+        m -= b.clone();
+        assert_eq!(m.data, a.data);
+        m *= &b;
+        assert_eq!(m.data, (&a * &b).data);
+        m *= 2.0;
+        m /= 4.0;
+        assert_eq!(m.data, (&a * &b).scale(0.5).data);
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Below this many elements the rayon overhead outweighs the work, so stay serial.
+#[cfg(feature = "parallel")]
+pub const PARALLEL_THRESHOLD: usize = 16 * 1024;
+
+// Calls f(row_index, row) for the first `rows` chunks of `row_len` elements, in
+// parallel when the `parallel` feature is enabled. Each row is handled independently,
+// so results match the serial path exactly.
+pub fn for_each_row_mut<T, F>(data: &mut [T], row_len: usize, rows: usize, f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut [T]) + Sync + Send,
+{
+    if row_len == 0 {
+        return;
+    }
+    #[cfg(feature = "parallel")]
+    if data.len() >= PARALLEL_THRESHOLD {
+        data.par_chunks_mut(row_len).take(rows).enumerate().for_each(|(i, row)| f(i, row));
+        return;
+    }
+    data.chunks_mut(row_len).take(rows).enumerate().for_each(|(i, row)| f(i, row));
+}
+
+pub fn map_collect<T, U, F>(data: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if data.len() >= PARALLEL_THRESHOLD {
+        return data.par_iter().map(f).collect();
+    }
+    data.iter().map(f).collect()
+}
+
+pub fn zip_collect<T, U, F>(a: &[T], b: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T, &T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if a.len() >= PARALLEL_THRESHOLD {
+        return a.par_iter().zip(b.par_iter()).map(|(x, y)| f(x, y)).collect();
+    }
+    a.iter().zip(b.iter()).map(|(x, y)| f(x, y)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linalg::Matrix;
+
+    const ROWS: usize = 300;
+    const COLS: usize = 200;
+    const LEN: usize = ROWS * COLS;
+
+    // Every input here must take the rayon path when the feature is on.
+    #[cfg(feature = "parallel")]
+    const _: () = assert!(LEN >= PARALLEL_THRESHOLD && 160 * 130 >= PARALLEL_THRESHOLD);
+
+    fn input(len: usize, seed: i64) -> Vec<i64> {
+        (0..len as i64).map(|v| (v * 7919 + seed) % 1009 - 504).collect()
+    }
+
+    #[test]
+    fn for_each_row_matches_serial_loop() {
+        let source = input(LEN, 3);
+        let f = |i: usize, row: &mut [i64]| {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = *value * 3 + (i * COLS + j) as i64;
+            }
+        };
+
+        let mut expected = source.clone();
+        for i in 0..ROWS {
+            f(i, &mut expected[i * COLS..(i + 1) * COLS]);
+        }
+        let mut actual = source.clone();
+        for_each_row_mut(&mut actual, COLS, ROWS, f);
+        assert_eq!(actual, expected);
+
+        // Rows past `rows` are left alone.
+        let mut partial = source.clone();
+        for_each_row_mut(&mut partial, COLS, 10, f);
+        assert_eq!(partial[..10 * COLS], expected[..10 * COLS]);
+        assert_eq!(partial[10 * COLS..], source[10 * COLS..]);
+        for_each_row_mut(&mut partial, 0, 10, |_, row| row.fill(0));
+        assert_eq!(partial[10 * COLS..], source[10 * COLS..]);
+    }
+
+    #[test]
+    fn collect_matches_serial_loop() {
+        let a = input(LEN, 1);
+        let b = input(LEN, 2);
+
+        let mut mapped = Vec::new();
+        let mut zipped = Vec::new();
+        for i in 0..LEN {
+            mapped.push(a[i] * a[i] - 1);
+            zipped.push(a[i] * 2 - b[i]);
+        }
+        assert_eq!(map_collect(&a, |v| v * v - 1), mapped);
+        assert_eq!(zip_collect(&a, &b, |x, y| x * 2 - y), zipped);
+    }
+
+    #[test]
+    fn large_matrix_operations_match_serial_loops() {
+        let a = Matrix::from_data(160, 140, input(160 * 140, 5).into_iter().map(|v| v as i32).collect());
+        let b = Matrix::from_data(140, 130, input(140 * 130, 9).into_iter().map(|v| v as i32).collect());
+
+        let product = Matrix::mul(&a, &b);
+        for i in 0..160 {
+            for j in 0..130 {
+                let mut sum = 0;
+                for k in 0..140 {
+                    sum += a.data[i * 140 + k] * b.data[k * 130 + j];
+                }
+                assert_eq!(product.data[i * 130 + j], sum, "product differs at ({}, {})", i, j);
+            }
+        }
+
+        let c = Matrix::from_data(160, 140, input(160 * 140, 11).into_iter().map(|v| v as i32).collect());
+        let sum = Matrix::add(&a, &c);
+        let scaled = a.scale(3);
+        for i in 0..a.data.len() {
+            assert_eq!(sum.data[i], a.data[i] + c.data[i]);
+            assert_eq!(scaled.data[i], a.data[i] * 3);
+        }
+
+        let mut filled = a.clone();
+        filled.view_block_mut(0, 0, 160, 140).fill(7);
+        assert!(filled.data.iter().all(|v| *v == 7));
+    }
+}
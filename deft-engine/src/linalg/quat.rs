@@ -0,0 +1,270 @@
+use std::ops::{Mul, MulAssign, Neg};
+
+use crate::linalg::mat::{Mat3, Mat4};
+use crate::linalg::vector::{Vec3, Vec4};
+
+// Unit quaternions for orientation; the vector part is (x, y, z) and the scalar part w.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Quat {
+        Quat::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Quat = Quat::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Quat {
+        Quat { x, y, z, w }
+    }
+
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Quat {
+        let axis = axis.normalize();
+        let (s, c) = (angle / 2.0).sin_cos();
+        Quat::new(axis.x * s, axis.y * s, axis.z * s, c)
+    }
+
+    // Same convention as Mat3::rotation_euler: x first, then y, then z.
+    pub fn from_euler(x: f32, y: f32, z: f32) -> Quat {
+        Quat::from_axis_angle(Vec3::Z, z) * Quat::from_axis_angle(Vec3::Y, y) * Quat::from_axis_angle(Vec3::X, x)
+    }
+
+    pub fn from_mat3(m: &Mat3) -> Quat {
+        let m = &m.m;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Quat::new((m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s, s / 4.0)
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Quat::new(s / 4.0, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s, (m[2][1] - m[1][2]) / s)
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Quat::new((m[0][1] + m[1][0]) / s, s / 4.0, (m[1][2] + m[2][1]) / s, (m[0][2] - m[2][0]) / s)
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Quat::new((m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, s / 4.0, (m[1][0] - m[0][1]) / s)
+        };
+        q.normalize()
+    }
+
+    pub fn to_axis_angle(self) -> (Vec3, f32) {
+        let q = self.normalize();
+        let angle = 2.0 * q.w.clamp(-1.0, 1.0).acos();
+        let s = (1.0 - q.w * q.w).sqrt();
+        if s < 1e-6 {
+            return (Vec3::X, 0.0);
+        }
+        (Vec3::new(q.x / s, q.y / s, q.z / s), angle)
+    }
+
+    pub fn vector(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    pub fn to_vec4(self) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, self.w)
+    }
+
+    pub fn dot(self, other: Quat) -> f32 {
+        self.to_vec4().dot(other.to_vec4())
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Quat {
+        let length = self.length();
+        if length == 0.0 {
+            return Quat::IDENTITY;
+        }
+        Quat::new(self.x / length, self.y / length, self.z / length, self.w / length)
+    }
+
+    pub fn conjugate(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn inverse(self) -> Quat {
+        let length_squared = self.dot(self);
+        if length_squared == 0.0 {
+            return Quat::IDENTITY;
+        }
+        let c = self.conjugate();
+        Quat::new(c.x / length_squared, c.y / length_squared, c.z / length_squared, c.w / length_squared)
+    }
+
+    pub fn rotate(self, vector: Vec3) -> Vec3 {
+        let u = self.vector();
+        let t = u.cross(vector) * 2.0;
+        vector + t * self.w + u.cross(t)
+    }
+
+    // Normalized linear interpolation, cheaper than slerp for small steps.
+    pub fn nlerp(self, other: Quat, t: f32) -> Quat {
+        let other = if self.dot(other) < 0.0 { -other } else { other };
+        let a = self.to_vec4().lerp(other.to_vec4(), t);
+        Quat::new(a.x, a.y, a.z, a.w).normalize()
+    }
+
+    // Spherical interpolation along the shortest arc.
+    pub fn slerp(self, other: Quat, t: f32) -> Quat {
+        let mut cos_theta = self.dot(other);
+        let mut other = other;
+        if cos_theta < 0.0 {
+            other = -other;
+            cos_theta = -cos_theta;
+        }
+        if cos_theta > 0.9995 {
+            return self.nlerp(other, t);
+        }
+        let theta = cos_theta.acos();
+        let sin_theta = theta.sin();
+        let a = ((1.0 - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        let v = self.to_vec4() * a + other.to_vec4() * b;
+        Quat::new(v.x, v.y, v.z, v.w)
+    }
+
+    pub fn to_mat3(self) -> Mat3 {
+        let Quat { x, y, z, w } = self.normalize();
+        Mat3 {
+            m: [
+                [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+                [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+                [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+            ],
+        }
+    }
+
+    pub fn to_mat4(self) -> Mat4 {
+        Mat4::from_mat3(self.to_mat3())
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, other: Quat) -> Quat {
+        Quat::new(
+            self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        )
+    }
+}
+
+impl MulAssign for Quat {
+    fn mul_assign(&mut self, other: Quat) {
+        *self = *self * other;
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    fn mul(self, vector: Vec3) -> Vec3 {
+        self.rotate(vector)
+    }
+}
+
+impl Neg for Quat {
+    type Output = Quat;
+
+    fn neg(self) -> Quat {
+        Quat::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    const TOLERANCE: f32 = 1e-5;
+
+    fn assert_vec3(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).length() < TOLERANCE, "{:?} != {:?}", actual, expected);
+    }
+
+    // q and -q are the same rotation.
+    fn assert_same_rotation(actual: Quat, expected: Quat) {
+        assert!((actual.dot(expected).abs() - 1.0).abs() < TOLERANCE, "{:?} != {:?}", actual, expected);
+    }
+
+    fn sample() -> Quat {
+        Quat::from_axis_angle(Vec3::new(1.0, -2.0, 0.5), 1.3)
+    }
+
+    #[test]
+    fn rotate_agrees_with_to_mat3() {
+        let q = sample();
+        let m = q.to_mat3();
+        for v in [Vec3::X, Vec3::Y, Vec3::Z, Vec3::new(3.0, -1.0, 2.0)] {
+            assert_vec3(q.rotate(v), m * v);
+            assert_vec3(q * v, m * v);
+        }
+        assert_vec3(Quat::from_axis_angle(Vec3::Z, FRAC_PI_2).rotate(Vec3::X), Vec3::Y);
+        assert_eq!(q.to_mat4().to_mat3(), m);
+    }
+
+    #[test]
+    fn matches_matrix_conventions() {
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_vec3(Quat::from_euler(0.3, -0.7, 1.1).rotate(v), Mat3::rotation_euler(0.3, -0.7, 1.1) * v);
+        let axis = Vec3::new(0.0, 1.0, 1.0);
+        assert_vec3(Quat::from_axis_angle(axis, 0.8).rotate(v), Mat3::rotation_axis_angle(axis, 0.8) * v);
+        assert_same_rotation(Quat::from_mat3(&sample().to_mat3()), sample());
+        // Exercise the non-positive trace branches.
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            let q = Quat::from_axis_angle(axis, PI * 0.9);
+            assert_same_rotation(Quat::from_mat3(&q.to_mat3()), q);
+        }
+    }
+
+    #[test]
+    fn axis_angle_round_trip() {
+        let (axis, angle) = sample().to_axis_angle();
+        assert_vec3(axis, Vec3::new(1.0, -2.0, 0.5).normalize());
+        assert!((angle - 1.3).abs() < TOLERANCE);
+        assert_eq!(Quat::IDENTITY.to_axis_angle(), (Vec3::X, 0.0));
+    }
+
+    #[test]
+    fn composition_and_inverse() {
+        let a = sample();
+        let b = Quat::from_axis_angle(Vec3::Y, -0.4);
+        let v = Vec3::new(0.5, 1.5, -2.0);
+        assert_vec3((a * b).rotate(v), a.rotate(b.rotate(v)));
+        assert_vec3(a.inverse().rotate(a.rotate(v)), v);
+        assert_same_rotation(a * a.inverse(), Quat::IDENTITY);
+
+        let mut c = a;
+        c *= b;
+        assert_eq!(c, a * b);
+        let scaled = Quat::new(a.x * 3.0, a.y * 3.0, a.z * 3.0, a.w * 3.0);
+        assert!((scaled.inverse().length() - 1.0 / 3.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn slerp_endpoints_and_midpoint() {
+        let a = Quat::from_axis_angle(Vec3::Z, 0.2);
+        let b = Quat::from_axis_angle(Vec3::Z, 1.4);
+        assert_same_rotation(a.slerp(b, 0.0), a);
+        assert_same_rotation(a.slerp(b, 1.0), b);
+        assert_same_rotation(a.slerp(b, 0.5), Quat::from_axis_angle(Vec3::Z, 0.8));
+        assert_same_rotation(a.nlerp(b, 0.5), Quat::from_axis_angle(Vec3::Z, 0.8));
+        // Takes the short way round even when b is given with the opposite sign.
+        assert_same_rotation(a.slerp(-b, 0.5), Quat::from_axis_angle(Vec3::Z, 0.8));
+        assert!((a.slerp(b, 0.3).length() - 1.0).abs() < TOLERANCE);
+    }
+}
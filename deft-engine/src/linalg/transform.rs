@@ -0,0 +1,419 @@
+use crate::linalg::mat::{Mat3, Mat4};
+use crate::linalg::vector::{Vec2, Vec3, Vec4};
+
+// Right-handed, column vectors, angles in radians. Projections map to OpenGL-style
+// clip space where the visible depth range is -1..1.
+
+impl Mat3 {
+    pub fn rotation_x(angle: f32) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3 {
+            m: [
+                [1.0, 0.0, 0.0],
+                [0.0, c, -s],
+                [0.0, s, c],
+            ],
+        }
+    }
+
+    pub fn rotation_y(angle: f32) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3 {
+            m: [
+                [c, 0.0, s],
+                [0.0, 1.0, 0.0],
+                [-s, 0.0, c],
+            ],
+        }
+    }
+
+    pub fn rotation_z(angle: f32) -> Mat3 {
+        let (s, c) = angle.sin_cos();
+        Mat3 {
+            m: [
+                [c, -s, 0.0],
+                [s, c, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    // Applies the x rotation first, then y, then z.
+    pub fn rotation_euler(x: f32, y: f32, z: f32) -> Mat3 {
+        Mat3::rotation_z(z) * Mat3::rotation_y(y) * Mat3::rotation_x(x)
+    }
+
+    pub fn rotation_axis_angle(axis: Vec3, angle: f32) -> Mat3 {
+        let Vec3 { x, y, z } = axis.normalize();
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        Mat3 {
+            m: [
+                [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+                [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+                [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+            ],
+        }
+    }
+
+    pub fn scale(scale: Vec3) -> Mat3 {
+        Mat3 {
+            m: [
+                [scale.x, 0.0, 0.0],
+                [0.0, scale.y, 0.0],
+                [0.0, 0.0, scale.z],
+            ],
+        }
+    }
+
+    pub fn translation_2d(offset: Vec2) -> Mat3 {
+        Mat3 {
+            m: [
+                [1.0, 0.0, offset.x],
+                [0.0, 1.0, offset.y],
+                [0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn rotation_2d(angle: f32) -> Mat3 {
+        Mat3::rotation_z(angle)
+    }
+
+    pub fn scale_2d(scale: Vec2) -> Mat3 {
+        Mat3::scale(scale.extend(1.0))
+    }
+
+    pub fn transform_point2(&self, point: Vec2) -> Vec2 {
+        let result = *self * point.extend(1.0);
+        if result.z != 0.0 && result.z != 1.0 {
+            return result.truncate() / result.z;
+        }
+        result.truncate()
+    }
+
+    pub fn transform_vector2(&self, vector: Vec2) -> Vec2 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn inverse(&self) -> Option<Mat3> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let m = &self.m;
+        let inv_det = 1.0 / det;
+        Some(Mat3 {
+            m: [
+                [
+                    (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+                    (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+                    (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+                ],
+                [
+                    (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+                    (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+                    (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+                ],
+                [
+                    (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+                    (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+                    (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+                ],
+            ],
+        })
+    }
+}
+
+impl Mat4 {
+    pub fn from_mat3(rotation: Mat3) -> Mat4 {
+        let mut m = Mat4::IDENTITY.m;
+        for (i, row) in rotation.m.iter().enumerate() {
+            m[i][..3].copy_from_slice(row);
+        }
+        Mat4 { m }
+    }
+
+    pub fn to_mat3(&self) -> Mat3 {
+        let mut m = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            row.copy_from_slice(&self.m[i][..3]);
+        }
+        Mat3 { m }
+    }
+
+    pub fn translation(offset: Vec3) -> Mat4 {
+        Mat4 {
+            m: [
+                [1.0, 0.0, 0.0, offset.x],
+                [0.0, 1.0, 0.0, offset.y],
+                [0.0, 0.0, 1.0, offset.z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn scale(scale: Vec3) -> Mat4 {
+        Mat4::from_mat3(Mat3::scale(scale))
+    }
+
+    pub fn rotation_x(angle: f32) -> Mat4 {
+        Mat4::from_mat3(Mat3::rotation_x(angle))
+    }
+
+    pub fn rotation_y(angle: f32) -> Mat4 {
+        Mat4::from_mat3(Mat3::rotation_y(angle))
+    }
+
+    pub fn rotation_z(angle: f32) -> Mat4 {
+        Mat4::from_mat3(Mat3::rotation_z(angle))
+    }
+
+    pub fn rotation_euler(x: f32, y: f32, z: f32) -> Mat4 {
+        Mat4::from_mat3(Mat3::rotation_euler(x, y, z))
+    }
+
+    pub fn rotation_axis_angle(axis: Vec3, angle: f32) -> Mat4 {
+        Mat4::from_mat3(Mat3::rotation_axis_angle(axis, angle))
+    }
+
+    // View matrix for a camera at eye looking towards target.
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+        let f = (target - eye).normalize();
+        let s = f.cross(up).normalize();
+        let u = s.cross(f);
+        Mat4 {
+            m: [
+                [s.x, s.y, s.z, -s.dot(eye)],
+                [u.x, u.y, u.z, -u.dot(eye)],
+                [-f.x, -f.y, -f.z, f.dot(eye)],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Mat4 {
+        Mat4 {
+            m: [
+                [2.0 / (right - left), 0.0, 0.0, -(right + left) / (right - left)],
+                [0.0, 2.0 / (top - bottom), 0.0, -(top + bottom) / (top - bottom)],
+                [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Mat4 {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        Mat4 {
+            m: [
+                [f / aspect, 0.0, 0.0, 0.0],
+                [0.0, f, 0.0, 0.0],
+                [0.0, 0.0, (far + near) / (near - far), 2.0 * far * near / (near - far)],
+                [0.0, 0.0, -1.0, 0.0],
+            ],
+        }
+    }
+
+    // Applies the full transform including the perspective divide.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let result = *self * point.extend(1.0);
+        if result.w != 0.0 && result.w != 1.0 {
+            return result.truncate() / result.w;
+        }
+        result.truncate()
+    }
+
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        (*self * vector.extend(0.0)).truncate()
+    }
+
+    pub fn get_translation(&self) -> Vec3 {
+        Vec3::new(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let (s, c) = self.sub_determinants();
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+
+    pub fn inverse(&self) -> Option<Mat4> {
+        let (s, c) = self.sub_determinants();
+        let det = s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0];
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let a = &self.m;
+        let inv_det = 1.0 / det;
+        let rows = [
+            Vec4::new(
+                a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+            ),
+            Vec4::new(
+                -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+            ),
+            Vec4::new(
+                a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+            ),
+            Vec4::new(
+                -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+            ),
+        ];
+        Some(Mat4::from_rows(rows) * inv_det)
+    }
+
+    // 2x2 determinants of the top two rows (s) and bottom two rows (c), shared by
+    // determinant and inverse.
+    fn sub_determinants(&self) -> ([f32; 6], [f32; 6]) {
+        let a = &self.m;
+        let s = [
+            a[0][0] * a[1][1] - a[1][0] * a[0][1],
+            a[0][0] * a[1][2] - a[1][0] * a[0][2],
+            a[0][0] * a[1][3] - a[1][0] * a[0][3],
+            a[0][1] * a[1][2] - a[1][1] * a[0][2],
+            a[0][1] * a[1][3] - a[1][1] * a[0][3],
+            a[0][2] * a[1][3] - a[1][2] * a[0][3],
+        ];
+        let c = [
+            a[2][0] * a[3][1] - a[3][0] * a[2][1],
+            a[2][0] * a[3][2] - a[3][0] * a[2][2],
+            a[2][0] * a[3][3] - a[3][0] * a[2][3],
+            a[2][1] * a[3][2] - a[3][1] * a[2][2],
+            a[2][1] * a[3][3] - a[3][1] * a[2][3],
+            a[2][2] * a[3][3] - a[3][2] * a[2][3],
+        ];
+        (s, c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < TOLERANCE
+    }
+
+    fn assert_vec2(actual: Vec2, expected: Vec2) {
+        assert!(close(actual.x, expected.x) && close(actual.y, expected.y), "{:?} != {:?}", actual, expected);
+    }
+
+    fn assert_vec3(actual: Vec3, expected: Vec3) {
+        assert!((actual - expected).length() < TOLERANCE, "{:?} != {:?}", actual, expected);
+    }
+
+    fn assert_identity3(m: Mat3) {
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!(close(m.m[i][j], Mat3::IDENTITY.m[i][j]), "{:?}", m);
+            }
+        }
+    }
+
+    fn assert_identity4(m: Mat4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(close(m.m[i][j], Mat4::IDENTITY.m[i][j]), "{:?}", m);
+            }
+        }
+    }
+
+    #[test]
+    fn transforms_2d_points() {
+        let rotate = Mat3::rotation_2d(FRAC_PI_2);
+        assert_vec2(rotate.transform_point2(Vec2::new(1.0, 0.0)), Vec2::new(0.0, 1.0));
+
+        let translate = Mat3::translation_2d(Vec2::new(3.0, -2.0));
+        assert_vec2(translate.transform_point2(Vec2::new(1.0, 1.0)), Vec2::new(4.0, -1.0));
+        assert_vec2(translate.transform_vector2(Vec2::new(1.0, 1.0)), Vec2::new(1.0, 1.0));
+
+        // Scale, then rotate, then translate.
+        let m = translate * rotate * Mat3::scale_2d(Vec2::new(2.0, 2.0));
+        assert_vec2(m.transform_point2(Vec2::new(1.0, 0.0)), Vec2::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn rotations_about_each_axis() {
+        assert_vec3(Mat3::rotation_x(FRAC_PI_2) * Vec3::Y, Vec3::Z);
+        assert_vec3(Mat3::rotation_y(FRAC_PI_2) * Vec3::Z, Vec3::X);
+        assert_vec3(Mat3::rotation_z(FRAC_PI_2) * Vec3::X, Vec3::Y);
+        assert_vec3(Mat3::rotation_axis_angle(Vec3::new(0.0, 0.0, 2.0), FRAC_PI_2) * Vec3::X, Vec3::Y);
+
+        let euler = Mat3::rotation_euler(0.3, -0.7, 1.1);
+        let composed = Mat3::rotation_z(1.1) * Mat3::rotation_y(-0.7) * Mat3::rotation_x(0.3);
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert_vec3(euler * v, composed * v);
+    }
+
+    #[test]
+    fn determinants() {
+        assert!(close(Mat3::rotation_euler(0.4, 1.2, -2.0).determinant(), 1.0));
+        assert!(close(Mat3::scale(Vec3::new(2.0, 3.0, -4.0)).determinant(), -24.0));
+        assert!(close(Mat4::rotation_axis_angle(Vec3::new(1.0, 1.0, 0.0), 0.8).determinant(), 1.0));
+        assert!(close((Mat4::translation(Vec3::new(5.0, 6.0, 7.0)) * Mat4::scale(Vec3::splat(2.0))).determinant(), 8.0));
+    }
+
+    #[test]
+    fn inverse_times_matrix_is_identity() {
+        let m = Mat3::translation_2d(Vec2::new(4.0, -1.0)) * Mat3::rotation_2d(0.6) * Mat3::scale_2d(Vec2::new(2.0, 0.5));
+        assert_identity3(m.inverse().unwrap() * m);
+        assert_identity3(m * m.inverse().unwrap());
+
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::rotation_euler(0.2, 0.4, 0.8) * Mat4::scale(Vec3::new(1.0, 2.0, 3.0));
+        assert_identity4(m.inverse().unwrap() * m);
+        let p = Vec3::new(-3.0, 0.5, 2.0);
+        assert_vec3(m.inverse().unwrap().transform_point(m.transform_point(p)), p);
+
+        assert_eq!(Mat3::scale(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+        assert_eq!(Mat4::ZERO.inverse(), None);
+    }
+
+    #[test]
+    fn camera_and_projection() {
+        let eye = Vec3::new(0.0, 0.0, 5.0);
+        let view = Mat4::look_at(eye, Vec3::default(), Vec3::Y);
+        assert_vec3(view.transform_point(eye), Vec3::default());
+        // The camera looks down -z.
+        assert_vec3(view.transform_point(Vec3::default()), Vec3::new(0.0, 0.0, -5.0));
+
+        let projection = Mat4::perspective(FRAC_PI_2, 1.0, 1.0, 10.0);
+        assert!(close(projection.transform_point(Vec3::new(0.0, 0.0, -1.0)).z, -1.0));
+        assert!(close(projection.transform_point(Vec3::new(0.0, 0.0, -10.0)).z, 1.0));
+        assert_vec3(projection.transform_point(Vec3::new(2.0, 2.0, -2.0)), Vec3::new(1.0, 1.0, 1.0 / 9.0));
+
+        let ortho = Mat4::orthographic(-2.0, 2.0, -1.0, 1.0, 0.0, 10.0);
+        assert_vec3(ortho.transform_point(Vec3::new(2.0, -1.0, -10.0)), Vec3::new(1.0, -1.0, 1.0));
+        assert_vec3(ortho.transform_point(Vec3::new(-2.0, 1.0, 0.0)), Vec3::new(-1.0, 1.0, -1.0));
+    }
+
+    #[test]
+    fn mat4_keeps_translation_separate() {
+        let rotation = Mat3::rotation_y(0.9);
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::from_mat3(rotation);
+        assert_eq!(m.get_translation(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(m.to_mat3(), rotation);
+        assert_vec3(m.transform_vector(Vec3::X), rotation * Vec3::X);
+    }
+}
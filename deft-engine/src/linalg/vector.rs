@@ -0,0 +1,302 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+macro_rules! impl_vector {
+    ($name:ident, $size:expr, $($field:ident => $index:expr),+) => {
+        impl $name {
+            pub const ZERO: $name = $name { $($field: 0.0),+ };
+            pub const ONE: $name = $name { $($field: 1.0),+ };
+
+            pub const fn new($($field: f32),+) -> $name {
+                $name { $($field),+ }
+            }
+
+            pub const fn splat(value: f32) -> $name {
+                $name { $($field: value),+ }
+            }
+
+            pub fn from_array(values: [f32; $size]) -> $name {
+                $name { $($field: values[$index]),+ }
+            }
+
+            pub fn to_array(self) -> [f32; $size] {
+                [$(self.$field),+]
+            }
+
+            pub fn dot(self, other: $name) -> f32 {
+                0.0 $(+ self.$field * other.$field)+
+            }
+
+            pub fn length_squared(self) -> f32 {
+                self.dot(self)
+            }
+
+            pub fn length(self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(self, other: $name) -> f32 {
+                (other - self).length()
+            }
+
+            // Returns the zero vector unchanged instead of producing NaNs.
+            pub fn normalize(self) -> $name {
+                let length = self.length();
+                if length == 0.0 {
+                    return self;
+                }
+                self / length
+            }
+
+            pub fn lerp(self, other: $name, t: f32) -> $name {
+                self + (other - self) * t
+            }
+
+            // Reflects off a surface with the given unit normal.
+            pub fn reflect(self, normal: $name) -> $name {
+                self - normal * (2.0 * self.dot(normal))
+            }
+
+            pub fn min(self, other: $name) -> $name {
+                $name { $($field: self.$field.min(other.$field)),+ }
+            }
+
+            pub fn max(self, other: $name) -> $name {
+                $name { $($field: self.$field.max(other.$field)),+ }
+            }
+
+            pub fn abs(self) -> $name {
+                $name { $($field: self.$field.abs()),+ }
+            }
+
+            pub fn mul_elements(self, other: $name) -> $name {
+                $name { $($field: self.$field * other.$field),+ }
+            }
+
+            pub fn div_elements(self, other: $name) -> $name {
+                $name { $($field: self.$field / other.$field),+ }
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                $name { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                $name { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl Mul<f32> for $name {
+            type Output = $name;
+
+            fn mul(self, scalar: f32) -> $name {
+                $name { $($field: self.$field * scalar),+ }
+            }
+        }
+
+        impl Mul<$name> for f32 {
+            type Output = $name;
+
+            fn mul(self, vector: $name) -> $name {
+                vector * self
+            }
+        }
+
+        impl Div<f32> for $name {
+            type Output = $name;
+
+            fn div(self, scalar: f32) -> $name {
+                $name { $($field: self.$field / scalar),+ }
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name { $($field: -self.$field),+ }
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: $name) {
+                $(self.$field += other.$field;)+
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: $name) {
+                $(self.$field -= other.$field;)+
+            }
+        }
+
+        impl MulAssign<f32> for $name {
+            fn mul_assign(&mut self, scalar: f32) {
+                $(self.$field *= scalar;)+
+            }
+        }
+
+        impl DivAssign<f32> for $name {
+            fn div_assign(&mut self, scalar: f32) {
+                $(self.$field /= scalar;)+
+            }
+        }
+
+        impl Index<usize> for $name {
+            type Output = f32;
+
+            fn index(&self, index: usize) -> &f32 {
+                match index {
+                    $($index => &self.$field,)+
+                    _ => panic!("Index {} is out of bounds for {}", index, stringify!($name)),
+                }
+            }
+        }
+
+        impl IndexMut<usize> for $name {
+            fn index_mut(&mut self, index: usize) -> &mut f32 {
+                match index {
+                    $($index => &mut self.$field,)+
+                    _ => panic!("Index {} is out of bounds for {}", index, stringify!($name)),
+                }
+            }
+        }
+    };
+}
+
+impl_vector!(Vec2, 2, x => 0, y => 1);
+impl_vector!(Vec3, 3, x => 0, y => 1, z => 2);
+impl_vector!(Vec4, 4, x => 0, y => 1, z => 2, w => 3);
+
+impl Vec2 {
+    // z component of the 3D cross product, positive when other is counter-clockwise from self.
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn perp(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    pub fn extend(self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+}
+
+impl Vec3 {
+    pub const X: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+    pub const Y: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+    pub const Z: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+
+    pub fn cross(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn extend(self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+
+    pub fn truncate(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+}
+
+impl Vec4 {
+    pub fn truncate(self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_products() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(4.0, -5.0, 6.0);
+        assert_eq!(a + b, Vec3::new(5.0, -3.0, 9.0));
+        assert_eq!(a - b, Vec3::new(-3.0, 7.0, -3.0));
+        assert_eq!(2.0 * a, a * 2.0);
+        assert_eq!(-a / 2.0, Vec3::new(-0.5, -1.0, -1.5));
+        assert_eq!(a.dot(b), 12.0);
+        assert_eq!(a.mul_elements(b), Vec3::new(4.0, -10.0, 18.0));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        c *= 2.0;
+        c /= 4.0;
+        assert_eq!(c, b * 0.5);
+    }
+
+    #[test]
+    fn cross_products_follow_the_right_hand_rule() {
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::Y.cross(Vec3::X), -Vec3::Z);
+        let a = Vec3::new(1.0, 2.0, 3.0);
+        let b = Vec3::new(-2.0, 0.5, 4.0);
+        assert_eq!(a.cross(b).dot(a), 0.0);
+        assert_eq!(a.cross(b).dot(b), 0.0);
+
+        assert_eq!(Vec2::new(1.0, 0.0).cross(Vec2::new(0.0, 1.0)), 1.0);
+        assert_eq!(Vec2::new(1.0, 0.0).perp(), Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn length_and_normalize() {
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.normalize(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec4::default().normalize(), Vec4::default());
+        assert_eq!(Vec2::default().distance(v), 5.0);
+    }
+
+    #[test]
+    fn lerp_reflect_and_indexing() {
+        let a = Vec2::new(0.0, 10.0);
+        let b = Vec2::new(10.0, 20.0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.25), Vec2::new(2.5, 12.5));
+        assert_eq!(Vec2::new(1.0, -1.0).reflect(Vec2::new(0.0, 1.0)), Vec2::new(1.0, 1.0));
+
+        let mut v = Vec4::new(1.0, 2.0, 3.0, 4.0);
+        v[3] = 8.0;
+        assert_eq!((v[0], v[3]), (1.0, 8.0));
+        assert_eq!(v.truncate().truncate().extend(5.0).extend(6.0), Vec4::new(1.0, 2.0, 5.0, 6.0));
+        assert_eq!(Vec3::from_array(v.truncate().to_array()), Vec3::new(1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::linalg::{par, LinalgError, Matrix, Numeric};
+
+// Borrowed rectangular window into a row-major buffer. Row i starts at data[i * stride].
+pub struct MatrixView<'a, T: Numeric> {
+    pub data: &'a [T],
+    pub rows: u32,
+    pub cols: u32,
+    pub stride: usize,
+}
+
+pub struct MatrixViewMut<'a, T: Numeric> {
+    pub data: &'a mut [T],
+    pub rows: u32,
+    pub cols: u32,
+    pub stride: usize,
+}
+
+impl<T: Numeric> Clone for MatrixView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Numeric> Copy for MatrixView<'_, T> {}
+
+impl<'a, T: Numeric> MatrixView<'a, T> {
+    pub fn get(&self, row: u32, col: u32) -> T {
+        self.get_checked(row, col).unwrap_or_else(|| panic!("{}", self.index_error(row, col)))
+    }
+
+    pub fn get_checked(&self, row: u32, col: u32) -> Option<T> {
+        if row < self.rows && col < self.cols {
+            Some(self.data[row as usize * self.stride + col as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, row: u32) -> &'a [T] {
+        if self.cols == 0 {
+            return &[];
+        }
+        let start = row as usize * self.stride;
+        &self.data[start..start + self.cols as usize]
+    }
+
+    pub fn rows_iter(&self) -> impl Iterator<Item = &'a [T]> + 'a {
+        let view = *self;
+        (0..view.rows).map(move |row| view.row(row))
+    }
+
+    pub fn col_iter(&self, col: u32) -> impl Iterator<Item = T> + 'a {
+        let view = *self;
+        (0..view.rows).map(move |row| view.get(row, col))
+    }
+
+    pub fn sub_view(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<MatrixView<'a, T>, LinalgError> {
+        check_block(self.rows, self.cols, start_row, start_col, end_row, end_col)?;
+        let (start, len) = block_span(self.stride, start_row, start_col, end_row, end_col);
+        Ok(MatrixView {
+            data: &self.data[start..start + len],
+            rows: end_row - start_row,
+            cols: end_col - start_col,
+            stride: self.stride
+        })
+    }
+
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut data = Vec::with_capacity((self.rows * self.cols) as usize);
+        for row in self.rows_iter() {
+            data.extend_from_slice(row);
+        }
+        Matrix::from_data(self.rows, self.cols, data)
+    }
+
+    fn index_error(&self, row: u32, col: u32) -> LinalgError {
+        LinalgError::IndexOutOfBounds {
+            row,
+            col,
+            rows: self.rows,
+            cols: self.cols
+        }
+    }
+}
+
+impl<'a, T: Numeric> MatrixViewMut<'a, T> {
+    pub fn as_view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            data: self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.stride
+        }
+    }
+
+    pub fn get(&self, row: u32, col: u32) -> T {
+        self.as_view().get(row, col)
+    }
+
+    pub fn set(&mut self, row: u32, col: u32, value: T) {
+        if row >= self.rows || col >= self.cols {
+            panic!("{}", self.as_view().index_error(row, col));
+        }
+        self.data[row as usize * self.stride + col as usize] = value;
+    }
+
+    pub fn row_mut(&mut self, row: u32) -> &mut [T] {
+        let start = row as usize * self.stride;
+        &mut self.data[start..start + self.cols as usize]
+    }
+
+    pub fn rows_iter_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        let cols = self.cols as usize;
+        self.data.chunks_mut(self.stride.max(1)).take(self.rows as usize).map(move |row| &mut row[..cols])
+    }
+
+    pub fn sub_view_mut(&mut self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<MatrixViewMut<'_, T>, LinalgError> {
+        check_block(self.rows, self.cols, start_row, start_col, end_row, end_col)?;
+        let (start, len) = block_span(self.stride, start_row, start_col, end_row, end_col);
+        Ok(MatrixViewMut {
+            data: &mut self.data[start..start + len],
+            rows: end_row - start_row,
+            cols: end_col - start_col,
+            stride: self.stride
+        })
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.for_each_row(|_, row| row.fill(value));
+    }
+
+    // Combines each element with the matching element of `source`, which must have the same shape.
+    pub fn zip_apply<F: Fn(T, T) -> T + Sync + Send>(&mut self, source: &MatrixView<T>, f: F) -> Result<(), LinalgError> {
+        self.check_same_shape(source)?;
+        self.for_each_row(|i, dst_row| {
+            for (dst, src) in dst_row.iter_mut().zip(source.row(i as u32)) {
+                *dst = f(*dst, *src);
+            }
+        });
+        Ok(())
+    }
+
+    pub fn copy_from(&mut self, source: &MatrixView<T>) -> Result<(), LinalgError> {
+        self.check_same_shape(source)?;
+        self.for_each_row(|i, dst_row| dst_row.copy_from_slice(source.row(i as u32)));
+        Ok(())
+    }
+
+    // Row-parallel under the `parallel` feature; rows never alias so the result is the same.
+    pub fn for_each_row<F: Fn(usize, &mut [T]) + Sync + Send>(&mut self, f: F) {
+        let cols = self.cols as usize;
+        if cols == 0 {
+            return;
+        }
+        par::for_each_row_mut(self.data, self.stride, self.rows as usize, |i, row| f(i, &mut row[..cols]));
+    }
+
+    pub fn add_from(&mut self, source: &MatrixView<T>) -> Result<(), LinalgError> {
+        self.zip_apply(source, |dst, src| dst + src)
+    }
+
+    pub fn overlay_from(&mut self, source: &MatrixView<T>) -> Result<(), LinalgError> {
+        self.zip_apply(source, |dst, src| if src != T::zero() { src } else { dst })
+    }
+
+    fn check_same_shape(&self, source: &MatrixView<T>) -> Result<(), LinalgError> {
+        if self.rows != source.rows || self.cols != source.cols {
+            return Err(LinalgError::DimensionMismatch {
+                op: "block operation",
+                left: (self.rows, self.cols),
+                right: (source.rows, source.cols)
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Numeric> Matrix<T> {
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            data: &self.data,
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.cols as usize
+        }
+    }
+
+    pub fn view_mut(&mut self) -> MatrixViewMut<'_, T> {
+        MatrixViewMut {
+            rows: self.rows,
+            cols: self.cols,
+            stride: self.cols as usize,
+            data: &mut self.data
+        }
+    }
+
+    pub fn view_block(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> MatrixView<'_, T> {
+        self.view().sub_view(start_row, start_col, end_row, end_col).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_view_block(&self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<MatrixView<'_, T>, LinalgError> {
+        self.view().sub_view(start_row, start_col, end_row, end_col)
+    }
+
+    pub fn view_block_mut(&mut self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> MatrixViewMut<'_, T> {
+        self.try_view_block_mut(start_row, start_col, end_row, end_col).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_view_block_mut(&mut self, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<MatrixViewMut<'_, T>, LinalgError> {
+        check_block(self.rows, self.cols, start_row, start_col, end_row, end_col)?;
+        let stride = self.cols as usize;
+        let (start, len) = block_span(stride, start_row, start_col, end_row, end_col);
+        Ok(MatrixViewMut {
+            data: &mut self.data[start..start + len],
+            rows: end_row - start_row,
+            cols: end_col - start_col,
+            stride
+        })
+    }
+
+    pub fn row(&self, row: u32) -> &[T] {
+        self.view().row(row)
+    }
+
+    pub fn row_mut(&mut self, row: u32) -> &mut [T] {
+        let start = (row * self.cols) as usize;
+        let end = start + self.cols as usize;
+        &mut self.data[start..end]
+    }
+
+    pub fn rows_iter(&self) -> impl Iterator<Item = &[T]> {
+        self.view().rows_iter()
+    }
+
+    pub fn col_iter(&self, col: u32) -> impl Iterator<Item = T> + '_ {
+        self.view().col_iter(col)
+    }
+}
+
+fn check_block(rows: u32, cols: u32, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> Result<(), LinalgError> {
+    if start_row > end_row || start_col > end_col || end_row > rows || end_col > cols {
+        return Err(LinalgError::InvalidBlock {
+            start: (start_row, start_col),
+            end: (end_row, end_col),
+            rows,
+            cols
+        });
+    }
+    Ok(())
+}
+
+// Offset and length of the slice covering a block, so views never borrow past their last row.
+fn block_span(stride: usize, start_row: u32, start_col: u32, end_row: u32, end_col: u32) -> (usize, usize) {
+    if end_row == start_row || end_col == start_col {
+        return (0, 0);
+    }
+    let start = start_row as usize * stride + start_col as usize;
+    let len = (end_row - start_row - 1) as usize * stride + (end_col - start_col) as usize;
+    (start, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counting(rows: u32, cols: u32) -> Matrix<i32> {
+        Matrix::from_data(rows, cols, (0..(rows * cols) as i32).collect())
+    }
+
+    #[test]
+    fn sub_views_borrow_the_right_elements() {
+        let m = counting(4, 5);
+        let block = m.view_block(1, 2, 3, 5);
+        assert_eq!((block.rows, block.cols, block.stride), (2, 3, 5));
+        assert_eq!(block.row(1), &[12, 13, 14]);
+        assert_eq!(block.col_iter(0).collect::<Vec<_>>(), vec![7, 12]);
+        assert_eq!(block.get_checked(2, 0), None);
+
+        // Nested views keep the parent stride.
+        let inner = block.sub_view(1, 1, 2, 3).unwrap();
+        assert_eq!(inner.to_matrix().data, vec![13, 14]);
+        assert_eq!(m.get_row(3).data, vec![15, 16, 17, 18, 19]);
+        assert_eq!(m.get_col(4).data, vec![4, 9, 14, 19]);
+    }
+
+    #[test]
+    fn last_row_view_does_not_overrun() {
+        let m = counting(3, 4);
+        let corner = m.view_block(2, 3, 3, 4);
+        assert_eq!(corner.data.len(), 1);
+        assert_eq!(corner.to_matrix().data, vec![11]);
+
+        let empty = m.view_block(3, 0, 3, 4);
+        assert_eq!((empty.rows, empty.cols), (0, 4));
+        assert!(empty.to_matrix().data.is_empty());
+    }
+
+    #[test]
+    fn invalid_blocks_are_errors() {
+        let mut m = counting(3, 3);
+        let error = LinalgError::InvalidBlock { start: (0, 2), end: (2, 4), rows: 3, cols: 3 };
+        assert_eq!(m.try_view_block(0, 2, 2, 4).err(), Some(error));
+        assert_eq!(m.try_view_block_mut(0, 2, 2, 4).err(), Some(error));
+        assert!(m.view().sub_view(2, 0, 1, 1).is_err());
+    }
+
+    #[test]
+    fn mutable_views_write_through() {
+        let mut m = counting(3, 4);
+        let source = Matrix::from_data(2, 2, vec![-1, -2, -3, -4]);
+        {
+            let mut block = m.view_block_mut(1, 1, 3, 3);
+            block.copy_from(&source.view()).unwrap();
+            block.set(0, 0, 100);
+        }
+        assert_eq!(m.data, vec![0, 1, 2, 3, 4, 100, -2, 7, 8, -3, -4, 11]);
+
+        let mut block = m.view_block_mut(0, 2, 2, 4);
+        block.fill(0);
+        block.sub_view_mut(1, 1, 2, 2).unwrap().set(0, 0, 9);
+        assert_eq!(m.data, vec![0, 1, 0, 0, 4, 100, 0, 9, 8, -3, -4, 11]);
+    }
+
+    #[test]
+    fn zip_operations_check_shape() {
+        let mut m = Matrix::<i32>::zeros(2, 3);
+        let wide = Matrix::ones(2, 2);
+        let error = LinalgError::DimensionMismatch { op: "block operation", left: (2, 3), right: (2, 2) };
+        assert_eq!(m.view_mut().copy_from(&wide.view()), Err(error));
+        assert_eq!(m.view_mut().add_from(&wide.view()), Err(error));
+
+        let mut block = m.view_block_mut(0, 1, 2, 3);
+        block.add_from(&wide.view()).unwrap();
+        block.overlay_from(&Matrix::from_data(2, 2, vec![0, 5, 6, 0]).view()).unwrap();
+        assert_eq!(m.data, vec![0, 1, 5, 0, 6, 1]);
+        for row in m.view_mut().rows_iter_mut() {
+            row[0] = 7;
+        }
+        assert_eq!(m.col_iter(0).collect::<Vec<_>>(), vec![7, 7]);
+    }
+}
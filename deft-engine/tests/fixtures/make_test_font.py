@@ -0,0 +1,109 @@
+# Writes test-font.ttf, the TrueType font the engine's font tests load. Units are 1000
+# per em with the ascender at 800 and the descender at -200. Glyphs, all straight lines:
+#   .notdef  no outline, advance 500
+#   A        triangle with its point up, advance 600
+#   B        solid rectangle, advance 500
+#   O        square ring, advance 600
+#   V        triangle with its point down, advance 600
+#   space    no outline, advance 250
+# The legacy kern table moves A and V 80 units closer in either order.
+import struct
+
+UNITS_PER_EM = 1000
+ASCENDER, DESCENDER = 800, -200
+
+# (name, character, advance, contours); outer contours clockwise, holes anticlockwise.
+GLYPHS = [
+    (".notdef", None, 500, []),
+    ("A", "A", 600, [[(0, 0), (300, 700), (600, 0)]]),
+    ("B", "B", 500, [[(50, 0), (50, 700), (450, 700), (450, 0)]]),
+    ("O", "O", 600, [
+        [(50, 0), (50, 700), (550, 700), (550, 0)],
+        [(150, 100), (450, 100), (450, 600), (150, 600)],
+    ]),
+    ("V", "V", 600, [[(0, 700), (600, 700), (300, 0)]]),
+    ("space", " ", 250, []),
+]
+KERNING = {("A", "V"): -80, ("V", "A"): -80}
+
+
+def glyph_data(contours):
+    if not contours:
+        return b""
+    points = [point for contour in contours for point in contour]
+    xs, ys = [x for x, _ in points], [y for _, y in points]
+    data = struct.pack(">hhhhh", len(contours), min(xs), min(ys), max(xs), max(ys))
+    end = -1
+    for contour in contours:
+        end += len(contour)
+        data += struct.pack(">H", end)
+    data += struct.pack(">H", 0)
+    data += bytes([0x01] * len(points))
+    for coords in (xs, ys):
+        previous = 0
+        for value in coords:
+            data += struct.pack(">h", value - previous)
+            previous = value
+    return data + b"\0" * (-len(data) % 4)
+
+
+def cmap():
+    mapped = sorted((ord(character), index) for index, (_, character, _, _) in enumerate(GLYPHS) if character)
+    segments = [(code, code, (index - code) % 0x10000) for code, index in mapped] + [(0xFFFF, 0xFFFF, 1)]
+    count = len(segments)
+    search = 2 ** (count.bit_length() - 1)
+    subtable = struct.pack(">HHHHHHH", 4, 16 + 8 * count, 0, 2 * count, 2 * search, search.bit_length() - 1, 2 * count - 2 * search)
+    subtable += b"".join(struct.pack(">H", end) for _, end, _ in segments) + struct.pack(">H", 0)
+    subtable += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    subtable += b"".join(struct.pack(">H", delta) for _, _, delta in segments)
+    subtable += struct.pack(">H", 0) * count
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def kern():
+    index = {name: i for i, (name, _, _, _) in enumerate(GLYPHS)}
+    pairs = sorted((index[left], index[right], value) for (left, right), value in KERNING.items())
+    search = 2 ** (len(pairs).bit_length() - 1)
+    subtable = struct.pack(">HHHH", len(pairs), 6 * search, search.bit_length() - 1, 6 * (len(pairs) - search))
+    subtable += b"".join(struct.pack(">HHh", *pair) for pair in pairs)
+    return struct.pack(">HH", 0, 1) + struct.pack(">HHH", 0, 6 + len(subtable), 0x0001) + subtable
+
+
+def font():
+    glyf, loca = b"", []
+    for _, _, _, contours in GLYPHS:
+        loca.append(len(glyf))
+        glyf += glyph_data(contours)
+    loca.append(len(glyf))
+    points = [point for _, _, _, contours in GLYPHS for contour in contours for point in contour]
+    x_min, x_max = min(x for x, _ in points), max(x for x, _ in points)
+    y_min, y_max = min(y for _, y in points), max(y for _, y in points)
+    advance_max = max(advance for _, _, advance, _ in GLYPHS)
+    tables = {
+        b"cmap": cmap(),
+        b"glyf": glyf,
+        b"head": struct.pack(">IIIIHHqqhhhhHHhhh", 0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM,
+                             0, 0, x_min, y_min, x_max, y_max, 0, 8, 2, 1, 0),
+        b"hhea": struct.pack(">IhhhHhhhhhhhhhhhH", 0x00010000, ASCENDER, DESCENDER, 0, advance_max, 0, 0, x_max,
+                             1, 0, 0, 0, 0, 0, 0, 0, len(GLYPHS)),
+        b"hmtx": b"".join(struct.pack(">Hh", advance, min((x for c in contours for x, _ in c), default=0))
+                          for _, _, advance, contours in GLYPHS),
+        b"kern": kern(),
+        b"loca": b"".join(struct.pack(">I", offset) for offset in loca),
+        b"maxp": struct.pack(">IH", 0x00005000, len(GLYPHS)),
+    }
+    search = 2 ** (len(tables).bit_length() - 1)
+    header = struct.pack(">IHHHH", 0x00010000, len(tables), 16 * search, search.bit_length() - 1, 16 * (len(tables) - search))
+    offset = len(header) + 16 * len(tables)
+    records, body = b"", b""
+    for tag, data in sorted(tables.items()):
+        padded = data + b"\0" * (-len(data) % 4)
+        checksum = sum(struct.unpack(">%dI" % (len(padded) // 4), padded)) & 0xFFFFFFFF
+        records += struct.pack(">4sIII", tag, checksum, offset + len(body), len(data))
+        body += padded
+    return header + records + body
+
+
+if __name__ == "__main__":
+    with open("test-font.ttf", "wb") as file:
+        file.write(font())